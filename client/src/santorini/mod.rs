@@ -344,7 +344,10 @@ impl SantoriniClient {
 
         let player_moving = self.game.players.for_player(self.game.board.state().to_move).clone();
 
-        self.game.board.make_move(&self.board, mve);
+        if let Err(error) = self.game.board.make_move(&self.board, mve) {
+            println!("not playing {} -> {}", self.board.move_notation(mve), error);
+            return self.game.board.match_status(&self.board);
+        }
 
         let match_status = self.game.board.match_status(&self.board);
         
@@ -428,24 +431,36 @@ impl SantoriniClient {
                 match mve {
                     Move::PlaceBuilders { a, b } => {
                         for slot in vec![a, b] {
-                            let v = Self::exact_position(&subtracted_state, slot, units_per_point);
+                            let v = self.exact_position(&subtracted_state, slot, units_per_point);
                             tesselator.color = [1.0, 1.0, 1.0, progress as f32];
                             tesselator.draw_floor_tile_at(trans, &self.atlas.players[subtracted_state.to_move.0 as usize], v, 0.15);
                         }
                     },
-                    Move::Move { from, to, build } => {
-                        let from_position = Self::exact_position(&subtracted_state, from, units_per_point);
-                        let to_position = Self::exact_position(&subtracted_state, to, units_per_point);
-                        let exact_position = from_position.lerp(to_position, progress);
-                        tesselator.color = WHITE.float_raw();
-                        tesselator.draw_floor_tile_at(trans, &self.atlas.players[subtracted_state.to_move.0 as usize], exact_position, 0.15);
+                    Move::Move { from, to, build } |
+                    Move::DoubleBuild { from, to, build, .. } |
+                    Move::Swap { from, to, build } |
+                    Move::Push { from, to, build } |
+                    Move::Dome { from, to, build } |
+                    Move::BuildFirst { from, to, build, .. } |
+                    Move::Travel { from, to, build, .. } => {
+                        let mut walks = vec![(from, to)];
+                        if let Move::Travel { other_from, other_to, .. } = mve {
+                            walks.push((other_from, other_to));
+                        }
+                        for (from, to) in walks {
+                            let from_position = self.exact_position(&subtracted_state, from, units_per_point);
+                            let to_position = self.exact_position(&subtracted_state, to, units_per_point);
+                            let exact_position = from_position.lerp(to_position, progress);
+                            tesselator.color = WHITE.float_raw();
+                            tesselator.draw_floor_tile_at(trans, &self.atlas.players[subtracted_state.to_move.0 as usize], exact_position, 0.15);
+                        }
 
                         // progress for fading in building
                         tesselator.color = [1.0, 1.0, 1.0, progress as f32];
                         let building_height = subtracted_state.get_building_height(build);
                         let is_dome = building_height == 3;
 
-                        let pos = StandardBoard::position(build);
+                        let pos = self.board.position(build);
                         
 
                         if is_dome {
@@ -467,7 +482,7 @@ impl SantoriniClient {
                 if let Some(ref tentative) = self.game.tentative {
                     self.draw_opaques(&tentative.proposed_state, tesselator, opaque, units_per_point);
                     for slot in &tentative.matching_slots {
-                        let pos = StandardBoard::position(*slot);
+                        let pos = self.board.position(*slot);
                         let v = Vec3::new(pos.x as f64, 0.0, pos.y as f64) + BOARD_OFFSET;
                         tesselator.color = next_player_color.float_raw();
                         tesselator.draw_floor_tile_at(trans, &self.atlas.indicator, v, 0.1);
@@ -491,7 +506,7 @@ impl SantoriniClient {
 
         if let Some(ui) = self.game.players.human_ui_state(&self.profile.player) {
             if let Some(slot) = ui.tentative_slot {
-                let position = StandardBoard::position(slot);
+                let position = self.board.position(slot);
                 let v = Vec3::new(position.x as f64, 0.0, position.y as f64) + BOARD_OFFSET;
                 tesselator.color = color::WHITE.float_raw();
                 tesselator.draw_floor_tile_at(opaque, &self.atlas.indicator, v, 0.12);
@@ -499,8 +514,8 @@ impl SantoriniClient {
         }
     }
 
-    pub fn exact_position(state:&State, slot:Slot, units_per_point: f64) -> Vec3 {
-        let pos = StandardBoard::position(slot);
+    pub fn exact_position(&self, state:&State, slot:Slot, units_per_point: f64) -> Vec3 {
+        let pos = self.board.position(slot);
         let mut v = Vec3::new(pos.x as f64, 0.0, pos.y as f64) + BOARD_OFFSET;
         let building_height = state.get_building_height(slot);
         v.y += (BUILDING_PIXEL_OFFSETS[building_height as usize] as f64) * units_per_point;
@@ -510,7 +525,7 @@ impl SantoriniClient {
     pub fn draw_opaques(&self, state: &State, tesselator: &GeometryTesselator, vertices: &mut Vec<Vertex>, units_per_point: f64) {
         // DRAW BOARD CONTENTS
         for &slot in &self.board.slots {
-            let pos = StandardBoard::position(slot);
+            let pos = self.board.position(slot);
             let v = Vec3::new(pos.x as f64, 0.0, pos.y as f64) + BOARD_OFFSET;
 
             let building_height = state.get_building_height(slot);
@@ -532,7 +547,7 @@ impl SantoriniClient {
         for (player_id, builders) in state.builders.iter().enumerate() {
             for slot in builders.iter() {
                 if slot != UNPLACED_BUILDER {
-                    let v = Self::exact_position(state, slot, units_per_point);
+                    let v = self.exact_position(state, slot, units_per_point);
                     tesselator.draw_floor_tile_at(vertices, &self.atlas.players[player_id as usize],  v, 0.15);
                 }
             }
@@ -544,8 +559,13 @@ pub fn subtract(state:&State, mve:Move) -> State {
     let mut new_state = state.clone();
     match mve {
         Move::PlaceBuilders { .. } => (),
-        Move::Move { from, .. } => {
-            new_state = new_state.without_builder_at(from);
+        Move::Travel { from, other_from, .. } => {
+            new_state = new_state.without_builder_at(from).without_builder_at(other_from);
+        },
+        _ => {
+            if let Some((from, _)) = mve.movement() {
+                new_state = new_state.without_builder_at(from);
+            }
         },
     }
     new_state
@@ -0,0 +1,500 @@
+use game::checkers::*;
+use game::*;
+
+use std;
+
+use rand::Rng;
+use rand::XorShiftRng;
+
+#[derive(Debug, Clone)]
+pub struct ZobristHash {
+    pub to_move : [StateHash; 2],
+    pub switch_move : StateHash,
+    pub men : [[StateHash; SQUARE_COUNT]; 2],
+    pub kings : [[StateHash; SQUARE_COUNT]; 2],
+}
+
+impl ZobristHash {
+    pub fn new_unseeded() -> ZobristHash {
+        Self::new(&mut XorShiftRng::new_unseeded())
+    }
+
+    pub fn new<R : Rng>(r: &mut R) -> ZobristHash {
+        let mut hash = ZobristHash {
+            to_move: [STATE_HASH_ZERO; 2],
+            switch_move: STATE_HASH_ZERO,
+            men: [[STATE_HASH_ZERO; SQUARE_COUNT]; 2],
+            kings: [[STATE_HASH_ZERO; SQUARE_COUNT]; 2],
+        };
+
+        for to_move in &mut hash.to_move {
+            *to_move = StateHash(r.next_u64());
+        }
+
+        hash.switch_move = hash.to_move[0] ^ hash.to_move[1];
+
+        for player_hashes in hash.men.iter_mut().chain(hash.kings.iter_mut()) {
+            for i in player_hashes {
+                *i = StateHash(r.next_u64());
+            }
+        }
+
+        hash
+    }
+
+    #[inline]
+    pub fn piece(&self, player_idx: usize, king: bool, slot: Slot) -> StateHash {
+        if king {
+            self.kings[player_idx][slot.0 as usize]
+        } else {
+            self.men[player_idx][slot.0 as usize]
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CheckersBoard {
+    pub steps : [[Option<Slot>; 4]; SQUARE_COUNT], // neighbour in each direction
+    pub jumps : [[Option<(Slot, Slot)>; 4]; SQUARE_COUNT], // (jumped over, landing) in each direction
+    pub hash : ZobristHash,
+}
+
+impl Game for CheckersBoard {
+    type State = State;
+    type Move = Move;
+    type Player = Player;
+    type Undo = State; // captures, crowning and the history make the old state the simplest undo
+
+    fn initial_state(&self) -> State {
+        State::initial()
+    }
+
+    fn to_move(&self, state: &State) -> Player {
+        state.to_move
+    }
+
+    fn next_player(&self, state: &State) -> Player {
+        state.next_player()
+    }
+
+    fn player_index(&self, player: Player) -> usize {
+        player.0 as usize
+    }
+
+    fn color(&self, player: Player) -> HeuristicValue {
+        match player {
+            Player(0) => 1,
+            Player(1) => -1,
+            _ => panic!("checkers only has two players, not {:?}", player),
+        }
+    }
+
+    fn next_moves<T : MoveSink<Move>>(&self, state: &State, move_sink: &mut T) {
+        CheckersBoard::next_moves(self, state, move_sink)
+    }
+
+    fn apply(&self, mve: Move, state: &State) -> State {
+        CheckersBoard::apply(self, mve, state)
+    }
+
+    fn make_move(&self, state: &mut State, mve: Move) -> State {
+        let new_state = CheckersBoard::apply(self, mve, state);
+        std::mem::replace(state, new_state)
+    }
+
+    #[allow(unused_variables)]
+    fn unmake_move(&self, state: &mut State, mve: Move, undo: State) {
+        *state = undo;
+    }
+
+    fn hash(&self, state: &State) -> StateHash {
+        CheckersBoard::hash(self, state)
+    }
+
+    fn delta_hash(&self, state: &State, mve: Move) -> StateHash {
+        CheckersBoard::delta_hash(self, state, mve)
+    }
+
+    // taking the last piece, being blocked in is picked up by no_moves_value
+    fn winning_move(&self, state: &State, mve: Move) -> bool {
+        mve.captured.any() && mve.captured == state.pieces[state.next_player().0 as usize]
+    }
+
+    #[allow(unused_variables)]
+    fn no_moves_value(&self, state: &State) -> HeuristicValue {
+        WORST
+    }
+
+    // repetitions and the move limit depend on the history, which hash leaves out
+    fn transposable(&self, state: &State) -> bool {
+        state.history.is_empty()
+    }
+
+    fn drawn(&self, state: &State) -> bool {
+        CheckersBoard::drawn(self, state)
+    }
+
+    fn print(&self, state: &State) -> String {
+        CheckersBoard::print(state)
+    }
+}
+
+impl CheckersBoard {
+    pub fn new(hash: ZobristHash) -> CheckersBoard {
+        let mut steps = [[None; 4]; SQUARE_COUNT];
+        let mut jumps = [[None; 4]; SQUARE_COUNT];
+
+        for i in 0..SQUARE_COUNT {
+            let position = CheckersBoard::position(Slot(i as i8));
+            for (dir, &delta) in DIRECTIONS.iter().enumerate() {
+                let over = CheckersBoard::slot(position + delta);
+                let land = CheckersBoard::slot(position + delta * 2);
+                steps[i][dir] = over;
+                if let (Some(over), Some(land)) = (over, land) {
+                    jumps[i][dir] = Some((over, land));
+                }
+            }
+        }
+
+        CheckersBoard {
+            steps: steps,
+            jumps: jumps,
+            hash: hash,
+        }
+    }
+
+    pub fn position(slot: Slot) -> Position {
+        let y = slot.0 / 4;
+        let x = (slot.0 % 4) * 2 + if y % 2 == 0 { 1 } else { 0 };
+        Position { x: x, y: y }
+    }
+
+    // None for light squares and anything off the board
+    pub fn slot(pos: Position) -> Option<Slot> {
+        let size = BOARD_SIZE as i8;
+        if pos.x < 0 || pos.x >= size || pos.y < 0 || pos.y >= size || (pos.x + pos.y) % 2 == 0 {
+            None
+        } else {
+            Some(Slot(pos.y * 4 + pos.x / 2))
+        }
+    }
+
+    fn directions(player_idx: usize, king: bool) -> &'static [usize] {
+        if king {
+            &KING_DIRECTIONS
+        } else {
+            &MAN_DIRECTIONS[player_idx]
+        }
+    }
+
+    // captures are forced, a capture has to be followed up while there's another jump, unless a man has just been crowned
+    pub fn next_moves<T : MoveSink<Move>>(&self, state: &State, move_sink: &mut T) {
+        let player_idx = state.to_move.0 as usize;
+        let own = state.pieces[player_idx];
+        let opponents = state.pieces[1 - player_idx];
+        let occupied = state.occupied();
+
+        let mut captures : Vec<Move> = Vec::new();
+
+        for from in own.iter() {
+            let king = state.is_king(from);
+            let vacated = occupied ^ Packed1(1 << from.0);
+            self.jump_chains(player_idx, king, from, from, PACKED1_EMPTY, opponents, vacated, &mut captures);
+        }
+
+        if !captures.is_empty() {
+            for mve in captures {
+                move_sink.sink(mve);
+            }
+            return;
+        }
+
+        for from in own.iter() {
+            for &dir in CheckersBoard::directions(player_idx, state.is_king(from)) {
+                if let Some(to) = self.steps[from.0 as usize][dir] {
+                    if occupied.get(to) == 0 {
+                        move_sink.sink(Move { from: from, to: to, captured: PACKED1_EMPTY });
+                    }
+                }
+            }
+        }
+    }
+
+    // captured pieces stay on the board until the turn is over, they can't be jumped twice or landed on
+    fn jump_chains(&self, player_idx: usize, king: bool, from: Slot, at: Slot, captured: Packed1, opponents: Packed1, occupied: Packed1, out: &mut Vec<Move>) {
+        let mut extended = false;
+
+        for &dir in CheckersBoard::directions(player_idx, king) {
+            if let Some((over, land)) = self.jumps[at.0 as usize][dir] {
+                if opponents.get(over) == 0 || captured.get(over) > 0 || occupied.get(land) > 0 {
+                    continue;
+                }
+                extended = true;
+                let now_captured = captured | Packed1(1 << over.0);
+                if !king && PROMOTION_ROWS[player_idx].get(land) > 0 {
+                    CheckersBoard::push_unique(out, Move { from: from, to: land, captured: now_captured });
+                } else {
+                    self.jump_chains(player_idx, king, from, land, now_captured, opponents, occupied, out);
+                }
+            }
+        }
+
+        if !extended && captured.any() {
+            CheckersBoard::push_unique(out, Move { from: from, to: at, captured: captured });
+        }
+    }
+
+    fn push_unique(out: &mut Vec<Move>, mve: Move) {
+        if !out.contains(&mve) {
+            out.push(mve);
+        }
+    }
+
+    pub fn crowned(state: &State, mve: Move) -> bool {
+        !state.is_king(mve.from) && PROMOTION_ROWS[state.to_move.0 as usize].get(mve.to) > 0
+    }
+
+    pub fn apply(&self, mve: Move, state: &State) -> State {
+        let player_idx = state.to_move.0 as usize;
+        let was_king = state.is_king(mve.from);
+        let from_mask = Packed1(1 << mve.from.0);
+        let to_mask = Packed1(1 << mve.to.0);
+
+        let mut new_state = state.clone();
+
+        // a king can finish a capture loop on the square it started from
+        new_state.pieces[player_idx] = (new_state.pieces[player_idx] & !from_mask) | to_mask;
+        new_state.pieces[1 - player_idx] &= !mve.captured;
+        new_state.kings &= !(mve.captured | from_mask);
+        if was_king || CheckersBoard::crowned(state, mve) {
+            new_state.kings |= to_mask;
+        }
+
+        if was_king && !mve.is_capture() {
+            new_state.history.push(self.hash(state));
+        } else {
+            new_state.history.clear();
+        }
+
+        new_state.to_move = state.next_player();
+        new_state
+    }
+
+    // history isn't part of the hash, the transposition table can't see repetitions so only states without any
+    // are transposable
+    pub fn hash(&self, state: &State) -> StateHash {
+        let mut hash = self.hash.to_move[state.to_move.0 as usize];
+
+        for player_idx in 0..2 {
+            for slot in state.pieces[player_idx].iter() {
+                hash = hash ^ self.hash.piece(player_idx, state.is_king(slot), slot);
+            }
+        }
+
+        hash
+    }
+
+    pub fn delta_hash(&self, state: &State, mve: Move) -> StateHash {
+        let player_idx = state.to_move.0 as usize;
+        let was_king = state.is_king(mve.from);
+        let is_king = was_king || CheckersBoard::crowned(state, mve);
+
+        let mut hash = self.hash.switch_move ^ self.hash.piece(player_idx, was_king, mve.from) ^ self.hash.piece(player_idx, is_king, mve.to);
+
+        for slot in mve.captured.iter() {
+            hash = hash ^ self.hash.piece(1 - player_idx, state.is_king(slot), slot);
+        }
+
+        hash
+    }
+
+    // third repetition of a position, or the move limit without a capture or a man moving
+    pub fn drawn(&self, state: &State) -> bool {
+        if state.history.len() >= MOVE_LIMIT {
+            return true;
+        }
+        if state.history.len() < 4 {
+            return false;
+        }
+
+        let hash = self.hash(state);
+        state.history.iter().filter(|&&h| h == hash).count() >= 2
+    }
+
+    pub fn print(state: &State) -> String {
+        let mut out = String::new();
+
+        out.push_str(&format!(" === To move {:?} === \n", state.to_move));
+
+        for y in 0..BOARD_SIZE {
+            for x in 0..BOARD_SIZE {
+                let c = match CheckersBoard::slot(Position { x: x as i8, y: y as i8 }) {
+                    Some(slot) => {
+                        let king = state.is_king(slot);
+                        if state.pieces[0].get(slot) > 0 {
+                            if king { 'B' } else { 'b' }
+                        } else if state.pieces[1].get(slot) > 0 {
+                            if king { 'W' } else { 'w' }
+                        } else {
+                            '.'
+                        }
+                    },
+                    None => ' ',
+                };
+                out.push(c);
+            }
+            out.push_str("\n");
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> CheckersBoard {
+        CheckersBoard::new(ZobristHash::new_unseeded())
+    }
+
+    fn packed(slots: &[i8]) -> Packed1 {
+        let mut p = PACKED1_EMPTY;
+        for &sl in slots {
+            p.0 |= 1 << sl;
+        }
+        p
+    }
+
+    fn position(black: &[i8], white: &[i8], kings: &[i8]) -> State {
+        State {
+            pieces: [packed(black), packed(white)],
+            kings: packed(kings),
+            to_move: Player(0),
+            history: Vec::new(),
+        }
+    }
+
+    fn moves_for(board: &CheckersBoard, state: &State) -> Vec<Move> {
+        let mut moves = Vec::new();
+        board.next_moves(state, &mut moves);
+        moves
+    }
+
+    #[test]
+    fn geometry() {
+        for i in 0..SQUARE_COUNT {
+            let slot = Slot(i as i8);
+            assert_eq!(CheckersBoard::slot(CheckersBoard::position(slot)), Some(slot));
+        }
+        assert_eq!(CheckersBoard::slot(Position { x: 0, y: 0 }), None);
+    }
+
+    #[test]
+    fn opening_moves() {
+        let board = board();
+        assert_eq!(moves_for(&board, &State::initial()).len(), 7);
+    }
+
+    #[test]
+    fn captures_are_forced() {
+        let board = board();
+        let state = position(&[1, 9], &[14], &[]);
+        assert_eq!(moves_for(&board, &state), vec![Move { from: Slot(9), to: Slot(18), captured: packed(&[14]) }]);
+    }
+
+    #[test]
+    fn multi_jump() {
+        let board = board();
+        let state = position(&[9], &[14, 23], &[]);
+        assert_eq!(moves_for(&board, &state), vec![Move { from: Slot(9), to: Slot(27), captured: packed(&[14, 23]) }]);
+    }
+
+    #[test]
+    fn crowning_ends_the_turn() {
+        let board = board();
+        let state = position(&[21], &[25, 26], &[]);
+        let mve = Move { from: Slot(21), to: Slot(30), captured: packed(&[25]) };
+        assert_eq!(moves_for(&board, &state), vec![mve]);
+
+        let new_state = board.apply(mve, &state);
+        assert!(new_state.is_king(Slot(30)));
+        assert_eq!(new_state.pieces[1], packed(&[26]));
+    }
+
+    #[test]
+    fn delta_hash_matches_hash() {
+        let board = board();
+        let states = vec![State::initial(), position(&[9], &[14, 23], &[]), position(&[21], &[25, 26], &[]), position(&[13], &[17, 26], &[13, 26])];
+
+        for state in states {
+            let hash = board.hash(&state);
+            for mve in moves_for(&board, &state) {
+                let new_state = board.apply(mve, &state);
+                assert_eq!(hash ^ board.delta_hash(&state, mve), board.hash(&new_state), "{:?}", mve);
+            }
+        }
+    }
+
+    #[test]
+    fn repetition_draws() {
+        let board = board();
+        let mut state = position(&[0], &[31], &[0, 31]);
+        let shuffle = [(0, 4), (31, 26), (4, 0), (26, 31)];
+
+        for round in 0..2 {
+            assert!(!board.drawn(&state), "drawn before round {}", round);
+            for &(from, to) in shuffle.iter() {
+                state = board.apply(Move { from: Slot(from), to: Slot(to), captured: PACKED1_EMPTY }, &state);
+            }
+        }
+
+        assert!(board.drawn(&state));
+    }
+
+    #[test]
+    fn move_limit_draws() {
+        let board = board();
+        let mut state = position(&[0], &[31], &[0, 31]);
+        state.history = vec![STATE_HASH_ZERO; MOVE_LIMIT - 1];
+        assert!(!board.drawn(&state));
+        state.history.push(STATE_HASH_ZERO);
+        assert!(board.drawn(&state));
+    }
+
+    #[test]
+    fn table_doesnt_cover_history() {
+        let board = board();
+        let mut state = position(&[0, 1], &[31], &[0, 1, 31]);
+
+        // the table knows it's up a king
+        let mut ev_state = EvState::new();
+        let (best, _) = NegaMaxAlphaBetaExp::evaluate_moves::<MaterialHeuristic>(&mut ev_state, &board, &state, 2);
+        assert!(best.unwrap().1 > 0);
+
+        // but one move from the limit every move draws, whatever's under the same hash
+        state.history = vec![STATE_HASH_ZERO; MOVE_LIMIT - 1];
+        let (best, _) = NegaMaxAlphaBetaExp::evaluate_moves::<MaterialHeuristic>(&mut ev_state, &board, &state, 2);
+        assert_eq!(best.unwrap().1, 0);
+    }
+
+    #[test]
+    fn search_takes_last_piece() {
+        let board = board();
+        let state = position(&[9], &[14], &[]);
+        let mut ev_state = EvState::new();
+        let (best, _) = NegaMaxAlphaBetaExp::evaluate_moves::<MaterialHeuristic>(&mut ev_state, &board, &state, 4);
+        assert_eq!(best, Some((Move { from: Slot(9), to: Slot(18), captured: packed(&[14]) }, victory_in(1))));
+    }
+
+    #[test]
+    fn search_opening() {
+        let board = board();
+        let mut ev_state = EvState::new();
+        for depth in 1..7 {
+            let (best, info) = NegaMaxAlphaBetaExp::evaluate_moves::<MaterialHeuristic>(&mut ev_state, &board, &State::initial(), depth);
+            println!("depth {} best {:?} info {:?}", depth, best, info);
+            assert!(best.is_some());
+        }
+    }
+}
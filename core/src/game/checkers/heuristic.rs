@@ -0,0 +1,33 @@
+use game::checkers::*;
+use game::*;
+
+pub const MAN_SCORE : HeuristicValue = 100;
+pub const KING_SCORE : HeuristicValue = 160;
+pub const ADVANCE_SCORE : HeuristicValue = 2; // per row a man has moved up the board
+
+pub struct MaterialHeuristic {}
+
+impl Heuristic<CheckersBoard> for MaterialHeuristic {
+    fn name() -> String {
+        "MaterialHeuristic".into()
+    }
+
+    #[allow(unused_variables)]
+    fn evaluate(board: &CheckersBoard, state: &State) -> HeuristicValue {
+        let mut n : HeuristicValue = 0;
+
+        for (i, &sign) in [1, -1].iter().enumerate() {
+            let player = Player(i as i8);
+            let kings = state.player_kings(player).count() as HeuristicValue;
+            let mut score = kings * KING_SCORE;
+            for man in state.men(player).iter() {
+                let row = CheckersBoard::position(man).y as HeuristicValue;
+                let advance = if i == 0 { row } else { 7 - row };
+                score += MAN_SCORE + advance * ADVANCE_SCORE;
+            }
+            n += score * sign;
+        }
+
+        n
+    }
+}
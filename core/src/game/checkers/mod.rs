@@ -0,0 +1,49 @@
+pub mod board;
+pub mod state;
+pub mod heuristic;
+
+pub use self::board::*;
+pub use self::state::*;
+pub use self::heuristic::*;
+
+use game::util::*;
+use game::packed::*;
+
+// 32 dark squares of an 8x8 board, square 0 is top left, player 0 (black) starts at the top and moves first
+pub const BOARD_SIZE : usize = 8;
+pub const SQUARE_COUNT : usize = 32;
+
+pub const BLACK_START : Packed1 = Packed1(0x0000_0FFF);
+pub const WHITE_START : Packed1 = Packed1(0xFFF0_0000);
+
+// rows a man of each player is promoted on
+pub const PROMOTION_ROWS : [Packed1; 2] = [Packed1(0xF000_0000), Packed1(0x0000_000F)];
+
+// 40 moves each without a capture or a man moving
+pub const MOVE_LIMIT : usize = 80;
+
+// up left, up right, down left, down right
+pub const DIRECTIONS : [Position; 4] = [
+    Position { x: -1, y: -1 },
+    Position { x: 1, y: -1 },
+    Position { x: -1, y: 1 },
+    Position { x: 1, y: 1 },
+];
+
+// men only move forward, kings use all four
+pub static MAN_DIRECTIONS : [[usize; 2]; 2] = [[2, 3], [0, 1]];
+pub static KING_DIRECTIONS : [usize; 4] = [0, 1, 2, 3];
+
+// a full (possibly multi-jump) turn, from/to/captured is enough to apply it, different jump orders over the same pieces are the same move
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Move {
+    pub from : Slot,
+    pub to : Slot,
+    pub captured : Packed1,
+}
+
+impl Move {
+    pub fn is_capture(&self) -> bool {
+        self.captured.any()
+    }
+}
@@ -0,0 +1,41 @@
+use game::checkers::*;
+use game::*;
+
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub struct State {
+    pub pieces : [Packed1; 2],
+    pub kings : Packed1, // which of the pieces (either side) are kings
+    pub to_move : Player,
+    pub history : Vec<StateHash>, // hashes of the positions since the last capture or man move, for repetition & the move limit
+}
+
+impl State {
+    pub fn initial() -> State {
+        State {
+            pieces: [BLACK_START, WHITE_START],
+            kings: PACKED1_EMPTY,
+            to_move: Player(0),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn occupied(&self) -> Packed1 {
+        self.pieces[0] | self.pieces[1]
+    }
+
+    pub fn next_player(&self) -> Player {
+        Player((self.to_move.0 + 1) % 2)
+    }
+
+    pub fn is_king(&self, slot: Slot) -> bool {
+        self.kings.get(slot) > 0
+    }
+
+    pub fn men(&self, player: Player) -> Packed1 {
+        self.pieces[player.0 as usize] & !self.kings
+    }
+
+    pub fn player_kings(&self, player: Player) -> Packed1 {
+        self.pieces[player.0 as usize] & self.kings
+    }
+}
@@ -0,0 +1,243 @@
+use std;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use time;
+
+use game::*;
+
+pub type MoveCount = u32;
+
+pub type HeuristicValue = i16;
+
+pub type BranchFactor = f64;
+
+pub type Depth = i8;
+
+pub type Symmetry = u8; // which of a game's symmetries, 0 is the identity
+
+// a win the search finds scores VICTORY less the plies from the root until it's over, so it goes for the nearest
+// and puts a loss off for as long as it can. anything at least VICTORY_BOUND either way is decided, that includes
+// the flat BEST / WORST that games and the simpler evaluators give
+pub const VICTORY : HeuristicValue = 32_000;
+pub const VICTORY_BOUND : HeuristicValue = VICTORY - 1_000;
+
+pub const BEST : HeuristicValue = std::i16::MAX;
+pub const WORST : HeuristicValue = -std::i16::MAX; // to prevent overflow on negation
+
+pub const PLAYER_0_WIN : HeuristicValue = std::i16::MAX;
+pub const PLAYER_1_WIN : HeuristicValue = -std::i16::MAX; // to prevent overflow on negation
+
+pub fn victory_in(plies: usize) -> HeuristicValue {
+    VICTORY - std::cmp::min(plies, (VICTORY - VICTORY_BOUND) as usize) as HeuristicValue
+}
+
+// a game's BEST / WORST for the position as it stands (no_moves_value, say) as a win or loss that many plies out
+pub fn distance_scored(value: HeuristicValue, plies: usize) -> HeuristicValue {
+    if value == BEST {
+        victory_in(plies)
+    } else if value == WORST {
+        -victory_in(plies)
+    } else {
+        value
+    }
+}
+
+pub fn decided(value: HeuristicValue) -> bool {
+    value >= VICTORY_BOUND || value <= -VICTORY_BOUND
+}
+
+// plies until a decided value is over, None if it isn't decided or doesn't say (BEST / WORST)
+pub fn plies_to_end(value: HeuristicValue) -> Option<usize> {
+    let value = value.abs();
+    if value >= VICTORY_BOUND && value <= VICTORY {
+        Some((VICTORY - value) as usize)
+    } else {
+        None
+    }
+}
+
+// any win as the flat BEST / WORST, however far off it is
+pub fn without_distance(value: HeuristicValue) -> HeuristicValue {
+    if value >= VICTORY_BOUND {
+        BEST
+    } else if value <= -VICTORY_BOUND {
+        WORST
+    } else {
+        value
+    }
+}
+
+pub trait MoveSink<M> {
+    fn sink(&mut self, mve:M);
+}
+
+impl<M> MoveSink<M> for Vec<M> {
+    fn sink(&mut self, mve:M) {
+        self.push(mve);
+    }
+}
+
+// everything the evaluators, transposition table, perft and playouts need to know about a game
+pub trait Game {
+    type State : Clone + Eq + Hash + Debug;
+    type Move : Copy + Eq + Hash + Debug;
+    type Player : Copy + Eq + Hash + Debug;
+    // whatever unmake_move needs on top of the move to put the state back
+    type Undo;
+
+    fn initial_state(&self) -> Self::State;
+
+    fn to_move(&self, state: &Self::State) -> Self::Player;
+    fn next_player(&self, state: &Self::State) -> Self::Player;
+    fn player_index(&self, player: Self::Player) -> usize;
+    // +1 for the maximizing side, -1 for the minimizing side (negamax)
+    fn color(&self, player: Self::Player) -> HeuristicValue;
+
+    fn next_moves<T : MoveSink<Self::Move>>(&self, state: &Self::State, move_sink: &mut T);
+
+    // one stage's worth of next_moves for StagedMoves, every stage together is exactly next_moves.
+    // games that don't stage put everything in Rest
+    fn next_moves_in_stage<T : MoveSink<Self::Move>>(&self, state: &Self::State, stage: MoveStage, move_sink: &mut T) {
+        if stage == MoveStage::Rest {
+            self.next_moves(state, move_sink);
+        }
+    }
+
+    // for moves that didn't come from next_moves, like the transposition table's
+    fn legal_move(&self, state: &Self::State, mve: Self::Move) -> bool {
+        let mut moves = Vec::new();
+        self.next_moves(state, &mut moves);
+        moves.contains(&mve)
+    }
+    // the squares a move goes from and to, for the history heuristic. games without them don't get history ordering
+    #[allow(unused_variables)]
+    fn history_key(&self, mve: Self::Move) -> Option<(usize, usize)> {
+        None
+    }
+
+    fn apply(&self, mve: Self::Move, state: &Self::State) -> Self::State;
+    // apply in place for the search, unmake_move with the returned undo restores the state exactly
+    fn make_move(&self, state: &mut Self::State, mve: Self::Move) -> Self::Undo;
+    fn unmake_move(&self, state: &mut Self::State, mve: Self::Move, undo: Self::Undo);
+
+    fn hash(&self, state: &Self::State) -> StateHash;
+    fn delta_hash(&self, state: &Self::State, mve: Self::Move) -> StateHash;
+
+    // the same hash for every symmetric copy of a state, and the symmetry taking state to the copy hashed.
+    // games without symmetries just hash
+    fn canonical_hash(&self, state: &Self::State) -> (StateHash, Symmetry) {
+        (self.hash(state), 0)
+    }
+
+    // moves in the canonical copy, for storing, and back out of it
+    #[allow(unused_variables)]
+    fn to_canonical_move(&self, mve: Self::Move, symmetry: Symmetry) -> Self::Move {
+        mve
+    }
+
+    #[allow(unused_variables)]
+    fn from_canonical_move(&self, mve: Self::Move, symmetry: Symmetry) -> Self::Move {
+        mve
+    }
+
+    // the move wins on the spot for the player making it (checked before applying)
+    fn winning_move(&self, state: &Self::State, mve: Self::Move) -> bool;
+
+    // whether player could win on the spot if it were their move, for the search's threat extensions.
+    // games that don't say never extend
+    #[allow(unused_variables)]
+    fn winning_threat(&self, state: &Self::State, player: Self::Player) -> bool {
+        false
+    }
+
+    // value to the player to move when they have no moves at all, santorini is a loss, tic-tac-toe a draw
+    fn no_moves_value(&self, state: &Self::State) -> HeuristicValue;
+
+    // whether the side to move changes every ply. the negamax evaluators flip color each ply and refuse games where
+    // it doesn't (paranoid free for all, two opponents in a row), the minimax evaluators ask color at every node
+    fn alternating(&self) -> bool {
+        true
+    }
+
+    // whether hash covers everything the state's value depends on, so the transposition table can give a value for
+    // it. games whose draws look back at how the position came about say no while that matters
+    #[allow(unused_variables)]
+    fn transposable(&self, state: &Self::State) -> bool {
+        true
+    }
+
+    // drawn regardless of the moves available, move limits etc.
+    #[allow(unused_variables)]
+    fn drawn(&self, state: &Self::State) -> bool {
+        false
+    }
+
+    fn print(&self, state: &Self::State) -> String {
+        format!("{:?}", state)
+    }
+}
+
+pub trait Heuristic<G> where G: Game {
+    fn name() -> String;
+    fn evaluate(game: &G, state: &G::State) -> HeuristicValue;
+}
+
+pub trait Evaluator<G> where G: Game {
+    type EvaluatorState;
+    fn name() -> String;
+    fn new_state() -> Self::EvaluatorState;
+    fn new_search(evaluator_state: &mut Self::EvaluatorState);
+    fn reset(evaluator_state: &mut Self::EvaluatorState);
+
+    fn evaluate_moves<H>(evaluator_state: &mut Self::EvaluatorState, game:&G, state: &G::State, depth: Depth) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        let start_time = time::precise_time_ns();
+        let (best_move, mut info) = Self::evaluate_moves_impl::<H>(evaluator_state, game, state, depth);
+        let duration_seconds = (time::precise_time_ns() - start_time) as f64 / 1_000_000_000f64;
+        info.time += duration_seconds;
+        (best_move, info)
+    }
+    fn evaluate_moves_impl<H>(evaluator_state: &mut Self::EvaluatorState, game:&G, state: &G::State, depth: Depth) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G>;
+
+    // as evaluate_moves, but gives up once the limits are hit with info.aborted set. an aborted result only
+    // covers the moves searched in full before stopping
+    fn evaluate_moves_limited<H>(evaluator_state: &mut Self::EvaluatorState, game:&G, state: &G::State, depth: Depth, limits: &SearchLimits) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        let start_time = time::precise_time_ns();
+        let (best_move, mut info) = Self::evaluate_moves_limited_impl::<H>(evaluator_state, game, state, depth, limits);
+        let duration_seconds = (time::precise_time_ns() - start_time) as f64 / 1_000_000_000f64;
+        info.time += duration_seconds;
+        (best_move, info)
+    }
+
+    // evaluators that can't stop part way through ignore the limits
+    #[allow(unused_variables)]
+    fn evaluate_moves_limited_impl<H>(evaluator_state: &mut Self::EvaluatorState, game:&G, state: &G::State, depth: Depth, limits: &SearchLimits) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        Self::evaluate_moves_impl::<H>(evaluator_state, game, state, depth)
+    }
+
+    // the line the last search expects from state, each move with the score it was searched to (player 0 positive
+    // like best_move), no more than max_length long. evaluators that don't keep a table have nothing to give
+    #[allow(unused_variables)]
+    fn principal_variation(evaluator_state: &Self::EvaluatorState, game:&G, state: &G::State, max_length: Depth) -> Vec<(G::Move, HeuristicValue)> {
+        Vec::new()
+    }
+}
+
+// the manual/crap way, Evaluator::principal_variation reads the line back without searching again
+pub fn principal_variant<G, E, H>(evaluator_state: &mut E::EvaluatorState, game:&G, state:&G::State, depth:Depth) where G: Game, E: Evaluator<G>, H: Heuristic<G> {
+    println!("about to playout {}", game.print(state));
+    let mut current_state = state.clone();
+    for d in (1..(depth+1)).rev() {
+        let (best_move, _) = E::evaluate_moves::<H>(evaluator_state, game, &current_state, d);
+        println!("best move -> {:?}", best_move);
+        if let Some((mve, _)) = best_move {
+            current_state = game.apply(mve, &current_state);
+            let h = H::evaluate(game, &current_state);
+            println!("depth {:?} playing move {:?} score {:?}", d, mve, h);
+            println!("{}", game.print(&current_state));
+        } else {
+            println!("no move at depth {:?}", d);
+        }
+    }
+    // println!("what is shit -> {:?}", shit);
+}
@@ -1,12 +1,12 @@
 
-use game::santorini::*;
+use game::*;
 use std::cmp::{max, min};
 
 pub struct MiniMax {}
 
 
 
-impl Evaluator for MiniMax {
+impl<G> Evaluator<G> for MiniMax where G: Game {
     type EvaluatorState = ();
     fn name() -> String {
         "MiniMax".into()
@@ -19,32 +19,35 @@ impl Evaluator for MiniMax {
     fn reset(evaluator_state: &mut ()) { }
 
     #[allow(unused_variables)]
-    fn evaluate_moves_impl<H>(evaluator_state: &mut (), board: &StandardBoard, state: &State, depth: Depth) -> (Option<(Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic {
-        let mut moves = Vec::with_capacity(200);
+    fn evaluate_moves_impl<H>(evaluator_state: &mut (), board: &G, state: &G::State, depth: Depth) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        let state = &mut state.clone();
+        let mut moves : Vec<G::Move> = Vec::with_capacity(200);
+        let maximizing = board.color(board.to_move(state)) == 1;
         board.next_moves(state, &mut moves);
 
         let mut total_moves = 0;
 
-        let mut unsorted_moves : Vec<(Move, HeuristicValue)> = Vec::new(); 
+        let mut unsorted_moves : Vec<(G::Move, HeuristicValue)> = Vec::new(); 
 
         for &mve in &moves {
-            let v = if board.ascension_winning_move(state, mve) {
+            let v = if board.winning_move(state, mve) {
                 total_moves += 1;
-                if state.to_move == Player(0) {
+                if maximizing {
                     PLAYER_0_WIN
                 } else { 
                     PLAYER_1_WIN
                 }
             } else {
-                let new_state = board.apply(mve, state);
-                let (val, move_count) = MiniMax::eval::<H>(board, &new_state, depth - 1);
+                let undo = board.make_move(state, mve);
+                let (val, move_count) = MiniMax::eval::<G, H>(board, state, depth - 1);
+                board.unmake_move(state, mve, undo);
                 total_moves += move_count;
                 val
             };
             unsorted_moves.push((mve, v));
         }
 
-        if state.to_move == Player(0) {
+        if maximizing {
             unsorted_moves.sort_by_key(|&(_, hv)| -hv); // maximizing player wants biggest first
         } else {
             unsorted_moves.sort_by_key(|&(_, hv)| hv); // minimizing player wants smallest first
@@ -54,28 +57,36 @@ impl Evaluator for MiniMax {
 }
 
 impl MiniMax {
-    pub fn eval<H>(board: &StandardBoard, state: &State, depth: Depth) -> (HeuristicValue, MoveCount) where H: Heuristic {
-        
+    pub fn eval<G, H>(board: &G, state: &mut G::State, depth: Depth) -> (HeuristicValue, MoveCount) where G: Game, H: Heuristic<G> {
+        let color = board.color(board.to_move(state));
+
+        if board.drawn(state) {
+            return (0, 1);
+        }
 
         if depth == 0 {
             return (H::evaluate(board, state), 1);
         }
 
-        let mut moves = Vec::with_capacity(200);
+        let mut moves : Vec<G::Move> = Vec::with_capacity(200);
         board.next_moves(state, &mut moves);
 
+        if moves.is_empty() {
+            return (board.no_moves_value(state) * color, 0);
+        }
 
         let mut total_moves = 0;
 
-        if state.to_move == Player(0) {
+        if color == 1 {
             let mut best_observed = PLAYER_1_WIN; // assume worst case
 
             for &mve in &moves {
-                if board.ascension_winning_move(state, mve) {
+                if board.winning_move(state, mve) {
                     return (PLAYER_0_WIN, total_moves + 1);
                 } else {
-                    let new_state = board.apply(mve, state);
-                    let (v, move_count) = MiniMax::eval::<H>(board, &new_state, depth - 1);
+                    let undo = board.make_move(state, mve);
+                    let (v, move_count) = MiniMax::eval::<G, H>(board, state, depth - 1);
+                    board.unmake_move(state, mve, undo);
                     total_moves += move_count;
                     best_observed = max(v, best_observed);
                 }
@@ -86,11 +97,12 @@ impl MiniMax {
             let mut best_observed = PLAYER_0_WIN; // assume worst cast
 
             for &mve in &moves {
-                if board.ascension_winning_move(state, mve) {
+                if board.winning_move(state, mve) {
                     return (PLAYER_1_WIN, total_moves + 1);
                 } else {
-                    let new_state = board.apply(mve, state);
-                    let (v, move_count) = MiniMax::eval::<H>(board, &new_state, depth - 1);
+                    let undo = board.make_move(state, mve);
+                    let (v, move_count) = MiniMax::eval::<G, H>(board, state, depth - 1);
+                    board.unmake_move(state, mve, undo);
                     best_observed = min(v, best_observed);    
                     total_moves += move_count;
                 }
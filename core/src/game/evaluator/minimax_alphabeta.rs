@@ -1,13 +1,11 @@
 
-use game::santorini::*;
+use game::*;
 use std::cmp::{max, min};
 use rand::{Rng, XorShiftRng, SeedableRng};
 
-pub struct MiniMaxAlphaBeta {
-    board: StandardBoard,
-}
+pub struct MiniMaxAlphaBeta { }
 
-impl Evaluator for MiniMaxAlphaBeta {
+impl<G> Evaluator<G> for MiniMaxAlphaBeta where G: Game {
     type EvaluatorState = ();
     fn name() -> String {
         "MiniMaxAlphaBeta".into()
@@ -20,8 +18,10 @@ impl Evaluator for MiniMaxAlphaBeta {
     fn reset(evaluator_state: &mut ()) { }
      
     #[allow(unused_variables)]
-    fn evaluate_moves_impl<H>(evaluator_state: &mut (), board: &StandardBoard, state: &State, depth: Depth) -> (Option<(Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic {
-        let mut moves = Vec::with_capacity(200);
+    fn evaluate_moves_impl<H>(evaluator_state: &mut (), board: &G, state: &G::State, depth: Depth) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        let state = &mut state.clone();
+        let maximizing = board.color(board.to_move(state)) == 1;
+        let mut moves : Vec<G::Move> = Vec::with_capacity(200);
 
         board.next_moves(state, &mut moves);
 
@@ -29,19 +29,19 @@ impl Evaluator for MiniMaxAlphaBeta {
         rand.shuffle(&mut moves);
 
         let mut total_moves = 0;
-        let mut unsorted_moves : Vec<(Move, HeuristicValue)> = Vec::with_capacity(200);
+        let mut unsorted_moves : Vec<(G::Move, HeuristicValue)> = Vec::with_capacity(200);
 
         let mut alpha = WORST;
         let mut beta = BEST;
 
         for &mve in &moves {
-            if board.ascension_winning_move(state, mve) {
-                let v = if state.to_move == Player(0) {
+            if board.winning_move(state, mve) {
+                let v = if maximizing {
                     PLAYER_0_WIN
                 } else { 
                     PLAYER_1_WIN
                 };
-                if state.to_move == Player(0) {
+                if maximizing {
                     alpha = max(alpha, PLAYER_0_WIN);
                 } else {
                     beta = min(beta, PLAYER_1_WIN);
@@ -50,11 +50,11 @@ impl Evaluator for MiniMaxAlphaBeta {
                 unsorted_moves.push((mve, v));
                 total_moves += 1;
             } else {
-                let new_state = board.apply(mve, state);
-
-                let (val, move_count) = Self::eval::<H>(board, &new_state, depth - 1, alpha, beta);
+                let undo = board.make_move(state, mve);
+                let (val, move_count) = Self::eval::<G, H>(board, state, depth - 1, alpha, beta);
+                board.unmake_move(state, mve, undo);
 
-                if state.to_move == Player(0) {
+                if maximizing {
                     // maximizing pass
                     alpha = max(alpha, val);
                 } else {
@@ -69,7 +69,7 @@ impl Evaluator for MiniMaxAlphaBeta {
 
         // println!("mmab dunzo");
 
-        if state.to_move == Player(0) {
+        if maximizing {
             unsorted_moves.sort_by_key(|&(_, hv)| -hv); // maximizing player wants biggest first
         } else {
             unsorted_moves.sort_by_key(|&(_, hv)| hv); // minimizing player wants smallest first
@@ -80,30 +80,41 @@ impl Evaluator for MiniMaxAlphaBeta {
 }
 
 impl MiniMaxAlphaBeta {
-    pub fn eval<H>(board: &StandardBoard, state: &State, depth: Depth, alpha: HeuristicValue, beta:HeuristicValue) -> (HeuristicValue, MoveCount) where H: Heuristic {
+    pub fn eval<G, H>(board: &G, state: &mut G::State, depth: Depth, alpha: HeuristicValue, beta:HeuristicValue) -> (HeuristicValue, MoveCount) where G: Game, H: Heuristic<G> {
+        let color = board.color(board.to_move(state));
+
+        if board.drawn(state) {
+            return (0, 1);
+        }
+
         if depth == 0 {
             return (H::evaluate(board, state), 1);
         }
 
-        let mut moves = Vec::with_capacity(200);
+        let mut moves : Vec<G::Move> = Vec::with_capacity(200);
         board.next_moves(state, &mut moves);
         moves.reverse();
 
+        if moves.is_empty() {
+            return (board.no_moves_value(state) * color, 0);
+        }
+
         let mut total_moves = 0;
 
-        if state.to_move == Player(0) {
+        if color == 1 {
 
             let mut new_alpha = alpha;
             let mut best_observed = PLAYER_1_WIN; // assume worst case
 
 
             for &mve in &moves {
-                if board.ascension_winning_move(state, mve) {
+                if board.winning_move(state, mve) {
                     return (PLAYER_0_WIN, total_moves + 1);
                 } else {
          
-                    let new_state = board.apply(mve, state);
-                    let (v, move_count) = Self::eval::<H>(board, &new_state, depth - 1, new_alpha, beta);
+                    let undo = board.make_move(state, mve);
+                    let (v, move_count) = Self::eval::<G, H>(board, state, depth - 1, new_alpha, beta);
+                    board.unmake_move(state, mve, undo);
                     new_alpha = max(v, new_alpha);
                     total_moves += move_count;
                     best_observed = max(v, best_observed);
@@ -119,11 +130,12 @@ impl MiniMaxAlphaBeta {
             let mut best_observed = PLAYER_0_WIN; // assume worst cast
 
             for &mve in &moves {
-                if board.ascension_winning_move(state, mve) {
+                if board.winning_move(state, mve) {
                     return (PLAYER_1_WIN, total_moves + 1);
                 } else {
-                    let new_state = board.apply(mve, state);
-                    let (v, move_count) = Self::eval::<H>(board, &new_state, depth - 1, alpha, new_beta);
+                    let undo = board.make_move(state, mve);
+                    let (v, move_count) = Self::eval::<G, H>(board, state, depth - 1, alpha, new_beta);
+                    board.unmake_move(state, mve, undo);
                     best_observed = min(v, best_observed);    
                     new_beta = min(new_beta, v);
                     total_moves += move_count;
@@ -0,0 +1,86 @@
+
+use game::*;
+use std::cmp::max;
+
+
+pub struct NegaMax { }
+
+
+impl<G> Evaluator<G> for NegaMax where G: Game {
+    type EvaluatorState = ();
+
+    fn name() -> String {
+        "NegaMax".into()
+    }
+
+    fn new_state() -> () { () }
+    #[allow(unused_variables)]
+    fn new_search(evaluator_state: &mut ()) { }
+    #[allow(unused_variables)]
+    fn reset(evaluator_state: &mut ()) { }
+
+    #[allow(unused_variables)]
+    fn evaluate_moves_impl<H>(evaluator_state: &mut (), board: &G, state: &G::State, depth: Depth) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        assert!(board.alternating(), "NegaMax flips color every ply, this game needs a minimax evaluator");
+        let state = &mut state.clone(); // made and unmade in place from here down
+        let color = board.color(board.to_move(state));
+        let mut moves : Vec<G::Move> = Vec::with_capacity(200);
+
+        board.next_moves(state, &mut moves);
+
+        let mut total_moves = 0;
+        let mut unsorted_moves : Vec<(G::Move, HeuristicValue)> = Vec::with_capacity(200);
+
+        for &mve in &moves {
+            let v = if board.winning_move(state, mve) {
+                total_moves += 1;
+                BEST * color
+            } else {
+                let undo = board.make_move(state, mve);
+                let (v, move_count) = NegaMax::eval::<G, H>(board, state, depth - 1, -color);
+                board.unmake_move(state, mve, undo);
+                total_moves += move_count;
+                v * -color
+            };
+            unsorted_moves.push((mve, v));
+        }
+        unsorted_moves.sort_by_key(|&(_, hv)| hv * -color);
+        (unsorted_moves.first().cloned(), EvaluatorInfo::from_moves_depth(total_moves, depth))
+    }
+}
+
+impl NegaMax {
+    pub fn eval<G, H>(board: &G, state: &mut G::State, depth: Depth, color: HeuristicValue) -> (HeuristicValue, MoveCount) where G: Game, H: Heuristic<G> {
+        if board.drawn(state) {
+            return (0, 1);
+        }
+
+        if depth == 0 {
+            return (H::evaluate(board, state) * color, 1);
+        }
+        
+        let mut moves : Vec<G::Move> = Vec::with_capacity(200); // enough to prevent resizing
+        board.next_moves(state, &mut moves);
+
+        if moves.is_empty() {
+            return (board.no_moves_value(state), 0);
+        }
+
+
+        let mut total_moves = 0;
+        let mut best_observed = WORST;
+        for &mve in &moves {
+            if board.winning_move(state, mve) {
+                return (BEST, total_moves + 1);
+            } else {
+                let undo = board.make_move(state, mve);
+                let (v, move_count) = NegaMax::eval::<G, H>(board, state, depth - 1, -color);
+                board.unmake_move(state, mve, undo);
+                total_moves += move_count;
+                best_observed = max(-v, best_observed);
+            }
+        }
+
+        (best_observed, total_moves)
+    }
+}
\ No newline at end of file
@@ -1,18 +1,11 @@
 
-use game::santorini::*;
-use std::cmp::{max, min};
-
-fn color(player:Player) -> HeuristicValue {
-    match player {
-        Player(0) => 1,
-        Player(1) => -1,
-        _ => panic!("fn color was given player -> {:?} (only supports 0, 1)", player),
-    }
-}
+use game::*;
+use std::cmp::max;
+
 
 pub struct NegaMaxAlphaBeta { }
 
-impl Evaluator for NegaMaxAlphaBeta {
+impl<G> Evaluator<G> for NegaMaxAlphaBeta where G: Game {
     type EvaluatorState = ();
 
     fn name() -> String {
@@ -26,26 +19,29 @@ impl Evaluator for NegaMaxAlphaBeta {
     fn reset(evaluator_state: &mut ()) { }
      
     #[allow(unused_variables)]
-    fn evaluate_moves_impl<H>(evaluator_state: &mut (), board: &StandardBoard, state: &State, depth: Depth) -> (Option<(Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic {
-    	let color = color(state.to_move);
+    fn evaluate_moves_impl<H>(evaluator_state: &mut (), board: &G, state: &G::State, depth: Depth) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        assert!(board.alternating(), "NegaMaxAlphaBeta flips color every ply, this game needs a minimax evaluator");
+        let state = &mut state.clone();
+    	let color = board.color(board.to_move(state));
 
-        let mut moves = Vec::with_capacity(200);
+        let mut moves : Vec<G::Move> = Vec::with_capacity(200);
         board.next_moves(state, &mut moves);
 
         let mut total_moves = 0;
-        let mut unsorted_moves : Vec<(Move, HeuristicValue)> = Vec::with_capacity(200);
+        let mut unsorted_moves : Vec<(G::Move, HeuristicValue)> = Vec::with_capacity(200);
 
     	let mut alpha = WORST;
 
         for &mve in &moves {
-            let v = if board.ascension_winning_move(state, mve) {
+            let v = if board.winning_move(state, mve) {
                 let av = BEST * color;
                 alpha = max(alpha, av);
                 total_moves += 1;
                 av
             } else {
-                let new_state = board.apply(mve, state);
-                let (v, move_count) = Self::eval::<H>(board, &new_state, depth - 1, WORST, -alpha, -color); // 
+                let undo = board.make_move(state, mve);
+                let (v, move_count) = Self::eval::<G, H>(board, state, depth - 1, WORST, -alpha, -color); // 
+                board.unmake_move(state, mve, undo);
                 let av = v * -color;
                 alpha = max(alpha, -v);
                 total_moves += move_count;
@@ -60,23 +56,32 @@ impl Evaluator for NegaMaxAlphaBeta {
 }
 
 impl NegaMaxAlphaBeta {
-    pub fn eval<H>(board: &StandardBoard, state: &State, depth: Depth, alpha:HeuristicValue, beta:HeuristicValue, color: HeuristicValue) -> (HeuristicValue, MoveCount) where H: Heuristic {
+    pub fn eval<G, H>(board: &G, state: &mut G::State, depth: Depth, alpha:HeuristicValue, beta:HeuristicValue, color: HeuristicValue) -> (HeuristicValue, MoveCount) where G: Game, H: Heuristic<G> {
+        if board.drawn(state) {
+            return (0, 1);
+        }
+
         if depth == 0 {
             return (H::evaluate(board, state) * color, 1);
         }
 
-        let mut moves = Vec::with_capacity(200); // enough to prevent resizing
+        let mut moves : Vec<G::Move> = Vec::with_capacity(200); // enough to prevent resizing
         board.next_moves(state, &mut moves);
 
+        if moves.is_empty() {
+            return (board.no_moves_value(state), 0);
+        }
+
         let mut total_moves = 0;
         let mut best_observed = WORST;
         let mut new_alpha = alpha;
         for &mve in &moves {
-            if board.ascension_winning_move(state, mve) {
+            if board.winning_move(state, mve) {
                 return (BEST, total_moves + 1);
             } else {
-                let new_state = board.apply(mve, state);
-                let (new_v, move_count) = Self::eval::<H>(board, &new_state, depth - 1, -beta, -new_alpha, -color);
+                let undo = board.make_move(state, mve);
+                let (new_v, move_count) = Self::eval::<G, H>(board, state, depth - 1, -beta, -new_alpha, -color);
+                board.unmake_move(state, mve, undo);
                 let v = -new_v;
                 total_moves += move_count;
                 best_observed = max(v, best_observed);
@@ -0,0 +1,456 @@
+
+// use HashMap;
+use game::*;
+use std::cmp::{max, min};
+
+pub struct NegaMaxAlphaBetaExp { }
+
+// extra plies a line can get for having to answer a winning threat
+pub const MAX_THREAT_EXTENSIONS : u8 = 4;
+
+// use rand::Rng;
+// use rand::{XorShiftRng, ChaChaRng};
+
+pub struct EvState<M> {
+    pub transposition: TranspositionTable<M>,
+    // key the table on Game::canonical_hash so symmetric positions share entries, costs a full hash per node
+    pub canonical: bool,
+    // the current iteration's limits, and how it's doing against them
+    pub limits: SearchLimits,
+    pub nodes: u64,
+    pub aborted: bool,
+    // killers and history, and how far from the root the node being searched is
+    pub ordering: MoveOrdering<M>,
+    pub ply: usize,
+    // search on past the horizon while the side to move has to stop a win, and how many plies that's added so far
+    pub threat_extensions: bool,
+    pub extensions: u8,
+    // pv_nodes : Vec<TranspositionEntry>,
+}
+
+impl<M> EvState<M> where M: Copy + Eq {
+    pub fn new() -> EvState<M> {
+        EvState {
+            transposition : TranspositionTable::new(22),
+            canonical: false,
+            limits: SearchLimits::none(),
+            nodes: 0,
+            aborted: false,
+            ordering: MoveOrdering::new(),
+            ply: 0,
+            threat_extensions: false,
+            extensions: 0,
+        }
+    }
+
+    pub fn canonical() -> EvState<M> {
+        EvState {
+            canonical: true,
+            ..EvState::new()
+        }
+    }
+
+    pub fn extending_threats() -> EvState<M> {
+        EvState {
+            threat_extensions: true,
+            ..EvState::new()
+        }
+    }
+
+    // at the horizon, whether it's won already for the side to move (standing next to a win), or they have to
+    // stop the other side's and should be searched a ply further
+    pub fn horizon_threat<G>(&self, board: &G, state: &G::State) -> HorizonThreat where G: Game<Move=M> {
+        if !self.threat_extensions {
+            HorizonThreat::Quiet
+        } else if board.winning_threat(state, board.to_move(state)) {
+            HorizonThreat::Winning
+        } else if self.extensions < MAX_THREAT_EXTENSIONS && board.winning_threat(state, board.next_player(state)) {
+            HorizonThreat::MustBlock
+        } else {
+            HorizonThreat::Quiet
+        }
+    }
+
+    // starts an iteration under the limits, false if they're already used up
+    pub fn begin_iteration(&mut self, limits: &SearchLimits) -> bool {
+        self.limits = limits.clone();
+        self.nodes = 0;
+        self.ply = 0;
+        self.extensions = 0;
+        self.aborted = limits.exceeded(0);
+        !self.aborted
+    }
+
+    // counts a node, and once the limits are hit the rest of the iteration gives up too
+    pub fn out_of_limits(&mut self) -> bool {
+        self.nodes += 1;
+        if self.limits.over_budget(self.nodes) || (self.nodes % LIMIT_CHECK_INTERVAL == 0 && self.limits.out_of_time()) {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HorizonThreat {
+    Quiet, // the heuristic is fine
+    Winning, // the side to move wins next move
+    MustBlock, // the other side wins next move unless it's stopped
+}
+
+impl<G> Evaluator<G> for NegaMaxAlphaBetaExp where G: Game {
+    type EvaluatorState = EvState<G::Move>;
+
+    fn name() -> String {
+        "NegaMaxAlphaBetaExp".into()
+    }
+
+    fn new_state() -> EvState<G::Move> {
+        EvState::new()
+    }
+
+    fn reset(evaluator_state: &mut EvState<G::Move>) {
+        evaluator_state.transposition.reset();
+        evaluator_state.ordering.reset();
+    }
+
+    fn new_search(evaluator_state: &mut EvState<G::Move>) {
+        evaluator_state.transposition.increment_generation();
+        evaluator_state.ordering.new_search();
+    }
+     
+    fn evaluate_moves_impl<H>(evaluator_state: &mut EvState<G::Move>, board: &G, state: &G::State, depth: Depth) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        Self::evaluate_moves_limited_impl::<H>(evaluator_state, board, state, depth, &SearchLimits::none())
+    }
+
+    #[allow(unused_variables)]
+    fn evaluate_moves_limited_impl<H>(evaluator_state: &mut EvState<G::Move>, board: &G, state: &G::State, depth: Depth, limits: &SearchLimits) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        assert!(board.alternating(), "NegaMaxAlphaBetaExp flips color every ply, this game needs a minimax evaluator");
+        if !evaluator_state.begin_iteration(limits) {
+            let mut info = EvaluatorInfo::new();
+            info.aborted = true;
+            return (None, info);
+        }
+
+        let state = &mut state.clone();
+        let color = board.color(board.to_move(state));
+
+        let mut unsorted_moves : Vec<(G::Move, HeuristicValue)> = Vec::with_capacity(200);
+
+        let mut move_stack : MoveStack<G::Move> = MoveStack::new();
+        let stack_begin = 0;
+        board.next_moves(state, &mut move_stack);
+        let stack_end = move_stack.next;
+
+        let mut alpha = WORST;
+        let mut beta = BEST;
+
+        let mut info = EvaluatorInfo::new();
+
+        let (hash, symmetry) = if evaluator_state.canonical {
+            board.canonical_hash(state)
+        } else {
+            (board.hash(state), 0)
+        };
+
+        let mut tt_best_move : Option<G::Move> = None;
+
+        let (tt_idx, found) = evaluator_state.transposition.probe(hash);
+
+        if found {
+            let entry = &evaluator_state.transposition.entries[tt_idx];
+            if entry.depth >= depth && board.transposable(state) {
+                info.tt_valid += 1;
+                match entry.entry_type {
+                    EntryType::Exact => {
+                        if let Some(mv) = entry.best_move {
+                            return (Some((board.from_canonical_move(mv, symmetry), entry.value)), info)  // unsure about this negation
+                        } 
+                    },
+                    EntryType::Lower => {
+                        alpha = max(alpha, entry.value);
+                    },
+                    EntryType::Upper => {
+                        beta = min(beta, entry.value)
+                    },
+                }
+                if alpha >= beta {
+                    if let Some(mv) = entry.best_move {
+                        return (Some((board.from_canonical_move(mv, symmetry), entry.value)), info)  // unsure about this negation
+                    } 
+                }
+            } else {
+                info.tt_suggest += 1;
+            }
+            tt_best_move = entry.best_move.map(|mv| board.from_canonical_move(mv, symmetry));
+        } else {
+            info.tt_miss += 1;
+        }
+
+        // if we have a best move, order it first
+        if let Some(mve) = tt_best_move {
+            for idx in stack_begin..stack_end {
+                if move_stack.moves[idx] == mve {
+                    move_stack.moves.swap(stack_begin, idx);
+                    break;
+                }
+            }    
+        }
+
+        let mut best_move : Option<G::Move> = None;
+        let mut best_observed = WORST;
+
+        for idx in stack_begin..stack_end {
+            let mve = move_stack.moves[idx];
+            let (v, count) = if board.winning_move(state, mve) {
+                let win = victory_in(1);
+                let av = win * color;
+                // alpha is from the side to move's view, a win is positive whichever side that is
+                if win > alpha {
+                    alpha = win;
+                    best_move = Some(mve);
+                    best_observed = av;
+                    info.pv_count += 1;
+                }
+                (av, 1)
+            } else {
+                let child_hash = Self::child_hash(board, state, hash, mve, evaluator_state.canonical);
+                let undo = board.make_move(state, mve);
+                evaluator_state.ply += 1;
+                let (v, move_count) = Self::eval::<G, H>(board, state, child_hash, depth - 1, -beta, -alpha, -color, &mut move_stack, &mut info, evaluator_state); // 
+                evaluator_state.ply -= 1;
+                board.unmake_move(state, mve, undo);
+                if evaluator_state.aborted {
+                    // the rest of this move's search never happened, leave it out
+                    break;
+                }
+
+                let av = v * -color;
+                // a lost position still keeps a move, so there's a line to show
+                if -v > alpha || best_move.is_none() {
+                    alpha = max(alpha, -v);
+                    best_move = Some(mve);
+                    best_observed = av;
+                    info.pv_count += 1;
+                }
+
+                (av, move_count)
+            };
+            info.move_count += count;
+            unsorted_moves.push((mve, v));
+        }
+
+        if evaluator_state.aborted {
+            info.aborted = true;
+            unsorted_moves.sort_by_key(|&(_, hv)| hv * -color);
+            return (unsorted_moves.first().cloned(), info);
+        }
+
+        info.pv_count += 1;
+
+        let canonical_best_move = best_move.map(|mv| board.to_canonical_move(mv, symmetry));
+        evaluator_state.transposition.store(tt_idx, hash, best_observed, stored_depth(board, state, depth), EntryType::Exact, canonical_best_move);
+  
+        unsorted_moves.sort_by_key(|&(_, hv)| hv * -color);
+
+        info.branch_factors.push(branch_factor(info.move_count, depth));
+        
+        (unsorted_moves.first().cloned(), info)
+    }
+
+    // follows the table's best moves down from the root. it stops when an entry is gone, a hash comes round again,
+    // or a move isn't legal (another position's entry under the same hash)
+    fn principal_variation(evaluator_state: &EvState<G::Move>, board: &G, state: &G::State, max_length: Depth) -> Vec<(G::Move, HeuristicValue)> {
+        let mut line = Vec::new();
+        let mut seen : Vec<StateHash> = Vec::new();
+        let mut state = state.clone();
+
+        while line.len() < max(max_length, 0) as usize {
+            let (hash, symmetry) = if evaluator_state.canonical {
+                board.canonical_hash(&state)
+            } else {
+                (board.hash(&state), 0)
+            };
+            if seen.contains(&hash) {
+                break;
+            }
+            seen.push(hash);
+
+            let (tt_idx, found) = evaluator_state.transposition.probe(hash);
+            if !found {
+                break;
+            }
+            let entry = &evaluator_state.transposition.entries[tt_idx];
+            let mve = match entry.best_move.map(|mv| board.from_canonical_move(mv, symmetry)) {
+                Some(mve) if board.legal_move(&state, mve) => mve,
+                _ => break,
+            };
+
+            // the root's entry is stored as is, everything under it is relative to the player to move and
+            // counts wins from itself rather than the root
+            let score = if line.is_empty() {
+                entry.value
+            } else {
+                from_tt_value(entry.value, line.len()) * board.color(board.to_move(&state))
+            };
+            line.push((mve, score));
+
+            if board.winning_move(&state, mve) {
+                break;
+            }
+            state = board.apply(mve, &state);
+        }
+
+        line
+    }
+}
+
+impl NegaMaxAlphaBetaExp {
+    // canonical hashes can't be updated incrementally, each node works its own out instead
+    pub fn child_hash<G>(board: &G, state: &G::State, hash: StateHash, mve: G::Move, canonical: bool) -> StateHash where G: Game {
+        if canonical {
+            STATE_HASH_ZERO
+        } else {
+            hash ^ board.delta_hash(state, mve)
+        }
+    }
+
+    pub fn eval<G, H>(board: &G, state: &mut G::State, hash: StateHash, depth: Depth, alpha:HeuristicValue, beta:HeuristicValue, color: HeuristicValue, move_stack: &mut MoveStack<G::Move>, info: &mut EvaluatorInfo, ev_state : &mut EvState<G::Move>) -> (HeuristicValue, MoveCount) where G: Game, H: Heuristic<G> {
+        if ev_state.out_of_limits() {
+            return (0, 0);
+        }
+
+        if board.drawn(state) {
+            return (0, 1);
+        }
+
+        let (hash, symmetry) = if ev_state.canonical {
+            board.canonical_hash(state)
+        } else {
+            (hash, 0)
+        };
+
+        let mut new_alpha = alpha;
+        let mut new_beta = beta;
+
+        // lookup transposition table
+        let ply = ev_state.ply;
+        let mut tt_best_move : Option<G::Move> = None;
+        let (tt_idx, found) = ev_state.transposition.probe(hash);
+        if found {
+            let entry = &ev_state.transposition.entries[tt_idx];
+            if entry.depth >= depth && board.transposable(state) {
+                info.tt_valid += 1;
+                let value = from_tt_value(entry.value, ply);
+                match entry.entry_type {
+                    EntryType::Exact => {
+                        return (value, 0);
+                    },
+                    EntryType::Lower => {
+                        new_alpha = max(new_alpha, value);
+                    },
+                    EntryType::Upper => {
+                        new_beta = min(new_beta, value);
+                    },
+                }
+                if new_alpha >= new_beta {
+                    return (value, 0)
+                }
+            } else {
+                info.tt_suggest += 1;
+            }
+            tt_best_move = entry.best_move.map(|mv| board.from_canonical_move(mv, symmetry));
+        } else {
+            info.tt_miss += 1;
+        }
+
+        if depth == 0 {
+            match ev_state.horizon_threat(board, state) {
+                HorizonThreat::Quiet => {
+                    let v = H::evaluate(board, state) * color;
+                    return (v, 1);
+                },
+                HorizonThreat::Winning => return (victory_in(ply + 1), 1),
+                HorizonThreat::MustBlock => {
+                    ev_state.extensions += 1;
+                    let result = Self::eval::<G, H>(board, state, hash, 1, alpha, beta, color, move_stack, info, ev_state);
+                    ev_state.extensions -= 1;
+                    return result;
+                },
+            }
+        }
+
+        let mut total_moves = 0;
+        let mut best_observed = WORST;
+        let mut best_move : Option<G::Move> = None;
+
+        // the tt move, wins, killers, then climbs and the rest by history, a cutoff early on skips generating the rest
+        let mut staged = StagedMoves::with_killers(move_stack, tt_best_move, ev_state.ordering.killers(ply));
+        let mut moves_tried = 0;
+
+        while let Some(mve) = staged.next_by_history(board, state, move_stack, &ev_state.ordering) {
+            moves_tried += 1;
+            let winning = board.winning_move(state, mve);
+            let (score, count) = if winning {
+                // the sooner the better, and the later the better for whoever's losing
+                (victory_in(ply + 1), 1)
+            } else {
+                let child_hash = Self::child_hash(board, state, hash, mve, ev_state.canonical);
+                let undo = board.make_move(state, mve);
+                ev_state.ply += 1;
+                let (v, move_count) = Self::eval::<G, H>(board, state, child_hash, depth - 1, -new_beta, -new_alpha, -color, move_stack, info, ev_state);
+                ev_state.ply -= 1;
+                board.unmake_move(state, mve, undo);
+                if ev_state.aborted {
+                    break;
+                }
+                (-v, move_count)
+            };
+
+            if score > best_observed || best_move.is_none() {
+                best_move = Some(mve);
+                best_observed = score;
+            }
+
+            new_alpha = max(new_alpha, score);
+            total_moves += count;
+            if new_beta <= new_alpha {
+                info.cutoffs += 1;
+                if moves_tried == 1 {
+                    info.first_move_cutoffs += 1;
+                }
+                // wins are found first regardless, only quiet moves are worth remembering
+                if !winning {
+                    ev_state.ordering.add_killer(ply, mve);
+                    ev_state.ordering.add_history(board, mve, depth);
+                }
+                break;
+            }
+        }
+
+        staged.finish(move_stack);
+
+        // nothing from an aborted search goes in the table
+        if ev_state.aborted {
+            return (0, total_moves);
+        }
+
+        if moves_tried == 0 {
+            return (distance_scored(board.no_moves_value(state), ply), 0);
+        }
+
+        let score_type = if best_observed <= alpha {
+            EntryType::Upper
+        } else if best_observed >= new_beta { // unsure if this should be beta
+            EntryType::Lower
+        } else {
+            info.pv_count += 1;
+            EntryType::Exact
+        };
+
+        let canonical_best_move = best_move.map(|mv| board.to_canonical_move(mv, symmetry));
+        ev_state.transposition.store(tt_idx, hash, to_tt_value(best_observed, ply), stored_depth(board, state, depth), score_type, canonical_best_move);
+
+        (best_observed, total_moves)
+    }
+}
@@ -0,0 +1,316 @@
+use game::*;
+use std::cmp::{max, min};
+
+// principal variation search: the first move at each node gets the full window, the rest a null window
+// that only proves they're no better, searched again in full if they turn out to be. each depth starts from
+// a window around the score of the depth before, widened when the score lands outside it
+pub struct NegaMaxPVS { }
+
+pub const ASPIRATION_WINDOW : HeuristicValue = 8;
+
+pub struct PvsState<M> {
+    pub search: EvState<M>, // the table, limits and move ordering, shared with NegaMaxAlphaBetaExp
+    pub previous: Option<(StateHash, HeuristicValue)>, // the last completed iteration's root and score for the side to move
+    pub researches: u64, // aspiration windows missed
+}
+
+impl<M> PvsState<M> where M: Copy + Eq {
+    pub fn new() -> PvsState<M> {
+        PvsState {
+            search: EvState::new(),
+            previous: None,
+            researches: 0,
+        }
+    }
+}
+
+impl<G> Evaluator<G> for NegaMaxPVS where G: Game {
+    type EvaluatorState = PvsState<G::Move>;
+
+    fn name() -> String {
+        "NegaMaxPVS".into()
+    }
+
+    fn new_state() -> PvsState<G::Move> {
+        PvsState::new()
+    }
+
+    fn reset(evaluator_state: &mut PvsState<G::Move>) {
+        <NegaMaxAlphaBetaExp as Evaluator<G>>::reset(&mut evaluator_state.search);
+        evaluator_state.previous = None;
+    }
+
+    fn new_search(evaluator_state: &mut PvsState<G::Move>) {
+        <NegaMaxAlphaBetaExp as Evaluator<G>>::new_search(&mut evaluator_state.search);
+    }
+
+    fn evaluate_moves_impl<H>(evaluator_state: &mut PvsState<G::Move>, board: &G, state: &G::State, depth: Depth) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        Self::evaluate_moves_limited_impl::<H>(evaluator_state, board, state, depth, &SearchLimits::none())
+    }
+
+    fn evaluate_moves_limited_impl<H>(evaluator_state: &mut PvsState<G::Move>, board: &G, state: &G::State, depth: Depth, limits: &SearchLimits) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<G> {
+        assert!(board.alternating(), "NegaMaxPVS flips color every ply, this game needs a minimax evaluator");
+        let mut info = EvaluatorInfo::new();
+        if !evaluator_state.search.begin_iteration(limits) {
+            info.aborted = true;
+            return (None, info);
+        }
+
+        let state = &mut state.clone();
+        let color = board.color(board.to_move(state));
+        let canonical = evaluator_state.search.canonical;
+
+        let (hash, symmetry) = if canonical {
+            board.canonical_hash(state)
+        } else {
+            (board.hash(state), 0)
+        };
+
+        let mut root_moves = Vec::new();
+        board.next_moves(state, &mut root_moves);
+
+        // the last iteration's best move first
+        let (tt_idx, found) = evaluator_state.search.transposition.probe(hash);
+        if found {
+            let entry = &evaluator_state.search.transposition.entries[tt_idx];
+            if let Some(mve) = entry.best_move.map(|mv| board.from_canonical_move(mv, symmetry)) {
+                if let Some(idx) = root_moves.iter().position(|&m| m == mve) {
+                    root_moves.swap(0, idx);
+                }
+            }
+        }
+
+        let (mut alpha, mut beta) = match evaluator_state.previous {
+            // a decided score won't move by a few points, it either stays or it's something else entirely
+            Some((previous_hash, score)) if previous_hash == hash && !decided(score) => {
+                (max(WORST, score.saturating_sub(ASPIRATION_WINDOW)), min(BEST, score.saturating_add(ASPIRATION_WINDOW)))
+            },
+            _ => (WORST, BEST),
+        };
+
+        let mut move_stack : MoveStack<G::Move> = MoveStack::new();
+
+        loop {
+            let (best_move, score) = Self::root::<G, H>(board, state, hash, depth, alpha, beta, color, &root_moves, &mut move_stack, &mut info, &mut evaluator_state.search);
+
+            if evaluator_state.search.aborted {
+                info.aborted = true;
+                return (best_move.map(|mve| (mve, score * color)), info);
+            }
+
+            // outside the window it's only a bound, so again with that side opened up
+            if score <= alpha && alpha > WORST {
+                alpha = WORST;
+            } else if score >= beta && beta < BEST {
+                beta = BEST;
+            } else {
+                evaluator_state.previous = Some((hash, score));
+                let canonical_best_move = best_move.map(|mv| board.to_canonical_move(mv, symmetry));
+                // stored from player 0's side like NegaMaxAlphaBetaExp's root, so principal_variation reads it the same
+                let (tt_idx, _) = evaluator_state.search.transposition.probe(hash);
+                evaluator_state.search.transposition.store(tt_idx, hash, score * color, stored_depth(board, state, depth), EntryType::Exact, canonical_best_move);
+                info.branch_factors.push(branch_factor(info.move_count, depth));
+                return (best_move.map(|mve| (mve, score * color)), info);
+            }
+            evaluator_state.researches += 1;
+        }
+    }
+
+    fn principal_variation(evaluator_state: &PvsState<G::Move>, board: &G, state: &G::State, max_length: Depth) -> Vec<(G::Move, HeuristicValue)> {
+        <NegaMaxAlphaBetaExp as Evaluator<G>>::principal_variation(&evaluator_state.search, board, state, max_length)
+    }
+}
+
+impl NegaMaxPVS {
+    // the best move and its score for the side to move, only a bound if it's outside alpha beta
+    fn root<G, H>(board: &G, state: &mut G::State, hash: StateHash, depth: Depth, alpha: HeuristicValue, beta: HeuristicValue, color: HeuristicValue, root_moves: &[G::Move], move_stack: &mut MoveStack<G::Move>, info: &mut EvaluatorInfo, ev_state: &mut EvState<G::Move>) -> (Option<G::Move>, HeuristicValue) where G: Game, H: Heuristic<G> {
+        let mut alpha = alpha;
+        let mut best_move : Option<G::Move> = None;
+        let mut best_observed = WORST;
+
+        for (idx, &mve) in root_moves.iter().enumerate() {
+            let score = if board.winning_move(state, mve) {
+                info.move_count += 1;
+                victory_in(1)
+            } else {
+                let child_hash = NegaMaxAlphaBetaExp::child_hash(board, state, hash, mve, ev_state.canonical);
+                let undo = board.make_move(state, mve);
+                ev_state.ply += 1;
+                let (v, count) = if idx == 0 {
+                    Self::pvs::<G, H>(board, state, child_hash, depth - 1, -beta, -alpha, -color, move_stack, info, ev_state)
+                } else {
+                    Self::scout::<G, H>(board, state, child_hash, depth - 1, alpha, beta, -color, move_stack, info, ev_state)
+                };
+                ev_state.ply -= 1;
+                board.unmake_move(state, mve, undo);
+                if ev_state.aborted {
+                    break;
+                }
+                info.move_count += count;
+                -v
+            };
+
+            if score > best_observed || best_move.is_none() {
+                best_move = Some(mve);
+                best_observed = score;
+                info.pv_count += 1;
+            }
+            alpha = max(alpha, score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        (best_move, best_observed)
+    }
+
+    // a null window search of the child showing it's no better than alpha, the full alpha beta one if it is.
+    // the value is the child's, from its side
+    fn scout<G, H>(board: &G, state: &mut G::State, hash: StateHash, depth: Depth, alpha: HeuristicValue, beta: HeuristicValue, color: HeuristicValue, move_stack: &mut MoveStack<G::Move>, info: &mut EvaluatorInfo, ev_state: &mut EvState<G::Move>) -> (HeuristicValue, MoveCount) where G: Game, H: Heuristic<G> {
+        let (v, count) = Self::pvs::<G, H>(board, state, hash, depth, -alpha - 1, -alpha, color, move_stack, info, ev_state);
+        if -v > alpha && -v < beta && !ev_state.aborted {
+            let (v, research_count) = Self::pvs::<G, H>(board, state, hash, depth, -beta, -alpha, color, move_stack, info, ev_state);
+            (v, count + research_count)
+        } else {
+            (v, count)
+        }
+    }
+
+    pub fn pvs<G, H>(board: &G, state: &mut G::State, hash: StateHash, depth: Depth, alpha:HeuristicValue, beta:HeuristicValue, color: HeuristicValue, move_stack: &mut MoveStack<G::Move>, info: &mut EvaluatorInfo, ev_state : &mut EvState<G::Move>) -> (HeuristicValue, MoveCount) where G: Game, H: Heuristic<G> {
+        if ev_state.out_of_limits() {
+            return (0, 0);
+        }
+
+        if board.drawn(state) {
+            return (0, 1);
+        }
+
+        let (hash, symmetry) = if ev_state.canonical {
+            board.canonical_hash(state)
+        } else {
+            (hash, 0)
+        };
+
+        let mut new_alpha = alpha;
+        let mut new_beta = beta;
+
+        let ply = ev_state.ply;
+        let mut tt_best_move : Option<G::Move> = None;
+        let (tt_idx, found) = ev_state.transposition.probe(hash);
+        if found {
+            let entry = &ev_state.transposition.entries[tt_idx];
+            if entry.depth >= depth && board.transposable(state) {
+                info.tt_valid += 1;
+                let value = from_tt_value(entry.value, ply);
+                match entry.entry_type {
+                    EntryType::Exact => {
+                        return (value, 0);
+                    },
+                    EntryType::Lower => {
+                        new_alpha = max(new_alpha, value);
+                    },
+                    EntryType::Upper => {
+                        new_beta = min(new_beta, value);
+                    },
+                }
+                if new_alpha >= new_beta {
+                    return (value, 0)
+                }
+            } else {
+                info.tt_suggest += 1;
+            }
+            tt_best_move = entry.best_move.map(|mv| board.from_canonical_move(mv, symmetry));
+        } else {
+            info.tt_miss += 1;
+        }
+
+        if depth == 0 {
+            match ev_state.horizon_threat(board, state) {
+                HorizonThreat::Quiet => {
+                    let v = H::evaluate(board, state) * color;
+                    return (v, 1);
+                },
+                HorizonThreat::Winning => return (victory_in(ply + 1), 1),
+                HorizonThreat::MustBlock => {
+                    ev_state.extensions += 1;
+                    let result = Self::pvs::<G, H>(board, state, hash, 1, alpha, beta, color, move_stack, info, ev_state);
+                    ev_state.extensions -= 1;
+                    return result;
+                },
+            }
+        }
+
+        let mut total_moves = 0;
+        let mut best_observed = WORST;
+        let mut best_move : Option<G::Move> = None;
+
+        let mut staged = StagedMoves::with_killers(move_stack, tt_best_move, ev_state.ordering.killers(ply));
+        let mut moves_tried = 0;
+
+        while let Some(mve) = staged.next_by_history(board, state, move_stack, &ev_state.ordering) {
+            moves_tried += 1;
+            let winning = board.winning_move(state, mve);
+            let (score, count) = if winning {
+                (victory_in(ply + 1), 1)
+            } else {
+                let child_hash = NegaMaxAlphaBetaExp::child_hash(board, state, hash, mve, ev_state.canonical);
+                let undo = board.make_move(state, mve);
+                ev_state.ply += 1;
+                let (v, move_count) = if moves_tried == 1 {
+                    Self::pvs::<G, H>(board, state, child_hash, depth - 1, -new_beta, -new_alpha, -color, move_stack, info, ev_state)
+                } else {
+                    Self::scout::<G, H>(board, state, child_hash, depth - 1, new_alpha, new_beta, -color, move_stack, info, ev_state)
+                };
+                ev_state.ply -= 1;
+                board.unmake_move(state, mve, undo);
+                if ev_state.aborted {
+                    break;
+                }
+                (-v, move_count)
+            };
+
+            if score > best_observed || best_move.is_none() {
+                best_move = Some(mve);
+                best_observed = score;
+            }
+
+            new_alpha = max(new_alpha, score);
+            total_moves += count;
+            if new_beta <= new_alpha {
+                info.cutoffs += 1;
+                if moves_tried == 1 {
+                    info.first_move_cutoffs += 1;
+                }
+                if !winning {
+                    ev_state.ordering.add_killer(ply, mve);
+                    ev_state.ordering.add_history(board, mve, depth);
+                }
+                break;
+            }
+        }
+
+        staged.finish(move_stack);
+
+        if ev_state.aborted {
+            return (0, total_moves);
+        }
+
+        if moves_tried == 0 {
+            return (distance_scored(board.no_moves_value(state), ply), 0);
+        }
+
+        let score_type = if best_observed <= alpha {
+            EntryType::Upper
+        } else if best_observed >= new_beta {
+            EntryType::Lower
+        } else {
+            info.pv_count += 1;
+            EntryType::Exact
+        };
+
+        let canonical_best_move = best_move.map(|mv| board.to_canonical_move(mv, symmetry));
+        ev_state.transposition.store(tt_idx, hash, to_tt_value(best_observed, ply), stored_depth(board, state, depth), score_type, canonical_best_move);
+
+        (best_observed, total_moves)
+    }
+}
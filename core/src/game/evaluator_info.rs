@@ -1,5 +1,5 @@
 use std::ops::{Add, AddAssign};
-use game::santorini::*;
+use game::*;
 use std::fmt;
 
 #[derive(Clone)]
@@ -11,6 +11,9 @@ pub struct EvaluatorInfo {
     pub tt_valid : u64,
     pub tt_suggest: u64,
     pub tt_miss : u64,
+    pub aborted : bool, // hit the search limits before finishing
+    pub cutoffs : u64,
+    pub first_move_cutoffs : u64, // cutoffs on the first move tried, how good the move ordering is
 }
 
 impl EvaluatorInfo {
@@ -23,6 +26,9 @@ impl EvaluatorInfo {
             tt_valid : 0,
             tt_suggest: 0,
             tt_miss : 0,
+            aborted : false,
+            cutoffs : 0,
+            first_move_cutoffs : 0,
         }
     }
 
@@ -39,6 +45,17 @@ impl EvaluatorInfo {
             tt_valid : 0,
             tt_suggest: 0,
             tt_miss : 0,
+            aborted : false,
+            cutoffs : 0,
+            first_move_cutoffs : 0,
+        }
+    }
+
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        if self.cutoffs > 0 {
+            self.first_move_cutoffs as f64 / self.cutoffs as f64
+        } else {
+            0.0
         }
     }
 
@@ -56,11 +73,14 @@ impl fmt::Debug for EvaluatorInfo {
         let average_branch_factor = average(&self.branch_factors);
         let moves_per_second = self.move_count as f64 / self.time / 1000000.0;
         let pv_percentage = self.pv_count as f64 / (self.move_count as f64);
-        write!(f, "EvaluatorInfo {{ moves: {} ({:.2}M/second) pv nodes: {} ({:.2}%) average branch factor: {:.1} time: {:0.2}s transpotition (valid {} sugg {} miss {})}}", 
+        write!(f, "EvaluatorInfo {{ moves: {} ({:.2}M/second) pv nodes: {} ({:.2}%) average branch factor: {:.1} time: {:0.2}s transpotition (valid {} sugg {} miss {}) first move cutoffs: {:.1}% of {}{}}}", 
             self.move_count, moves_per_second, self.pv_count, pv_percentage, average_branch_factor, self.time,
             self.tt_valid,
             self.tt_suggest,
             self.tt_miss,
+            self.first_move_cutoff_rate() * 100.0,
+            self.cutoffs,
+            if self.aborted { " aborted" } else { "" },
         )
     }
 }
@@ -79,6 +99,9 @@ impl Add for EvaluatorInfo {
             tt_valid : self.tt_valid + other.tt_valid,
             tt_suggest: self.tt_suggest + other.tt_suggest,
             tt_miss : self.tt_miss + other.tt_miss,
+            aborted : self.aborted || other.aborted,
+            cutoffs : self.cutoffs + other.cutoffs,
+            first_move_cutoffs : self.first_move_cutoffs + other.first_move_cutoffs,
         }
     }
 }
@@ -92,6 +115,9 @@ impl AddAssign for EvaluatorInfo {
         self.tt_valid += other.tt_valid;
         self.tt_suggest += other.tt_suggest;
         self.tt_miss += other.tt_miss;
+        self.aborted |= other.aborted;
+        self.cutoffs += other.cutoffs;
+        self.first_move_cutoffs += other.first_move_cutoffs;
     }
 }
 
@@ -1,6 +1,38 @@
 pub mod santorini;
 pub mod util;
 pub mod packed;
+pub mod engine;
+pub mod tictactoe;
+pub mod checkers;
+pub mod evaluator {
+    pub mod minimax;
+    pub mod minimax_alphabeta;
+    pub mod negamax;
+    pub mod negamax_alphabeta;
+    pub mod negamax_alphabeta_exp;    
+    pub mod negamax_pvs;
+}
+pub mod evaluator_info;
+pub mod move_stack;
+pub mod staged_moves;
+pub mod search_limits;
+pub mod transposition;
+pub mod perft;
+pub mod playout;
 
 pub use self::packed::*;
-pub use self::util::*;
\ No newline at end of file
+pub use self::util::*;
+pub use self::engine::*;
+pub use self::evaluator::negamax::*;
+pub use self::evaluator::negamax_alphabeta::*;
+pub use self::evaluator::negamax_alphabeta_exp::*;
+pub use self::evaluator::negamax_pvs::*;
+pub use self::evaluator::minimax::*;
+pub use self::evaluator::minimax_alphabeta::*;
+pub use self::evaluator_info::*;
+pub use self::move_stack::*;
+pub use self::staged_moves::*;
+pub use self::search_limits::*;
+pub use self::transposition::*;
+pub use self::perft::*;
+pub use self::playout::*;
@@ -0,0 +1,34 @@
+use game::*;
+
+pub const MAX_DEPTH: usize = 15;
+pub const MAX_MOVES: usize = 256;
+pub const MAX_MOVE_STACK : usize = MAX_DEPTH * MAX_MOVES;
+
+pub struct MoveStack<M> {
+    pub moves: Vec<M>, // only 0..next is live, anything past it is left over from earlier plies
+    pub next: usize,
+}
+
+impl<M> MoveStack<M> where M: Copy {
+    pub fn new() -> MoveStack<M> {
+        MoveStack {
+            moves: Vec::with_capacity(MAX_MOVE_STACK),
+            next: 0,
+        }
+    }
+
+    pub fn push(&mut self, mve:M) {
+       if self.next < self.moves.len() {
+           self.moves[self.next] = mve;
+       } else {
+           self.moves.push(mve);
+       }
+       self.next += 1;
+    }
+}
+
+impl<M> MoveSink<M> for MoveStack<M> where M: Copy {
+    fn sink(&mut self, mve:M) {
+        self.push(mve);
+    }
+}
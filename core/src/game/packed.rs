@@ -7,13 +7,13 @@ use std::ops::{BitOr, BitOrAssign, BitAnd, BitAndAssign, BitXor, BitXorAssign, N
   
 
 
+// a bit per slot, 64 wide so every board up to 8x8 fits
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
-pub struct Packed1(pub u32);
+pub struct Packed1(pub u64);
 
 pub const PACKED1_EMPTY : Packed1 = Packed1(0);
 
-pub const ONE_MASK : u32 = 1;
-pub const ALL_MASK_32 : u32 = 0xffffffff;
+pub const ONE_MASK : u64 = 1;
 
 
 impl Packed for Packed1 {
@@ -26,8 +26,8 @@ impl Packed for Packed1 {
     }
 
     fn set(&mut self, slot : Slot, value: u8) {
-        let remove_mask : u32 = (1 << slot.0) ^ ALL_MASK_32;
-        self.0 = (self.0 & remove_mask) | ((value as u32) << slot.0)
+        let remove_mask : u64 = (1 << slot.0) ^ ALL_MASK_64;
+        self.0 = (self.0 & remove_mask) | ((value as u64) << slot.0)
     }
 }
 
@@ -47,7 +47,7 @@ impl Packed1 {
 
     #[inline]
     fn lsb(&self) -> Packed1 {
-        Packed1(self.0 & 0u32.wrapping_sub(self.0))
+        Packed1(self.0 & 0u64.wrapping_sub(self.0))
     }
 
     #[inline]
@@ -56,8 +56,8 @@ impl Packed1 {
     }
 
     fn setb(&mut self, slot : Slot, value: u8) {
-        let remove_mask : u32 = (1 << slot.0) ^ ALL_MASK_32;
-        self.0 = (self.0 & remove_mask) | ((value as u32) << slot.0);
+        let remove_mask : u64 = (1 << slot.0) ^ ALL_MASK_64;
+        self.0 = (self.0 & remove_mask) | ((value as u64) << slot.0);
     }
 
     pub fn any(&self) -> bool {
@@ -65,18 +65,17 @@ impl Packed1 {
     }
 }
 
+// every bit, slot 0 first in groups of 8. slots only map to rows for a particular board size so there's no grid
 impl fmt::Debug for Packed1 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str("Packed1(\n").unwrap();
-        for y in 0..5 {
-            f.write_str("  ").unwrap();
-            for x in 0..5 {
-                let on = self.get(Slot(y * 5 + x ));
-                write!(f, "{}", on).unwrap();
+        f.write_str("Packed1(")?;
+        for i in 0..64 {
+            if i > 0 && i % 8 == 0 {
+                f.write_str("_")?;
             }
-            f.write_str("\n").unwrap();
+            write!(f, "{}", (self.0 >> i) & ONE_MASK)?;
         }
-        f.write_str(")\n")
+        f.write_str(")")
     }
 }
 
@@ -169,4 +168,10 @@ mod tests {
         }
 
     }
+
+    #[test]
+    fn debug_shows_every_slot() {
+        let p = Packed1((1 << 0) | (1 << 9) | (1 << 63));
+        assert_eq!(format!("{:?}", p), "Packed1(10000000_01000000_00000000_00000000_00000000_00000000_00000000_00000001)");
+    }
 }
@@ -0,0 +1,208 @@
+use game::*;
+
+pub fn perft<G>(game: &G, state: &G::State, depth: usize, move_stack : &mut MoveStack<G::Move>) -> u64 where G: Game {
+    perft_in_place(game, &mut state.clone(), depth, move_stack)
+}
+
+fn perft_in_place<G>(game: &G, state: &mut G::State, depth: usize, move_stack : &mut MoveStack<G::Move>) -> u64 where G: Game {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut n = 0;
+
+    let stack_begin = move_stack.next;
+    game.next_moves(state, move_stack);
+    let stack_end = move_stack.next;
+
+    for idx in stack_begin..stack_end {
+        let mve = move_stack.moves[idx];
+
+        if game.winning_move(state, mve) {
+            n += 1;
+        } else {
+            let undo = game.make_move(state, mve);
+            n += perft_in_place(game, state, depth - 1, move_stack);
+            game.unmake_move(state, mve, undo);
+        }
+    }
+
+    move_stack.next = stack_begin;
+
+    n
+}
+
+// the clone per node version, kept to measure make/unmake against
+pub fn perft_apply<G>(game: &G, state: &G::State, depth: usize, move_stack : &mut MoveStack<G::Move>) -> u64 where G: Game {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut n = 0;
+
+    let stack_begin = move_stack.next;
+    game.next_moves(state, move_stack);
+    let stack_end = move_stack.next;
+
+    for idx in stack_begin..stack_end {
+        let mve = move_stack.moves[idx];
+
+        if game.winning_move(state, mve) {
+            n += 1;
+        } else {
+            let new_state = game.apply(mve, state);
+            n += perft_apply(game, &new_state, depth - 1, move_stack);
+        }
+    }
+
+    move_stack.next = stack_begin;
+
+    n
+}
+
+pub fn perft_heuristic<G, H>(game: &G, state: &G::State, depth: usize, move_stack : &mut MoveStack<G::Move>) -> (u64, i64) where G: Game, H : Heuristic<G> {
+    perft_heuristic_in_place::<G, H>(game, &mut state.clone(), depth, move_stack)
+}
+
+fn perft_heuristic_in_place<G, H>(game: &G, state: &mut G::State, depth: usize, move_stack : &mut MoveStack<G::Move>) -> (u64, i64) where G: Game, H : Heuristic<G> {
+    if depth == 0 {
+        return (1, H::evaluate(game, state) as i64);
+    }
+
+    let mut n = 0;
+    let mut h = 0;
+
+    let stack_begin = move_stack.next;
+    game.next_moves(state, move_stack);
+    let stack_end = move_stack.next;
+
+    for idx in stack_begin..stack_end {
+        let mve = move_stack.moves[idx];
+
+        if game.winning_move(state, mve) {
+            n += 1;
+        } else {
+            let undo = game.make_move(state, mve);
+            let (moves, total_h) = perft_heuristic_in_place::<G, H>(game, state, depth - 1, move_stack);
+            game.unmake_move(state, mve, undo);
+            h += total_h;
+            n += moves;
+        }
+    }
+
+    move_stack.next = stack_begin;
+
+    (n, h)
+}
+
+// per root move counts, to narrow down where two move generators disagree
+pub fn perft_divide<G>(game: &G, state: &G::State, depth: usize, move_stack : &mut MoveStack<G::Move>) -> Vec<(G::Move, u64)> where G: Game {
+    let mut divided = Vec::new();
+    if depth == 0 {
+        return divided;
+    }
+
+    let state = &mut state.clone();
+
+    let stack_begin = move_stack.next;
+    game.next_moves(state, move_stack);
+    let stack_end = move_stack.next;
+
+    for idx in stack_begin..stack_end {
+        let mve = move_stack.moves[idx];
+
+        let n = if game.winning_move(state, mve) {
+            1
+        } else {
+            let undo = game.make_move(state, mve);
+            let n = perft_in_place(game, state, depth - 1, move_stack);
+            game.unmake_move(state, mve, undo);
+            n
+        };
+        divided.push((mve, n));
+    }
+
+    move_stack.next = stack_begin;
+
+    divided
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PerftEntry {
+    pub hash: StateHash,
+    pub depth: usize,
+    pub count: u64,
+}
+
+// subtree counts by hash and depth, always replacing
+pub struct PerftCache {
+    pub mask: u64,
+    pub entries: Vec<PerftEntry>,
+    pub hits: u64,
+}
+
+impl PerftCache {
+    pub fn new(power_of_two: usize) -> PerftCache {
+        let size = 1_usize << power_of_two;
+        PerftCache {
+            mask: (size - 1) as u64,
+            entries: vec![PerftEntry { hash: STATE_HASH_ZERO, depth: 0, count: 0 }; size],
+            hits: 0,
+        }
+    }
+
+    pub fn get(&mut self, hash: StateHash, depth: usize) -> Option<u64> {
+        let entry = self.entries[(hash.0 & self.mask) as usize];
+        if entry.hash == hash && entry.depth == depth {
+            self.hits += 1;
+            Some(entry.count)
+        } else {
+            None
+        }
+    }
+
+    pub fn put(&mut self, hash: StateHash, depth: usize, count: u64) {
+        self.entries[(hash.0 & self.mask) as usize] = PerftEntry { hash: hash, depth: depth, count: count };
+    }
+}
+
+// perft sharing the counts of transposed subtrees, a hash collision would throw the count off
+pub fn perft_hashed<G>(game: &G, state: &G::State, depth: usize, move_stack : &mut MoveStack<G::Move>, cache: &mut PerftCache) -> u64 where G: Game {
+    let hash = game.hash(state);
+    perft_hashed_in_place(game, &mut state.clone(), hash, depth, move_stack, cache)
+}
+
+fn perft_hashed_in_place<G>(game: &G, state: &mut G::State, hash: StateHash, depth: usize, move_stack : &mut MoveStack<G::Move>, cache: &mut PerftCache) -> u64 where G: Game {
+    if depth == 0 {
+        return 1;
+    }
+
+    if let Some(n) = cache.get(hash, depth) {
+        return n;
+    }
+
+    let mut n = 0;
+
+    let stack_begin = move_stack.next;
+    game.next_moves(state, move_stack);
+    let stack_end = move_stack.next;
+
+    for idx in stack_begin..stack_end {
+        let mve = move_stack.moves[idx];
+
+        if game.winning_move(state, mve) {
+            n += 1;
+        } else {
+            let child_hash = hash ^ game.delta_hash(state, mve);
+            let undo = game.make_move(state, mve);
+            n += perft_hashed_in_place(game, state, child_hash, depth - 1, move_stack, cache);
+            game.unmake_move(state, mve, undo);
+        }
+    }
+
+    move_stack.next = stack_begin;
+
+    cache.put(hash, depth, n);
+
+    n
+}
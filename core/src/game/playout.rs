@@ -0,0 +1,40 @@
+use game::*;
+
+// plays a game out between two searches, search gets the index of the player to move
+// returns the winner, None for a draw
+pub fn adversarial_playout_with<G, S, F>(game: &G, initial: &G::State, mut search: S, mut on_move: F) -> (Option<G::Player>, [EvaluatorInfo; 2]) 
+    where G: Game, 
+          S: FnMut(usize, &G::State) -> (Option<(G::Move, HeuristicValue)>, EvaluatorInfo), 
+          F: FnMut(&G::State, &G::Move, HeuristicValue) {
+    let mut state = initial.clone();
+
+    let mut infos = [EvaluatorInfo::new(), EvaluatorInfo::new()];
+
+    loop {
+        if game.drawn(&state) {
+            return (None, infos);
+        }
+
+        let player = game.to_move(&state);
+        let player_idx = game.player_index(player);
+
+        let (best_move, info) = search(player_idx, &state);
+        infos[player_idx] += info;
+
+        if let Some((mve, score)) = best_move {
+            let is_winning_move = game.winning_move(&state, mve);
+            state = game.apply(mve, &state);
+            on_move(&state, &mve, score);
+            if is_winning_move {
+                return (Some(player), infos);
+            }
+        } else {
+            let winner = if game.no_moves_value(&state) == WORST {
+                Some(game.next_player(&state))
+            } else {
+                None
+            };
+            return (winner, infos);
+        }
+    }
+}
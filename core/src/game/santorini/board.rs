@@ -5,44 +5,216 @@ use game::*;
 
 use std::cmp::min;
 
-pub const SLOT_COUNT : usize = 25;
+pub const SLOT_COUNT : usize = 25; // on the default 5x5 board
+
+// the one thing make_move can't work back out from the move
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Undo {
+    pub athena_blocks: bool,
+}
 
 #[derive(Debug, Clone)]
 pub struct StandardBoard {
-    pub slots : [Slot; SLOT_COUNT],
-    pub adjacencies : [[Slot ; 8] ; SLOT_COUNT],
-    pub packed_adjacencies : [Packed1 ; SLOT_COUNT],
-    pub transforms : [SlotTransform; 7],
+    pub size : usize, // width & height
+    pub slots : Vec<Slot>,
+    pub adjacencies : Vec<[Slot ; 8]>,
+    pub packed_adjacencies : Vec<Packed1>,
+    pub transforms : Vec<SlotTransform>, // the 7 non identity symmetries
+    pub inverse_transforms : Vec<SlotTransform>, // same order as transforms
+    pub tables : MoveTables,
     pub hash : ZobristHash,
 }
 
-pub trait MoveSink {
-    fn sink(&mut self, mve:Move);
+// the parts of a board that only depend on its size, what boards other than StandardBoard share with it
+#[derive(Debug, Clone)]
+pub struct Geometry {
+    pub size : usize,
+    pub slots : Vec<Slot>,
+    pub adjacencies : Vec<[Slot ; 8]>,
+    pub packed_adjacencies : Vec<Packed1>,
+    pub transforms : Vec<SlotTransform>, // the 7 non identity symmetries
 }
 
-impl MoveSink for Vec<Move> {
-    fn sink(&mut self, mve:Move) {
-        self.push(mve);
-    }
-}
+impl Geometry {
+    pub fn new(size: usize) -> Geometry {
+        assert!(size >= MIN_BOARD_SIZE && size <= MAX_BOARD_SIZE, "board size {} isn't in {}..{}", size, MIN_BOARD_SIZE, MAX_BOARD_SIZE);
+        let slot_count = size * size;
+
+        let mut geometry = Geometry {
+            size: size,
+            slots: (0..slot_count).map(|i| Slot(i as i8)).collect(),
+            adjacencies: Vec::with_capacity(slot_count),
+            packed_adjacencies: Vec::with_capacity(slot_count),
+            transforms: Vec::with_capacity(7),
+        };
+
+        for i in 0..slot_count {
+            let pos = geometry.position(Slot(i as i8));
+            let mut j = 0;
 
+            let mut adjacencies = [NONE ; 8];
+            let mut packed = PACKED1_EMPTY;
 
+            for nx in (pos.x-1)..(pos.x+2) {
+                for ny in (pos.y-1)..(pos.y+2) {
+                    if !(nx == pos.x && ny == pos.y) {
+                        if let Some(slot) = geometry.slot_for(Position { x: nx, y : ny }) {
+                            adjacencies[j] = slot;
+                            j += 1;
 
-impl StandardBoard {
-    pub fn transform<F>(&self, f: F) -> SlotTransform where F: Fn(Position) -> Position {
-        let mut transform = SlotTransform { slots: [Slot(0); 25] };
-        for x in 0..5 {
-            for y in 0..5 {
-                let position = Position{ x: x, y: y };
-                let sl = StandardBoard::slot(position);
-                let t_position = f(position);
+                            packed.0 |= 1 << slot.0;
+                        }
+                    }
+                }
+            }
+
+            geometry.adjacencies.push(adjacencies);
+            geometry.packed_adjacencies.push(packed);
+        }
+
+        geometry.transforms = vec![
+            geometry.transform(rotate_90),
+            geometry.transform(rotate_180),
+            geometry.transform(rotate_270),
+            geometry.transform(reflect_x),
+            geometry.transform(reflect_y),
+            geometry.transform(reflect_diag_a),
+            geometry.transform(reflect_diag_b)
+        ];
+
+        geometry
+    }
 
-                transform.slots[sl.0 as usize] = StandardBoard::slot(t_position);
+    pub fn transform<F>(&self, f: F) -> SlotTransform where F: Fn(Position, i8) -> Position {
+        let size = self.size as i8;
+        let mut transform = SlotTransform { slots: vec![Slot(0); self.slots.len()] };
+        for x in 0..size {
+            for y in 0..size {
+                let position = Position{ x: x, y: y };
+                transform.slots[self.slot(position).0 as usize] = self.slot(f(position, size));
             }
         }
         transform
     }
 
+    pub fn position(&self, slot: Slot) -> Position {
+        let size = self.size as i8;
+        Position { x: slot.0 % size, y: slot.0 / size }
+    }
+
+    pub fn slot(&self, position:Position) -> Slot {
+        Slot(position.x + position.y * (self.size as i8))
+    }
+
+    pub fn slot_for(&self, position: Position) -> Option<Slot> {
+        let size = self.size as i8;
+        if position.x >= 0 && position.x < size && position.y >= 0 && position.y < size {
+            Some(self.slot(position))
+        } else {
+            None
+        }
+    }
+}
+
+impl Game for StandardBoard {
+    type State = State;
+    type Move = Move;
+    type Player = Player;
+    type Undo = Undo;
+
+    fn initial_state(&self) -> State {
+        INITIAL_STATE
+    }
+
+    fn to_move(&self, state: &State) -> Player {
+        state.to_move
+    }
+
+    fn next_player(&self, state: &State) -> Player {
+        state.next_player()
+    }
+
+    fn player_index(&self, player: Player) -> usize {
+        player.0 as usize
+    }
+
+    fn color(&self, player: Player) -> HeuristicValue {
+        match player {
+            Player(0) => 1,
+            Player(1) => -1,
+            _ => panic!("fn color was given player -> {:?} (only supports 0, 1)", player),
+        }
+    }
+
+    fn next_moves<T : MoveSink<Move>>(&self, state: &State, move_sink: &mut T) {
+        StandardBoard::next_moves(self, state, move_sink)
+    }
+
+    fn next_moves_in_stage<T : MoveSink<Move>>(&self, state: &State, stage: MoveStage, move_sink: &mut T) {
+        StandardBoard::next_moves_in_stage(self, state, stage, move_sink)
+    }
+
+    fn legal_move(&self, state: &State, mve: Move) -> bool {
+        StandardBoard::legal_move(self, state, mve)
+    }
+
+    fn history_key(&self, mve: Move) -> Option<(usize, usize)> {
+        mve.movement().map(|(from, to)| (from.0 as usize, to.0 as usize))
+    }
+
+    fn apply(&self, mve: Move, state: &State) -> State {
+        StandardBoard::apply(self, mve, state)
+    }
+
+    fn make_move(&self, state: &mut State, mve: Move) -> Undo {
+        StandardBoard::make_move(self, state, mve)
+    }
+
+    fn unmake_move(&self, state: &mut State, mve: Move, undo: Undo) {
+        StandardBoard::unmake_move(self, state, mve, undo)
+    }
+
+    fn hash(&self, state: &State) -> StateHash {
+        StandardBoard::hash(self, state)
+    }
+
+    fn delta_hash(&self, state: &State, mve: Move) -> StateHash {
+        StandardBoard::delta_hash(self, state, mve)
+    }
+
+    fn canonical_hash(&self, state: &State) -> (StateHash, Symmetry) {
+        StandardBoard::canonical_hash(self, state)
+    }
+
+    fn to_canonical_move(&self, mve: Move, symmetry: Symmetry) -> Move {
+        StandardBoard::to_canonical_move(self, mve, symmetry)
+    }
+
+    fn from_canonical_move(&self, mve: Move, symmetry: Symmetry) -> Move {
+        StandardBoard::from_canonical_move(self, mve, symmetry)
+    }
+
+    fn winning_move(&self, state: &State, mve: Move) -> bool {
+        self.ascension_winning_move(state, mve)
+    }
+
+    fn winning_threat(&self, state: &State, player: Player) -> bool {
+        self.ascension_threat(state, player)
+    }
+
+    #[allow(unused_variables)]
+    fn no_moves_value(&self, state: &State) -> HeuristicValue {
+        WORST // trapped, you lose
+    }
+
+    fn print(&self, state: &State) -> String {
+        StandardBoard::print(self, state)
+    }
+}
+
+
+
+impl StandardBoard {
     pub fn transform_packed(transform: &SlotTransform, packed:Packed1) -> Packed1 {
         let mut out = PACKED1_EMPTY;
 
@@ -57,7 +229,7 @@ impl StandardBoard {
     pub fn transform_state(&self, state: &State, slot_transform: &SlotTransform) -> State {
         let mut new_state = state.clone();
 
-        for i in 0..25 {
+        for i in 0..(self.slots.len() as i8) {
             let from = Slot(i);
             let to = slot_transform.slots[i as usize];
 
@@ -73,8 +245,9 @@ impl StandardBoard {
     }
 
     pub fn slot_for(&self, position: Position) -> Option<Slot> {
-        if position.x < 5 && position.y < 5 {
-            Some(StandardBoard::slot(position))
+        let size = self.size as i8;
+        if position.x >= 0 && position.x < size && position.y >= 0 && position.y < size {
+            Some(self.slot(position))
         } else {
             None
         }
@@ -87,57 +260,24 @@ impl StandardBoard {
     }
 
     pub fn new(hash:ZobristHash) -> StandardBoard {
-        let mut slots = [Slot(0) ; 25];
-        let mut adjacencies = [[NONE ; 8] ; 25];
-
-        let mut packed_adjacencies = [PACKED1_EMPTY ; SLOT_COUNT];
-
-        for i in 0..25 {
-            let slot = Slot(i as i8);
-            slots[i] = slot;
-            let pos = StandardBoard::position(slot);
-            // produce adjacencies based on position
-            let x = pos.x;
-            let y = pos.y;
-
-            let mut j = 0;
-
-
-            let mut packed = PACKED1_EMPTY;
-
-            for nx in (x-1)..(x+2) {
-                for ny in (y-1)..(y+2) {
-                    let adjacent_position = Position { x: nx, y : ny };
-                    if !(nx == x && ny == y) && nx >= 0 && nx < (BOARD_SIZE as i8) && ny >= 0 && ny < (BOARD_SIZE as i8) {
-                        let slot = StandardBoard::slot(adjacent_position);
-                        adjacencies[i][j] = slot;
-                        j += 1;
-
-                        packed.0 |= 1 << slot.0;
-                    }
-                }
-            }
+        StandardBoard::with_size(DEFAULT_BOARD_SIZE, hash)
+    }
 
-            packed_adjacencies[i] = packed;
-        }
+    pub fn with_size(size: usize, hash:ZobristHash) -> StandardBoard {
+        let geometry = Geometry::new(size);
+        assert!(hash.buildings.len() >= geometry.slots.len(), "zobrist hash doesn't cover a {}x{} board", size, size);
 
         let mut board = StandardBoard {
-            slots: slots,
-            adjacencies: adjacencies,
-            packed_adjacencies: packed_adjacencies,
-            transforms: [EMPTY_SLOT_TRANSFORM; 7],
+            size: size,
+            slots: geometry.slots,
+            adjacencies: geometry.adjacencies,
+            packed_adjacencies: geometry.packed_adjacencies,
+            inverse_transforms: geometry.transforms.iter().map(|t| t.inverse()).collect(),
+            transforms: geometry.transforms,
+            tables: MoveTables::empty(),
             hash: hash,
         };
-
-        board.transforms = [
-            board.transform(rotate_90),
-            board.transform(rotate_180),
-            board.transform(rotate_270),
-            board.transform(reflect_x),
-            board.transform(reflect_y),
-            board.transform(reflect_diag_a),
-            board.transform(reflect_diag_b)
-        ];
+        board.tables = MoveTables::new(&board);
 
         board
     }
@@ -159,99 +299,135 @@ impl StandardBoard {
         out
     }
 
-    pub fn next_moves<T : MoveSink>(&self, state:&State, move_sink: &mut T) {
+    pub fn next_moves<T : MoveSink<Move>>(&self, state:&State, move_sink: &mut T) {
         let player_to_move = state.to_move;
         let builders = state.builders[player_to_move.0 as usize];
         let in_placement_phase = builders.0 == 0;
 
-        let collision = state.builders[0] | state.builders[1] | state.domes;
-        let available = !collision;
-
         if in_placement_phase {
-            let mut seen : HashSet<[Packed1; 2]> = HashSet::default();
-
-            for a in 0..25 {
-                let a_mask = 1 << a;
-                if a_mask & collision.0 == 0 {
-                    for b in (a+1)..25 {
-                        let b_mask = 1 << b;
-                        if b_mask & collision.0 == 0 {
-                            let both_placed = Packed1(a_mask | b_mask);
-                            let mut dupe = false;
-
-                            let mut new_builders = state.builders;
-                            new_builders[player_to_move.0 as usize] = both_placed;
-
-                            for slot_transform in &self.transforms {
-                                let transformed_builders = StandardBoard::transform_packed2(slot_transform, new_builders); // this is the only usage of Packed2
-                                if seen.contains(&transformed_builders) {
-                                    dupe = true;
-                                    break;
-                                }
-                            }
+            self.sink_placements(state, move_sink);
+        } else if !state.god().standard_moves() || state.athena_blocks {
+            self.next_god_moves(state, move_sink);
+        } else {
+            self.sink_standard_moves(state, None, move_sink);
+        }
+    }
 
-                            if !dupe {
-                                move_sink.sink(Move::PlaceBuilders { a: Slot(a), b:Slot(b) });    
-                                seen.insert(new_builders);
-                            }
-                        }
-                    }
-                }
+    // next_moves split up for StagedMoves, placement and god moves all come in the last stage
+    pub fn next_moves_in_stage<T : MoveSink<Move>>(&self, state:&State, stage: MoveStage, move_sink: &mut T) {
+        let builders = state.builders[state.to_move.0 as usize];
+        if builders.0 == 0 || !state.god().standard_moves() || state.athena_blocks {
+            if stage == MoveStage::Rest {
+                self.next_moves(state, move_sink);
             }
         } else {
-            let heights = state.building_map();
-
-            let mut available_builders = [
-                builders, // anyone can move to height 0
-                builders, // anyone can move to height 1
-                PACKED1_EMPTY,
-                PACKED1_EMPTY,
-            ];
-
-            for bl in builders.iter() {
-                let height = state.get_building_height(bl);
-                let max_height = min(height + 1, 3);
-                for h in 2..(max_height+1) {
-                    available_builders[h as usize].0 |= 1 << bl.0;
+            match stage {
+                MoveStage::Winning | MoveStage::Climbing | MoveStage::Rest => self.sink_standard_moves(state, Some(stage), move_sink),
+                _ => (),
+            }
+        }
+    }
+
+    // a cheaper check than generating everything for plain moves, which is most of what the table hands back
+    pub fn legal_move(&self, state:&State, mve:Move) -> bool {
+        let builders = state.builders[state.to_move.0 as usize];
+        match mve {
+            Move::Move { from, to, build } if builders.any() && state.god().standard_moves() && !state.athena_blocks => {
+                let size = self.slots.len() as i8;
+                if [from, to, build].iter().any(|sl| sl.0 < 0 || sl.0 >= size) {
+                    return false;
                 }
+                let available = !state.collision();
+                builders.get(from) > 0 &&
+                    (self.packed_adjacencies[from.0 as usize] & available).get(to) > 0 &&
+                    state.get_building_height(to) <= state.get_building_height(from) + 1 &&
+                    (self.packed_adjacencies[to.0 as usize] & (available | Packed1(1 << from.0))).get(build) > 0
+            },
+            _ => {
+                let mut moves = Vec::new();
+                self.next_moves_for_player(state, &mut moves);
+                moves.contains(&mve)
+            },
+        }
+    }
+
+    // plain moves, from the highest destination down. a stage keeps only wins (onto level 3, or pan dropping two
+    // or more), climbs or the rest
+    fn sink_standard_moves<T : MoveSink<Move>>(&self, state:&State, stage: Option<MoveStage>, move_sink: &mut T) {
+        let builders = state.builders[state.to_move.0 as usize];
+        let available = !state.collision();
+        let heights = state.building_map();
+        let pan = state.god() == God::Pan;
+
+        let mut available_builders = [
+            builders, // anyone can move to height 0
+            builders, // anyone can move to height 1
+            PACKED1_EMPTY,
+            PACKED1_EMPTY,
+        ];
+
+        for bl in builders.iter() {
+            let height = state.get_building_height(bl);
+            let max_height = min(height + 1, 3);
+            for h in 2..(max_height+1) {
+                available_builders[h as usize].0 |= 1 << bl.0;
             }
+        }
 
-            // these are really any mutually exclusive masks ..... as long as they're mutually exclusive we can do whatever
-
-            // this is dual height based
-
-           
-            let mut h = 3_usize; 
-            loop {
-                let receive = heights[h] & available;
-
-                if available_builders[h].any() {
-                    for move_from in available_builders[h].iter() {
-                        let move_tos = receive & self.packed_adjacencies[move_from.0 as usize];
-                        if move_tos.any() {
-                            for move_to in move_tos.iter() {
-                                let buildable_adjacencies = self.packed_adjacencies[move_to.0 as usize] & available ^ Packed1(1 << move_from.0); 
-                                // there's always buildabl adjacencies ... or you couldnt have moved
-                                for &build_height in &HEIGHT_BUILDER_ORDER {
-                                    let buildings_of_height = buildable_adjacencies & heights[build_height];
-                                    if buildings_of_height.any() {
-                                        for build_at in buildings_of_height.iter() {
-                                            move_sink.sink(Move::Move { from: move_from, to:move_to, build: build_at });
-                                        }    
-                                    }
-                                }
-                            }    
+        // the squares open to build on by HEIGHT_BUILDER_ORDER, once per call. each builder only adds the slot
+        // it leaves from
+        let open_by_height = [
+            available & heights[HEIGHT_BUILDER_ORDER[0]],
+            available & heights[HEIGHT_BUILDER_ORDER[1]],
+            available & heights[HEIGHT_BUILDER_ORDER[2]],
+            available & heights[HEIGHT_BUILDER_ORDER[3]],
+        ];
+
+        let mut h = 3_usize;
+        loop {
+            let receive = heights[h] & available;
+
+            if available_builders[h].any() {
+                for move_from in available_builders[h].iter() {
+                    let from_height = state.get_building_height(move_from);
+                    let winning = h == 3 || (pan && from_height >= h as u8 + 2);
+                    let wanted = match stage {
+                        None => true,
+                        Some(MoveStage::Winning) => winning,
+                        Some(MoveStage::Climbing) => !winning && h as u8 > from_height,
+                        Some(_) => !winning && h as u8 <= from_height,
+                    };
+                    if !wanted || !(receive & self.packed_adjacencies[move_from.0 as usize]).any() {
+                        continue;
+                    }
+
+                    let mut open = open_by_height;
+                    for (i, &order) in HEIGHT_BUILDER_ORDER.iter().enumerate() {
+                        if order == from_height as usize {
+                            open[i].0 |= 1 << move_from.0;
                         }
-                    }    
-                }
-                
+                    }
 
-                if h == 0 {
-                    break;
+                    for &(move_to, build_squares) in &self.tables.destinations[move_from.0 as usize] {
+                        if receive.get(move_to) == 0 {
+                            continue;
+                        }
+                        // there's always buildable adjacencies ... or you couldnt have moved
+                        for &open_at_height in &open {
+                            for build_at in (build_squares & open_at_height).iter() {
+                                move_sink.sink(Move::Move { from: move_from, to:move_to, build: build_at });
+                            }
+                        }
+                    }
                 }
+            }
 
-                h -= 1;
+            // wins are only ever onto level 3, unless pan's falling
+            if h == 0 || (stage == Some(MoveStage::Winning) && !pan) {
+                break;
             }
+
+            h -= 1;
         }
     }
 
@@ -264,11 +440,12 @@ impl StandardBoard {
         let available = !collision;
 
         if in_placement_phase {
-            for a in 0..25 {
+            let slot_count = self.slots.len() as i8;
+            for a in 0..slot_count {
                 let a_mask = 1 << a;
                 let slot_a = Slot(a);
                 if a_mask & collision.0 == 0 {
-                    for b in 0..25 {
+                    for b in 0..slot_count {
                         let b_mask = 1 << b;
                         let slot_b = Slot(b);
                         if a != b && b_mask & collision.0 == 0 {
@@ -277,6 +454,8 @@ impl StandardBoard {
                     }    
                 }
             }
+        } else if !state.god().standard_moves() || state.athena_blocks {
+            self.next_god_moves(state, move_sink);
         } else {
             for move_from in builders.iter() {
                 let current_height = state.get_building_height(move_from);
@@ -294,15 +473,16 @@ impl StandardBoard {
         }
     }
 
-    pub fn position(slot: Slot) -> Position {
-        // slot is 0 -> 24
-        let x = slot.0 % 5;
-        let y = slot.0 / 5;
+    pub fn position(&self, slot: Slot) -> Position {
+        // slot is 0 -> size * size - 1
+        let size = self.size as i8;
+        let x = slot.0 % size;
+        let y = slot.0 / size;
         Position { x:x, y:y }
     }
 
-    pub fn slot(position:Position) -> Slot {
-        Slot(position.x + position.y * 5)
+    pub fn slot(&self, position:Position) -> Slot {
+        Slot(position.x + position.y * (self.size as i8))
     }
 
     pub fn hash(&self, state: &State) -> StateHash {
@@ -318,58 +498,183 @@ impl StandardBoard {
             hash = hash ^ self.hash.buildings[sl.0 as usize][state.hash_height(sl)];
         }
 
+        if state.athena_blocks {
+            hash = hash ^ self.hash.athena_blocks;
+        }
+
         hash
     }
 
     pub fn apply(&self, mve:Move, state:&State) -> State {
-        match mve {
-            Move::PlaceBuilders { a, b } => {
-                let player_to_move = state.to_move;
+        let mut new_state = state.clone();
+        self.make_move(&mut new_state, mve);
+        new_state
+    }
 
-                let mut new_state = state.clone();
+    pub fn make_move(&self, state:&mut State, mve:Move) -> Undo {
+        let undo = Undo { athena_blocks: state.athena_blocks };
+        let athena_blocks = self.athena_blocks_after(state, mve);
+        let player_to_move = state.to_move.0 as usize;
+        let opponent = state.next_player().0 as usize;
 
-                let new_builder_mask = Packed1(1 << a.0 | 1 << b.0);
+        match mve {
+            Move::PlaceBuilders { a, b } => {
+                state.builders[player_to_move] |= Packed1(1 << a.0 | 1 << b.0);
+            },
+            Move::Move { from, to, build } => {
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+                state.build_at(build);
+            },
+            Move::DoubleBuild { from, to, build, second } => {
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+                state.build_at(build);
+                state.build_at(second);
+            },
+            Move::Swap { from, to, build } => {
+                let movement_mask = Packed1(1 << from.0 | 1 << to.0);
+                state.builders[player_to_move] ^= movement_mask;
+                state.builders[opponent] ^= movement_mask;
+                state.build_at(build);
+            },
+            Move::Push { from, to, build } => {
+                let pushed_to = self.push_target(from, to).expect("push to land on the board");
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+                state.builders[opponent] ^= Packed1(1 << to.0 | 1 << pushed_to.0);
+                state.build_at(build);
+            },
+            Move::Dome { from, to, build } => {
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+                state.domes.set(build, 1);
+            },
+            Move::BuildFirst { first, from, to, build } => {
+                state.build_at(first);
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+                state.build_at(build);
+            },
+            Move::Travel { from, to, other_from, other_to, build } => {
+                // toggled one at a time, a builder that stays put (or steps into the other's old square) cancels out
+                state.builders[player_to_move] ^= Packed1(1 << from.0) ^ Packed1(1 << to.0) ^ Packed1(1 << other_from.0) ^ Packed1(1 << other_to.0);
+                state.build_at(build);
+            },
+        }
 
-                new_state.builders[player_to_move.0 as usize] |= new_builder_mask;
-                // new_state.collision |= new_builder_mask;
+        state.athena_blocks = athena_blocks;
+        state.to_move = state.next_player();
+        undo
+    }
 
-                new_state.to_move = new_state.next_player();                
+    // exactly reverses make_move, builds come off in the opposite order they went on
+    pub fn unmake_move(&self, state:&mut State, mve:Move, undo:Undo) {
+        state.to_move = state.next_player();
+        state.athena_blocks = undo.athena_blocks;
+        let player_to_move = state.to_move.0 as usize;
+        let opponent = state.next_player().0 as usize;
 
-                new_state
+        match mve {
+            Move::PlaceBuilders { a, b } => {
+                state.builders[player_to_move] ^= Packed1(1 << a.0 | 1 << b.0);
             },
             Move::Move { from, to, build } => {
-                let player_to_move = state.to_move;
-                let mut new_state = state.clone();
-
+                state.unbuild_at(build);
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+            },
+            Move::DoubleBuild { from, to, build, second } => {
+                state.unbuild_at(second);
+                state.unbuild_at(build);
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+            },
+            Move::Swap { from, to, build } => {
                 let movement_mask = Packed1(1 << from.0 | 1 << to.0);
-
-                new_state.builders[player_to_move.0 as usize] ^= movement_mask;
-                // new_state.collision ^= movement_mask;
-               
-                new_state.build_at(build);
-          
-                new_state.to_move = new_state.next_player();
-                new_state
+                state.unbuild_at(build);
+                state.builders[player_to_move] ^= movement_mask;
+                state.builders[opponent] ^= movement_mask;
+            },
+            Move::Push { from, to, build } => {
+                let pushed_to = self.push_target(from, to).expect("push to land on the board");
+                state.unbuild_at(build);
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+                state.builders[opponent] ^= Packed1(1 << to.0 | 1 << pushed_to.0);
+            },
+            Move::Dome { from, to, build } => {
+                state.domes.set(build, 0);
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+            },
+            Move::BuildFirst { first, from, to, build } => {
+                state.unbuild_at(build);
+                state.builders[player_to_move] ^= Packed1(1 << from.0 | 1 << to.0);
+                state.unbuild_at(first);
+            },
+            Move::Travel { from, to, other_from, other_to, build } => {
+                state.unbuild_at(build);
+                state.builders[player_to_move] ^= Packed1(1 << from.0) ^ Packed1(1 << to.0) ^ Packed1(1 << other_from.0) ^ Packed1(1 << other_to.0);
             },
         }
     }
 
+    pub fn athena_blocks_after(&self, state: &State, mve:Move) -> bool {
+        if state.god() != God::Athena {
+            return false;
+        }
+        match mve.movement() {
+            Some((from, to)) => state.get_building_height(to) > state.get_building_height(from),
+            None => false,
+        }
+    }
+
     pub fn delta_hash(&self, state: &State, mve:Move) -> StateHash { // this implicitly destroys the state atm.
-        match mve {
+        let athena = if state.athena_blocks != self.athena_blocks_after(state, mve) {
+            self.hash.athena_blocks
+        } else {
+            STATE_HASH_ZERO
+        };
+        let to_move = state.to_move.0 as usize;
+        let opponent = state.next_player().0 as usize;
+        let moved = |from: Slot, to: Slot| self.hash.builders[to_move][from.0 as usize] ^ self.hash.builders[to_move][to.0 as usize];
+
+        let delta = match mve {
             Move::PlaceBuilders { a, b } => {
-               let to_move = state.to_move.0 as usize;
-               self.hash.switch_move ^ self.hash.builders[to_move][a.0 as usize] ^ self.hash.builders[to_move][b.0 as usize]
+               self.hash.builders[to_move][a.0 as usize] ^ self.hash.builders[to_move][b.0 as usize]
             },
             Move::Move { from, to, build } => {
-                let to_move = state.to_move.0 as usize;
-                let original_height = state.hash_height(build);
-
+                moved(from, to) ^ self.built_hash(state, build, 1)
+            },
+            Move::DoubleBuild { from, to, build, second } if build == second => {
+                moved(from, to) ^ self.built_hash(state, build, 2)
+            },
+            Move::DoubleBuild { from, to, build, second } => {
+                moved(from, to) ^ self.built_hash(state, build, 1) ^ self.built_hash(state, second, 1)
+            },
+            Move::Swap { from, to, build } => {
+                moved(from, to) ^ self.hash.builders[opponent][to.0 as usize] ^ self.hash.builders[opponent][from.0 as usize] ^
+                self.built_hash(state, build, 1)
+            },
+            Move::Push { from, to, build } => {
+                let pushed_to = self.push_target(from, to).expect("push to land on the board");
+                moved(from, to) ^ self.hash.builders[opponent][to.0 as usize] ^ self.hash.builders[opponent][pushed_to.0 as usize] ^
+                self.built_hash(state, build, 1)
+            },
+            Move::Dome { from, to, build } => {
                 let build_at = build.0 as usize;
-
-                self.hash.switch_move ^ self.hash.builders[to_move][from.0 as usize] ^ self.hash.builders[to_move][to.0 as usize] ^ 
-                self.hash.buildings[build_at][original_height] ^ self.hash.buildings[build_at][original_height + 1]
+                moved(from, to) ^ self.hash.buildings[build_at][state.hash_height(build)] ^ self.hash.buildings[build_at][4]
             },
-        }
+            Move::BuildFirst { first, from, to, build } if first == build => {
+                moved(from, to) ^ self.built_hash(state, build, 2)
+            },
+            Move::BuildFirst { first, from, to, build } => {
+                moved(from, to) ^ self.built_hash(state, first, 1) ^ self.built_hash(state, build, 1)
+            },
+            Move::Travel { from, to, other_from, other_to, build } => {
+                moved(from, to) ^ moved(other_from, other_to) ^ self.built_hash(state, build, 1)
+            },
+        };
+
+        self.hash.switch_move ^ delta ^ athena
+    }
+
+    // building on slot that many times from where state has it, a dome is the level after 3
+    fn built_hash(&self, state: &State, slot: Slot, builds: usize) -> StateHash {
+        let height = state.hash_height(slot);
+        self.hash.buildings[slot.0 as usize][height] ^ self.hash.buildings[slot.0 as usize][height + builds]
     }
 
     pub fn valid(slot:Slot) -> bool {
@@ -378,26 +683,75 @@ impl StandardBoard {
 
     // use this to detect before applying move
     pub fn ascension_winning_move(&self, state:&State, mve: Move) -> bool {
-          match mve {
-            Move::PlaceBuilders { .. } => false,
-            Move::Move { to, .. } => state.get_building_height(to) == 3,
+        match mve.movement() {
+            None => false,
+            Some((from, to)) => {
+                let to_height = match mve {
+                    Move::BuildFirst { first, .. } if first == to => state.get_building_height(to) + 1,
+                    _ => state.get_building_height(to),
+                };
+                // pan also wins by jumping down two or more levels
+                to_height == 3 || (state.god() == God::Pan && state.get_building_height(from) >= to_height + 2)
+            },
         }
     }
 
+    // whether player could win on the spot if it were their move: a builder on level 2 next to an open level 3
+    // (or pan with somewhere to drop to). other gods' powers go through their generated moves
+    pub fn ascension_threat(&self, state:&State, player: Player) -> bool {
+        let builders = state.builders[player.0 as usize];
+        let god = state.gods[player.0 as usize];
+        if !builders.any() {
+            return false;
+        }
+
+        if !god.standard_moves() {
+            let mut as_player = state.clone();
+            as_player.to_move = player;
+            as_player.athena_blocks = player == state.to_move && state.athena_blocks;
+            let mut moves = Vec::new();
+            self.next_moves(&as_player, &mut moves);
+            return moves.iter().any(|&mve| self.ascension_winning_move(&as_player, mve));
+        }
+
+        let heights = state.building_map();
+        let open = !state.collision();
+        let can_climb = player != state.to_move || !state.athena_blocks;
+
+        if can_climb {
+            for from in (builders & heights[2]).iter() {
+                if (self.packed_adjacencies[from.0 as usize] & heights[3] & open).any() {
+                    return true;
+                }
+            }
+        }
+
+        if god == God::Pan {
+            for from in (builders & (heights[2] | heights[3])).iter() {
+                let drop_to = if state.get_building_height(from) == 3 { heights[0] | heights[1] } else { heights[0] };
+                if (self.packed_adjacencies[from.0 as usize] & drop_to & open).any() {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
     pub fn print(&self, state:&State) -> String {
         let mut out = String::new();
 
         out.push_str(&format!(" === To move {:?} === \n", state.to_move));
-        let divider = "+---+---+---+---+---+\n";
-        let empty =   "|   |   |   |   |   |\n";
+        let divider = format!("{}+\n", "+---".repeat(self.size));
+        let empty = format!("{}|\n", "|   ".repeat(self.size));
 
-        for y in 0..BOARD_SIZE {
-            out.push_str(divider);
+        for y in 0..self.size {
+            out.push_str(&divider);
             let mut terrain : Vec<String> = Vec::new();
             let mut players : Vec<String> = Vec::new();
 
-            for x in 0..BOARD_SIZE {
-                let slot = Self::slot(Position { x: x as i8 , y: y as i8 });
+            for x in 0..self.size {
+                let slot = self.slot(Position { x: x as i8 , y: y as i8 });
                 // terrain
                 if state.domes.get(slot) > 0 {
                     terrain.push("D".into());
@@ -430,9 +784,226 @@ impl StandardBoard {
             out.push_str("|");
             out.push_str(&players.join("|"));
             out.push_str("|\n");
-            out.push_str(empty);
+            out.push_str(&empty);
         }
-        out.push_str(divider);
+        out.push_str(&divider);
         out
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn board_sizes() {
+        for size in MIN_BOARD_SIZE..(MAX_BOARD_SIZE + 1) {
+            let board = StandardBoard::with_size(size, ZobristHash::new_unseeded());
+            assert_eq!(board.slots.len(), size * size);
+
+            let adjacency_count = |slot: Slot| board.packed_adjacencies[slot.0 as usize].count();
+            assert_eq!(adjacency_count(Slot(0)), 3);
+            assert_eq!(adjacency_count(board.slot(Position { x: 1, y: 1 })), 8);
+            assert_eq!(adjacency_count(board.slot(Position { x: size as i8 - 1, y: 2 })), 5);
+
+            assert_eq!(board.transforms.len(), 7);
+            for transform in &board.transforms {
+                assert!(transform.check(), "{}x{} transform isn't a permutation", size, size);
+            }
+
+            assert_eq!(board.print(&INITIAL_STATE).matches("+---").count(), size * (size + 1));
+        }
+    }
+
+    #[test]
+    fn standard_rotation() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        assert_eq!(board.transforms[0].slots[0], Slot(4));
+        assert_eq!(board.transforms[0].slots[12], Slot(12));
+    }
+
+    #[test]
+    fn large_board_play() {
+        let board = StandardBoard::with_size(7, ZobristHash::new_unseeded());
+        let mut state = INITIAL_STATE;
+        for &mve in &vec![Move::PlaceBuilders { a: Slot(16), b: Slot(48) },
+                          Move::PlaceBuilders { a: Slot(24), b: Slot(40) }] {
+            state = board.apply(mve, &state);
+        }
+
+        let hash = board.hash(&state);
+        let mut moves = Vec::new();
+        board.next_moves(&state, &mut moves);
+        assert!(moves.iter().any(|m| m.movement() == Some((Slot(48), Slot(47)))));
+        for &mve in &moves {
+            assert_eq!(hash ^ board.delta_hash(&state, mve), board.hash(&board.apply(mve, &state)));
+        }
+
+        let mut ev_state = EvState::new();
+        let (best, _) = NegaMaxAlphaBetaExp::evaluate_moves::<AdjustedNeighbourHeuristic>(&mut ev_state, &board, &state, 3);
+        assert!(best.is_some());
+    }
+
+    #[test]
+    fn make_unmake_restores() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut gods = GODS.to_vec();
+        gods.push(God::Mortal);
+
+        for &god in &gods {
+            let mut state = State::with_gods([god, God::Athena]);
+            for &mve in &vec![Move::PlaceBuilders { a: Slot(6), b: Slot(18) },
+                              Move::PlaceBuilders { a: Slot(7), b: Slot(24) }] {
+                state = board.apply(mve, &state);
+            }
+            state.set_building_height(Slot(11), 3); // builds here toggle a dome
+            state.set_building_height(Slot(12), 2);
+            state.set_building_height(Slot(1), 1);
+            state.athena_blocks = true;
+            let original = state.clone();
+            let hash = board.hash(&state);
+
+            let mut moves = Vec::new();
+            board.next_moves(&state, &mut moves);
+            for &mve in &moves {
+                let delta = board.delta_hash(&state, mve);
+                let undo = board.make_move(&mut state, mve);
+                assert_eq!(board.hash(&state), hash ^ delta, "{:?} {:?}", god, mve);
+
+                // and a reply on top, to unwind two deep
+                let mut replies = Vec::new();
+                board.next_moves(&state, &mut replies);
+                let after_move = state.clone();
+                for &reply in &replies {
+                    let reply_undo = board.make_move(&mut state, reply);
+                    board.unmake_move(&mut state, reply, reply_undo);
+                    assert_eq!(state, after_move, "{:?} {:?} {:?}", god, mve, reply);
+                }
+
+                board.unmake_move(&mut state, mve, undo);
+                assert_eq!(state, original, "{:?} {:?}", god, mve);
+                assert_eq!(board.hash(&state), hash);
+            }
+        }
+    }
+
+    #[test]
+    fn staged_moves() {
+        use super::super::tests::*;
+
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut god_state = a_in_1(&board, Player(0));
+        god_state.gods[0] = God::Minotaur;
+        let mut athena_state = b_in_1(&board, Player(1));
+        athena_state.athena_blocks = true;
+        // pan on level 2 can drop to the ground next to it as well as climb
+        let mut pan_state = a_in_1(&board, Player(0));
+        pan_state.gods[0] = God::Pan;
+        let mut pan_wins = Vec::new();
+        board.next_moves_in_stage(&pan_state, MoveStage::Winning, &mut pan_wins);
+        assert!(pan_wins.iter().any(|mve| mve.movement() == Some((Slot(1), Slot(6)))), "{:?}", pan_wins);
+
+        let states = vec![INITIAL_STATE, distant_state(&board), a_in_1(&board, Player(0)), a_in_2(&board, Player(1)),
+                          b_blockable(&board, Player(1)), any_trap_in_1(&board, Player(0)), god_state, athena_state,
+                          pan_state];
+
+        for state in &states {
+            let mut moves = Vec::new();
+            board.next_moves(state, &mut moves);
+
+            let mut staged = Vec::new();
+            for &stage in &[MoveStage::TTMove, MoveStage::Winning, MoveStage::Climbing, MoveStage::Rest] {
+                let mut stage_moves = Vec::new();
+                board.next_moves_in_stage(state, stage, &mut stage_moves);
+                if state.current_builders().any() && state.god().standard_moves() && !state.athena_blocks {
+                    for &mve in &stage_moves {
+                        assert_eq!(board.winning_move(state, mve), stage == MoveStage::Winning, "{:?} {:?}", stage, mve);
+                    }
+                }
+                staged.extend(stage_moves);
+            }
+
+            let mut sorted_moves = moves.clone();
+            sorted_moves.sort_by_key(|m| format!("{:?}", m));
+            staged.sort_by_key(|m| format!("{:?}", m));
+            assert_eq!(staged, sorted_moves);
+
+            for &mve in &moves {
+                assert!(board.legal_move(state, mve), "{:?}", mve);
+            }
+
+            // the tt move first, then the rest without it
+            if let Some(&tt_move) = moves.last() {
+                let mut move_stack = MoveStack::new();
+                let mut generator = StagedMoves::new(&move_stack, Some(tt_move));
+                let mut handed_out = Vec::new();
+                while let Some(mve) = generator.next(&board, state, &mut move_stack) {
+                    handed_out.push(mve);
+                }
+                generator.finish(&mut move_stack);
+
+                assert_eq!(move_stack.next, 0);
+                assert_eq!(handed_out[0], tt_move);
+                assert_eq!(handed_out.len(), moves.len());
+                handed_out.sort_by_key(|m| format!("{:?}", m));
+                assert_eq!(handed_out, sorted_moves);
+            }
+        }
+
+        let state = distant_state(&board);
+        assert!(board.legal_move(&state, Move::Move { from: Slot(0), to: Slot(5), build: Slot(0) }));
+        assert!(!board.legal_move(&state, Move::Move { from: Slot(0), to: Slot(2), build: Slot(3) }));
+        assert!(!board.legal_move(&state, Move::Move { from: Slot(23), to: Slot(18), build: Slot(13) }));
+    }
+
+    #[test]
+    fn killers_and_history() {
+        use super::super::tests::*;
+
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = a_in_1(&board, Player(0));
+        state.set_building_height(Slot(6), 1); // something to climb that doesn't win
+
+        let mut moves = Vec::new();
+        board.next_moves(&state, &mut moves);
+        let stage_moves = |stage| {
+            let mut stage_moves = Vec::new();
+            board.next_moves_in_stage(&state, stage, &mut stage_moves);
+            stage_moves
+        };
+        let (winning, climbing, rest) = (stage_moves(MoveStage::Winning), stage_moves(MoveStage::Climbing), stage_moves(MoveStage::Rest));
+        assert!(!winning.is_empty() && !climbing.is_empty() && rest.len() > 2);
+
+        // a killer from the rest, one that isn't legal here, and history for the last of the rest
+        let killer = rest[0];
+        let illegal = Move::Move { from: Slot(23), to: Slot(18), build: Slot(13) };
+        let favoured = *rest.last().unwrap();
+        let mut ordering = MoveOrdering::new();
+        ordering.add_killer(3, illegal);
+        ordering.add_killer(3, killer);
+        ordering.add_history(&board, favoured, 4);
+        assert_eq!(ordering.killers(3), [Some(killer), Some(illegal)]);
+
+        let mut move_stack = MoveStack::new();
+        let mut generator = StagedMoves::with_killers(&move_stack, None, ordering.killers(3));
+        let mut handed_out = Vec::new();
+        while let Some(mve) = generator.next_by_history(&board, &state, &mut move_stack, &ordering) {
+            handed_out.push(mve);
+        }
+        generator.finish(&mut move_stack);
+
+        assert_eq!(&handed_out[..winning.len()], &winning[..]);
+        assert_eq!(handed_out[winning.len()], killer);
+        // anything from the same builder to the same square shares the history
+        let rest_begin = winning.len() + 1 + climbing.len();
+        assert_eq!(handed_out[rest_begin].movement(), favoured.movement());
+
+        let mut sorted_moves = moves.clone();
+        sorted_moves.sort_by_key(|m| format!("{:?}", m));
+        handed_out.sort_by_key(|m| format!("{:?}", m));
+        assert_eq!(handed_out, sorted_moves);
+
+        ordering.new_search();
+        assert_eq!(ordering.killers(3), [None, None]);
+        assert_eq!(ordering.history_score(&board, favoured), 8);
+    }
+}
@@ -0,0 +1,237 @@
+use game::santorini::*;
+
+use std;
+use std::fmt;
+
+// reads back the diagrams StandardBoard::print writes
+//    === To move Player(0) ===
+//   +---+---+---+---+---+
+//   |   | 2 | 3 |   |   |
+//   |P0 |P0 |   |   |   |
+//   |   |   |   |   |   |
+//   +---+---+---+---+---+
+//   ...
+// and hand typed ones: the header, blank lines, outer dividers and cell widths are all optional, rows are
+// split by any line of + - =, and a cell holds any mix of a height 0-3, D, a builder P0 / P1 (or A / B) and .
+// the diagram doesn't show gods, athena or what's under a dome, so those come back as mortals, no block
+// and a dome on 3 unless the cell gives a height. without a header, the player to move follows placement
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagramError {
+    Empty,
+    UnexpectedLine { line: usize, text: String },
+    BadPlayer(String),
+    BoardSize(usize), // rows
+    RowWidth { row: usize, expected: usize, found: usize },
+    BadCell { row: usize, column: usize, text: String },
+    Invalid(StateViolation), // reads fine, but isn't a position
+}
+
+impl fmt::Display for DiagramError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &DiagramError::Empty => write!(f, "no board rows found"),
+            &DiagramError::UnexpectedLine { line, ref text } => write!(f, "line {} {:?} isn't a header, divider or row of cells", line, text),
+            &DiagramError::BadPlayer(ref text) => write!(f, "{:?} isn't a player to move", text),
+            &DiagramError::BoardSize(size) => write!(f, "{} rows, boards are {} to {} wide", size, MIN_BOARD_SIZE, MAX_BOARD_SIZE),
+            &DiagramError::RowWidth { row, expected, found } => write!(f, "row {} has {} cells, expected {}", row + 1, found, expected),
+            &DiagramError::BadCell { row, column, ref text } => write!(f, "can't read {:?} at row {} column {}", text, row + 1, column + 1),
+            &DiagramError::Invalid(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for DiagramError {}
+
+fn parse_player(text: &str) -> Option<Player> {
+    match text.to_uppercase().as_str() {
+        "PLAYER(0)" | "P0" | "A" | "0" => Some(Player(0)),
+        "PLAYER(1)" | "P1" | "B" | "1" => Some(Player(1)),
+        _ => None,
+    }
+}
+
+fn is_divider(line: &str) -> bool {
+    line.chars().all(|c| c == '+' || c == '-' || c == '=' || c.is_whitespace())
+}
+
+// false if the cell has anything it shouldn't
+fn parse_cell(text: &str, slot: Slot, state: &mut State) -> bool {
+    let mut height = None;
+    let mut dome = false;
+    let mut builder = None;
+
+    for token in text.split_whitespace() {
+        match token.to_uppercase().as_str() {
+            "." => (),
+            "D" => dome = true,
+            "P0" | "A" if builder.is_none() => builder = Some(0),
+            "P1" | "B" if builder.is_none() => builder = Some(1),
+            other => match other.parse::<u8>() {
+                Ok(h) if h <= 3 && height.is_none() => height = Some(h),
+                _ => return false,
+            },
+        }
+    }
+
+    if dome && builder.is_some() {
+        return false;
+    }
+
+    state.set_building_height(slot, height.unwrap_or(if dome { 3 } else { 0 }));
+    if dome {
+        state.domes.set(slot, 1);
+    }
+    if let Some(i) = builder {
+        state.builders[i].set(slot, 1);
+    }
+    true
+}
+
+// the state and the size of board it's for
+pub fn parse_diagram_sized(text: &str) -> Result<(State, usize), DiagramError> {
+    let mut to_move = None;
+    let mut rows : Vec<Vec<String>> = Vec::new();
+    let mut row : Option<Vec<String>> = None; // cell contents so far, a row spans several lines
+
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let lower = line.to_lowercase();
+
+        if line.is_empty() {
+            continue;
+        } else if let Some(at) = lower.find("to move") {
+            let player = lower[(at + "to move".len())..].trim_matches(|c: char| c == '=' || c == ':' || c.is_whitespace());
+            to_move = Some(parse_player(player).ok_or(DiagramError::BadPlayer(player.into()))?);
+        } else if is_divider(line) {
+            if let Some(cells) = row.take() {
+                rows.push(cells);
+            }
+        } else if line.starts_with('|') {
+            let contents : Vec<&str> = line.trim_matches('|').split('|').collect();
+            let cells = row.get_or_insert_with(|| vec![String::new(); contents.len()]);
+            if contents.len() != cells.len() {
+                return Err(DiagramError::RowWidth { row: rows.len(), expected: cells.len(), found: contents.len() });
+            }
+            for (cell, content) in cells.iter_mut().zip(contents) {
+                cell.push(' ');
+                cell.push_str(content);
+            }
+        } else {
+            return Err(DiagramError::UnexpectedLine { line: idx + 1, text: line.into() });
+        }
+    }
+    if let Some(cells) = row.take() {
+        rows.push(cells);
+    }
+
+    let size = rows.len();
+    if size == 0 {
+        return Err(DiagramError::Empty);
+    }
+    if size < MIN_BOARD_SIZE || size > MAX_BOARD_SIZE {
+        return Err(DiagramError::BoardSize(size));
+    }
+
+    let mut state = INITIAL_STATE;
+    for (y, cells) in rows.iter().enumerate() {
+        if cells.len() != size {
+            return Err(DiagramError::RowWidth { row: y, expected: size, found: cells.len() });
+        }
+        for (x, cell) in cells.iter().enumerate() {
+            let slot = Slot((x + y * size) as i8);
+            if !parse_cell(cell, slot, &mut state) {
+                return Err(DiagramError::BadCell { row: y, column: x, text: cell.split_whitespace().collect::<Vec<_>>().join(" ") });
+            }
+        }
+    }
+
+    state.to_move = match to_move {
+        Some(player) => player,
+        None if state.builders[0].any() && !state.builders[1].any() => Player(1),
+        None => Player(0),
+    };
+
+    state.check(size).map_err(DiagramError::Invalid)?;
+
+    Ok((state, size))
+}
+
+impl StandardBoard {
+    pub fn parse_diagram(&self, text: &str) -> Result<State, DiagramError> {
+        let (state, size) = parse_diagram_sized(text)?;
+        if size == self.size {
+            Ok(state)
+        } else {
+            Err(DiagramError::BoardSize(size))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::*;
+
+    #[test]
+    fn round_trips() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut domed = any_trap_in_1(&board, Player(1));
+        domed.set_building_height(Slot(12), 3);
+        domed.domes.set(Slot(12), 1);
+
+        for state in vec![initial(&board, Player(0)), a_in_1(&board, Player(0)), b_in_2(&board, Player(1)), b_blockable(&board, Player(1)), domed] {
+            assert_eq!(board.parse_diagram(&board.print(&state)), Ok(state.clone()), "{}", board.print(&state));
+        }
+
+        let small = StandardBoard::with_size(4, ZobristHash::new_unseeded());
+        let mut state = small.apply(Move::PlaceBuilders { a: Slot(0), b: Slot(5) }, &INITIAL_STATE);
+        state.set_building_height(Slot(15), 2);
+        assert_eq!(small.parse_diagram(&small.print(&state)), Ok(state.clone()));
+        assert_eq!(board.parse_diagram(&small.print(&state)), Err(DiagramError::BoardSize(4)));
+    }
+
+    #[test]
+    fn hand_typed() {
+        let board = StandardBoard::with_size(4, ZobristHash::new_unseeded());
+        let text = "
+            to move: B
+            |A 2| 3 |   |   |
+            +---+---+---+---+
+            | A | . | . | . |
+            ---
+            |   |   | D | B |
+            ---
+            | 1 |   |   |B 1
+        ";
+
+        let mut expected = INITIAL_STATE;
+        expected.builders[0] = Packed1((1 << 0) | (1 << 4));
+        expected.builders[1] = Packed1((1 << 11) | (1 << 15));
+        expected.set_building_height(Slot(0), 2);
+        expected.set_building_height(Slot(1), 3);
+        expected.set_building_height(Slot(10), 3);
+        expected.domes.set(Slot(10), 1);
+        expected.set_building_height(Slot(12), 1);
+        expected.set_building_height(Slot(15), 1);
+        expected.to_move = Player(1);
+
+        assert_eq!(board.parse_diagram(text), Ok(expected));
+    }
+
+    #[test]
+    fn errors() {
+        let rows = |rows: &[&str]| rows.join("\n---\n");
+
+        assert_eq!(parse_diagram_sized("\n\n"), Err(DiagramError::Empty));
+        assert_eq!(parse_diagram_sized("+---+\n| 2 |\nhello"), Err(DiagramError::UnexpectedLine { line: 3, text: "hello".into() }));
+        assert_eq!(parse_diagram_sized(" === To move Player(2) === "), Err(DiagramError::BadPlayer("player(2)".into())));
+        assert_eq!(parse_diagram_sized(&rows(&["| | |", "| | |"])), Err(DiagramError::BoardSize(2)));
+        assert_eq!(parse_diagram_sized(&rows(&["| | | | |", "| | | | |", "| | | |", "| | | | |"])), Err(DiagramError::RowWidth { row: 2, expected: 4, found: 3 }));
+        assert_eq!(parse_diagram_sized("| | | | |\n| | | |"), Err(DiagramError::RowWidth { row: 0, expected: 4, found: 3 }));
+        assert_eq!(parse_diagram_sized(&rows(&["| | | | |", "| |4| | |", "| | | | |", "| | | | |"])), Err(DiagramError::BadCell { row: 1, column: 1, text: "4".into() }));
+        assert_eq!(parse_diagram_sized(&rows(&["|P0 P1| | | |", "| | | | |", "| | | | |", "| | | | |"])), Err(DiagramError::BadCell { row: 0, column: 0, text: "P0 P1".into() }));
+        assert_eq!(parse_diagram_sized(&rows(&["|D A| | | |", "| | | | |", "| | | | |", "| | | | |"])), Err(DiagramError::BadCell { row: 0, column: 0, text: "D A".into() }));
+        assert_eq!(parse_diagram_sized(&rows(&["|A| | | |", "| | | | |", "| | | | |", "| | | | |"])), Err(DiagramError::Invalid(StateViolation::BuilderCount { player: Player(0), count: 1 })));
+    }
+}
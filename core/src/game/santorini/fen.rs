@@ -0,0 +1,246 @@
+use game::santorini::*;
+
+use std;
+use std::fmt;
+use std::str::FromStr;
+
+// one line positions, rows top to bottom separated by /, then the player to move, then anything non default
+//   0A0A000/00000/00000/00000/0000B0B A
+//   2A3D100/0A0000/00000/00000/0000B0B B Athena,Apollo athena
+// every cell is a height 0-3, followed by D for a dome, or A / B for player 0 / 1's builder
+// the board size comes from the number of rows
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    Empty,
+    BoardSize(usize), // rows, only 4x4 to 7x7 boards exist
+    RowLength { row: usize, expected: usize, found: usize },
+    BadCell { row: usize, column: usize, text: String },
+    BadPlayer(String),
+    BadGods(String),
+    UnknownField(String),
+    Invalid(StateViolation), // reads fine, but isn't a position
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &FenError::Empty => write!(f, "no position given"),
+            &FenError::BoardSize(size) => write!(f, "{} rows, boards are {} to {} wide", size, MIN_BOARD_SIZE, MAX_BOARD_SIZE),
+            &FenError::RowLength { row, expected, found } => write!(f, "row {} has {} cells, expected {}", row + 1, found, expected),
+            &FenError::BadCell { row, column, ref text } => write!(f, "{:?} at row {} column {} isn't a height 0-3 optionally followed by D, A or B", text, row + 1, column + 1),
+            &FenError::BadPlayer(ref text) => write!(f, "{:?} isn't a player to move, expected A or B", text),
+            &FenError::BadGods(ref text) => write!(f, "{:?} isn't a pair of gods like Apollo,Mortal", text),
+            &FenError::UnknownField(ref text) => write!(f, "don't know what {:?} is", text),
+            &FenError::Invalid(violation) => write!(f, "{}", violation),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+const PLAYER_LETTERS : [char; 2] = ['A', 'B'];
+
+pub fn state_to_fen(state: &State, size: usize) -> String {
+    let mut rows : Vec<String> = Vec::with_capacity(size);
+
+    for y in 0..size {
+        let mut row = String::new();
+        for x in 0..size {
+            let slot = Slot((x + y * size) as i8);
+            row.push_str(&state.get_building_height(slot).to_string());
+            if state.domes.get(slot) > 0 {
+                row.push('D');
+            }
+            for i in 0..2 {
+                if state.builders[i].get(slot) > 0 {
+                    row.push(PLAYER_LETTERS[i]);
+                }
+            }
+        }
+        rows.push(row);
+    }
+
+    let mut out = format!("{} {}", rows.join("/"), PLAYER_LETTERS[state.to_move.0 as usize]);
+    if state.gods != [God::Mortal; 2] {
+        out.push_str(&format!(" {:?},{:?}", state.gods[0], state.gods[1]));
+    }
+    if state.athena_blocks {
+        out.push_str(" athena");
+    }
+    out
+}
+
+fn parse_god(text: &str) -> Option<God> {
+    let mut gods = GODS.to_vec();
+    gods.push(God::Mortal);
+    gods.into_iter().find(|god| format!("{:?}", god) == text)
+}
+
+fn parse_row(text: &str, row: usize, size: usize, state: &mut State) -> Result<(), FenError> {
+    let chars : Vec<char> = text.chars().collect();
+    let mut idx = 0;
+    let mut column = 0;
+
+    while idx < chars.len() {
+        let bad_cell = |end: usize| FenError::BadCell { row: row, column: column, text: chars[idx..end].iter().collect() };
+
+        let height = match chars[idx].to_digit(10) {
+            Some(h) if h <= 3 => h as u8,
+            _ => return Err(bad_cell(idx + 1)),
+        };
+
+        let suffix = chars.get(idx + 1).cloned().filter(|c| !c.is_digit(10));
+        if column < size {
+            let slot = Slot((column + row * size) as i8);
+            state.set_building_height(slot, height);
+            match suffix {
+                None => (),
+                Some('D') => state.domes.set(slot, 1),
+                Some('A') => state.builders[0].set(slot, 1),
+                Some('B') => state.builders[1].set(slot, 1),
+                Some(_) => return Err(bad_cell(idx + 2)),
+            }
+        }
+
+        idx += if suffix.is_some() { 2 } else { 1 };
+        column += 1;
+    }
+
+    if column != size {
+        return Err(FenError::RowLength { row: row, expected: size, found: column });
+    }
+
+    Ok(())
+}
+
+// the state and the size of board it's for
+pub fn parse_fen_sized(text: &str) -> Result<(State, usize), FenError> {
+    let mut fields = text.split_whitespace();
+
+    let board = fields.next().ok_or(FenError::Empty)?;
+    let rows : Vec<&str> = board.split('/').collect();
+    let size = rows.len();
+    if size < MIN_BOARD_SIZE || size > MAX_BOARD_SIZE {
+        return Err(FenError::BoardSize(size));
+    }
+
+    let mut state = INITIAL_STATE;
+    for (row, row_text) in rows.iter().enumerate() {
+        parse_row(row_text, row, size, &mut state)?;
+    }
+
+    state.to_move = match fields.next() {
+        Some("A") => Player(0),
+        Some("B") => Player(1),
+        Some(other) => return Err(FenError::BadPlayer(other.into())),
+        None => return Err(FenError::BadPlayer("".into())),
+    };
+
+    for field in fields {
+        if field == "athena" {
+            state.athena_blocks = true;
+        } else if field.contains(',') {
+            let gods : Vec<Option<God>> = field.split(',').map(parse_god).collect();
+            match gods.as_slice() {
+                &[Some(a), Some(b)] => state.gods = [a, b],
+                _ => return Err(FenError::BadGods(field.into())),
+            }
+        } else {
+            return Err(FenError::UnknownField(field.into()));
+        }
+    }
+
+    state.check(size).map_err(FenError::Invalid)?;
+
+    Ok((state, size))
+}
+
+pub fn parse_fen(text: &str) -> Result<State, FenError> {
+    parse_fen_sized(text).map(|(state, _)| state)
+}
+
+impl StandardBoard {
+    pub fn fen(&self, state: &State) -> String {
+        state_to_fen(state, self.size)
+    }
+
+    pub fn parse_fen(&self, text: &str) -> Result<State, FenError> {
+        let (state, size) = parse_fen_sized(text)?;
+        if size == self.size {
+            Ok(state)
+        } else {
+            Err(FenError::BoardSize(size))
+        }
+    }
+}
+
+// the standard 5x5 board, boards of other sizes go through StandardBoard
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&state_to_fen(self, DEFAULT_BOARD_SIZE))
+    }
+}
+
+impl FromStr for State {
+    type Err = FenError;
+
+    fn from_str(s: &str) -> Result<State, FenError> {
+        parse_fen(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::*;
+
+    #[test]
+    fn initial() {
+        assert_eq!(INITIAL_STATE.to_string(), "00000/00000/00000/00000/00000 A");
+        assert_eq!("00000/00000/00000/00000/00000 A".parse::<State>(), Ok(INITIAL_STATE));
+    }
+
+    #[test]
+    fn test_positions() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        assert_eq!(a_in_1(&board, Player(0)).to_string(), "0A2A300/00000/00000/00000/0000B0B A");
+        assert_eq!("0A2A300/00000/00000/00000/0000B0B A".parse::<State>(), Ok(a_in_1(&board, Player(0))));
+        assert_eq!("0A0A000/00000/00000/00000/0032B0B B".parse::<State>(), Ok(b_in_1(&board, Player(1))));
+    }
+
+    #[test]
+    fn round_trips() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = a_blockable(&board, Player(1));
+        state.domes.set(Slot(7), 1);
+        state.set_building_height(Slot(7), 3);
+        state.gods = [God::Athena, God::Apollo];
+        state.athena_blocks = true;
+
+        let fen = state.to_string();
+        assert!(fen.ends_with(" B Athena,Apollo athena"), "{}", fen);
+        assert_eq!(fen.parse::<State>(), Ok(state));
+
+        let large = StandardBoard::with_size(7, ZobristHash::new_unseeded());
+        let state = large.apply(Move::PlaceBuilders { a: Slot(0), b: Slot(48) }, &INITIAL_STATE);
+        assert_eq!(large.parse_fen(&large.fen(&state)), Ok(state.clone()));
+        assert_eq!(board.parse_fen(&large.fen(&state)), Err(FenError::BoardSize(7)));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!("".parse::<State>(), Err(FenError::Empty));
+        assert_eq!("000/000/000 A".parse::<State>(), Err(FenError::BoardSize(3)));
+        assert_eq!("00000/0000/00000/00000/00000 A".parse::<State>(), Err(FenError::RowLength { row: 1, expected: 5, found: 4 }));
+        assert_eq!("00000/00400/00000/00000/00000 A".parse::<State>(), Err(FenError::BadCell { row: 1, column: 2, text: "4".into() }));
+        assert_eq!("00000/000X0/00000/00000/00000 A".parse::<State>(), Err(FenError::BadCell { row: 1, column: 2, text: "0X".into() }));
+        assert_eq!("00000/00000/00000/00000/00000 C".parse::<State>(), Err(FenError::BadPlayer("C".into())));
+        assert_eq!("00000/00000/00000/00000/00000".parse::<State>(), Err(FenError::BadPlayer("".into())));
+        assert_eq!("0A0000/00000/00000/00000/00000 A".parse::<State>(), Err(FenError::Invalid(StateViolation::BuilderCount { player: Player(0), count: 1 })));
+        assert_eq!("0A0A000/00000/00000/00000/00000 A".parse::<State>(), Err(FenError::Invalid(StateViolation::TurnOrder)));
+        assert_eq!("0A0A000/00000/00000/00000/0000B0B A Zeus,Mortal".parse::<State>(), Err(FenError::BadGods("Zeus,Mortal".into())));
+        assert_eq!("0A0A000/00000/00000/00000/0000B0B A athena".parse::<State>(), Err(FenError::Invalid(StateViolation::AthenaBlocks)));
+        assert_eq!("0A0A000/00000/00000/00000/0000B0B A extra".parse::<State>(), Err(FenError::UnknownField("extra".into())));
+    }
+}
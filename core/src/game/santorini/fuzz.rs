@@ -0,0 +1,285 @@
+use game::santorini::*;
+use game::*;
+
+use aphid::{HashMap, HashSet};
+use std::fmt;
+
+// differential testing of the fast, height ordered and placement deduped next_moves against the simple
+// next_moves_for_player, over positions from StateGenerator. both of those go through next_god_moves for gods
+// and athena's block, so there the reference is every candidate move that check_rules allows instead. a failing
+// position is shrunk to the smallest one that still fails before it's reported
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GeneratorMismatch {
+    Duplicate(Move), // next_moves gives it twice
+    Missing(Move), // only the reference has it (or, placing, anything symmetric to it)
+    Extra(Move), // only next_moves has it
+    SymmetricPlacements(Move, Move), // dedupe let both through
+}
+
+impl fmt::Display for GeneratorMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &GeneratorMismatch::Duplicate(mve) => write!(f, "next_moves has {:?} twice", mve),
+            &GeneratorMismatch::Missing(mve) => write!(f, "next_moves is missing {:?}", mve),
+            &GeneratorMismatch::Extra(mve) => write!(f, "next_moves has {:?}, which isn't legal", mve),
+            &GeneratorMismatch::SymmetricPlacements(a, b) => write!(f, "next_moves has both {:?} and its symmetric {:?}", a, b),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FuzzFailure {
+    pub seed: u64,
+    pub case: usize,
+    pub original: State,
+    pub shrunk: State,
+    pub mismatch: GeneratorMismatch, // for the shrunk state
+    pub size: usize,
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "seed {} case {}: {}", self.seed, self.case, self.mismatch)?;
+        writeln!(f, "found at {}", state_to_fen(&self.original, self.size))?;
+        write!(f, "shrunk to {}", state_to_fen(&self.shrunk, self.size))
+    }
+}
+
+fn placed(state: &State, mve: Move) -> [Packed1; 2] {
+    let mut builders = state.builders;
+    if let Move::PlaceBuilders { a, b } = mve {
+        builders[state.to_move.0 as usize] = Packed1((1 << a.0) | (1 << b.0));
+    }
+    builders
+}
+
+// every move of every kind from the current builders that check_rules allows, spelt the way next_god_moves spells
+// them: demeter's lower build first, and hermes's travels once per pair of squares, never where a single step
+// leaves the same position
+pub fn reference_god_moves(board: &StandardBoard, state: &State) -> Vec<Move> {
+    let legal = |mve: Move| board.check_rules(state, mve).is_ok();
+    let adjacent = |slot: Slot| board.packed_adjacencies[slot.0 as usize];
+    let mut moves = Vec::new();
+
+    for from in state.current_builders().iter() {
+        for &to in &board.slots {
+            for build in adjacent(to).iter() {
+                let mut candidates = vec![
+                    Move::Move { from: from, to: to, build: build },
+                    Move::Swap { from: from, to: to, build: build },
+                    Move::Push { from: from, to: to, build: build },
+                    Move::Dome { from: from, to: to, build: build },
+                ];
+                for second in adjacent(to).iter().filter(|second| second.0 >= build.0) {
+                    candidates.push(Move::DoubleBuild { from: from, to: to, build: build, second: second });
+                }
+                for first in adjacent(from).iter() {
+                    candidates.push(Move::BuildFirst { first: first, from: from, to: to, build: build });
+                }
+                moves.extend(candidates.into_iter().filter(|&mve| legal(mve)));
+            }
+        }
+    }
+
+    if state.god() == God::Hermes {
+        let single_steps : HashSet<State> = moves.iter().map(|&mve| board.apply(mve, state)).collect();
+        let mut builders = state.current_builders().iter();
+        let (a, b) = (builders.next().unwrap(), builders.next().unwrap());
+
+        for (i, &low) in board.slots.iter().enumerate() {
+            for &high in &board.slots[(i + 1)..] {
+                // the lower builder on the lower square if it can get there
+                let ends = if board.check_walks(state, [(a, low), (b, high)]).is_ok() {
+                    [low, high]
+                } else if board.check_walks(state, [(a, high), (b, low)]).is_ok() {
+                    [high, low]
+                } else {
+                    continue;
+                };
+                for &build in &board.slots {
+                    let travel = Move::Travel { from: a, to: ends[0], other_from: b, other_to: ends[1], build: build };
+                    if board.check_travel_build(state, ends, build).is_ok() && !single_steps.contains(&board.apply(travel, state)) {
+                        moves.push(travel);
+                    }
+                }
+            }
+        }
+    }
+
+    moves
+}
+
+pub fn compare_generators(board: &StandardBoard, state: &State) -> Option<GeneratorMismatch> {
+    let placing = !state.current_builders().any();
+    let mut fast = Vec::new();
+    board.next_moves(state, &mut fast);
+    let slow = if !placing && (!state.god().standard_moves() || state.athena_blocks) {
+        reference_god_moves(board, state)
+    } else {
+        let mut slow = Vec::new();
+        board.next_moves_for_player(state, &mut slow);
+        slow
+    };
+
+    for (i, &mve) in fast.iter().enumerate() {
+        if fast[..i].contains(&mve) {
+            return Some(GeneratorMismatch::Duplicate(mve));
+        }
+        if !slow.contains(&mve) {
+            return Some(GeneratorMismatch::Extra(mve));
+        }
+    }
+
+    if !placing {
+        return slow.iter().find(|mve| !fast.contains(mve)).map(|&mve| GeneratorMismatch::Missing(mve));
+    }
+
+    // placements only have to be there up to symmetry, and once. every symmetric copy of each maps back to it
+    let mut orbits : HashMap<[Packed1; 2], usize> = HashMap::default();
+    for (i, &mve) in fast.iter().enumerate() {
+        let builders = placed(state, mve);
+        let images = board.transforms.iter().map(|t| StandardBoard::transform_packed2(t, builders));
+        for image in Some(builders).into_iter().chain(images) {
+            match orbits.insert(image, i) {
+                Some(j) if j != i => return Some(GeneratorMismatch::SymmetricPlacements(fast[j], mve)),
+                _ => (),
+            }
+        }
+    }
+
+    slow.iter()
+        .find(|&&mve| !orbits.contains_key(&placed(state, mve)))
+        .map(|&mve| GeneratorMismatch::Missing(mve))
+}
+
+// smaller versions of the state, one change each
+fn simplifications(state: &State, slot_count: usize) -> Vec<State> {
+    let mut simpler = Vec::new();
+
+    if state.athena_blocks {
+        let mut s = state.clone();
+        s.athena_blocks = false;
+        simpler.push(s);
+    }
+
+    for i in 0..2 {
+        if state.gods[i] != God::Mortal {
+            let mut s = state.clone();
+            s.gods[i] = God::Mortal;
+            simpler.push(s);
+        }
+    }
+
+    for i in 0..slot_count {
+        let slot = Slot(i as i8);
+        if state.domes.get(slot) > 0 {
+            let mut s = state.clone();
+            s.domes.set(slot, 0);
+            simpler.push(s);
+        } else if state.get_building_height(slot) > 0 {
+            let mut s = state.clone();
+            let height = s.get_building_height(slot);
+            s.set_building_height(slot, height - 1);
+            simpler.push(s);
+        }
+    }
+
+    simpler
+}
+
+// greedily applies simplifications for as long as the state keeps failing
+pub fn shrink<F>(board: &StandardBoard, state: &State, fails: F) -> State where F: Fn(&State) -> bool {
+    let mut state = state.clone();
+
+    loop {
+        let simpler = simplifications(&state, board.slots.len()).into_iter()
+            .filter(|s| s.validate(board.size).is_empty())
+            .find(|s| fails(s));
+
+        match simpler {
+            Some(s) => state = s,
+            None => return state,
+        }
+    }
+}
+
+// cases random positions from every phase, the number checked if they all agree
+pub fn fuzz_generators(board: &StandardBoard, seed: u64, cases: usize) -> Result<usize, FuzzFailure> {
+    let mut generator = StateGenerator::new(seed);
+
+    for case in 0..cases {
+        let state = generator.any_state(board);
+
+        if compare_generators(board, &state).is_some() {
+            let shrunk = shrink(board, &state, |s| compare_generators(board, s).is_some());
+            return Err(FuzzFailure {
+                seed: seed,
+                case: case,
+                original: state,
+                mismatch: compare_generators(board, &shrunk).expect("shrinking keeps the failure"),
+                shrunk: shrunk,
+                size: board.size,
+            });
+        }
+    }
+
+    Ok(cases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generators_agree() {
+        for size in MIN_BOARD_SIZE..(MAX_BOARD_SIZE + 1) {
+            let board = StandardBoard::with_size(size, ZobristHash::new_unseeded());
+            if let Err(failure) = fuzz_generators(&board, size as u64, 300) {
+                panic!("{}\n{}", failure, board.print(&failure.shrunk));
+            }
+        }
+    }
+
+    #[test]
+    fn shrinks_to_the_cause() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = StateGenerator::new(7).state(&board, GamePhase::Midgame);
+        let free = (0..25).map(|i| Slot(i)).find(|&slot| state.collision().get(slot) == 0).expect("a free slot midgame");
+        state.set_building_height(free, 3);
+        state.domes.set(free, 1);
+
+        // pretend that dome breaks something
+        let shrunk = shrink(&board, &state, |s| s.domes.get(free) > 0);
+
+        assert_eq!(shrunk.domes, Packed1(1 << free.0));
+        assert_eq!(shrunk.gods, [God::Mortal; 2]);
+        assert!(!shrunk.athena_blocks);
+        assert_eq!(shrunk.builders, state.builders);
+        for i in 0..25 {
+            if i != free.0 {
+                assert_eq!(shrunk.get_building_height(Slot(i)), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn known_positions_agree() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = board.apply(Move::PlaceBuilders { a: Slot(0), b: Slot(1) }, &INITIAL_STATE);
+        assert_eq!(compare_generators(&board, &state), None);
+        assert_eq!(compare_generators(&board, &INITIAL_STATE), None);
+
+        // against the reference rather than next_moves_for_player, which shares next_god_moves
+        for &god in GODS.iter() {
+            let mut god_state = state.clone();
+            god_state.gods[0] = god;
+            let mut god_state = board.apply(Move::PlaceBuilders { a: Slot(12), b: Slot(7) }, &god_state);
+            god_state.set_building_height(Slot(6), 1);
+            god_state.set_building_height(Slot(11), 2);
+            god_state.set_building_height(Slot(13), 3);
+            assert_eq!(compare_generators(&board, &god_state), None, "{:?}", god);
+            assert!(!reference_god_moves(&board, &god_state).is_empty());
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use game::santorini::*;
+use game::*;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum God {
+    Mortal,
+    Apollo, // may move into an opponent's square, swapping with them
+    Artemis, // may move one additional time, but not back to the initial space
+    Athena, // if she moved up, opponents can't move up on their next turn
+    Atlas, // may build a dome at any level
+    Demeter, // may build one additional time, but not on the same space
+    Hephaestus, // may build one additional block (not a dome) on top of the first
+    // a mortal turn, or both builders move any number of times (zero included) without changing level, in any
+    // order, and then either builds. the second kind is Move::Travel
+    Hermes,
+    Minotaur, // may push an opponent back one space in the direction of travel
+    Pan, // also wins by moving down two or more levels
+    Prometheus, // if not moving up, may build both before and after moving
+}
+
+pub const GODS : [God; 10] = [
+    God::Apollo,
+    God::Artemis,
+    God::Athena,
+    God::Atlas,
+    God::Demeter,
+    God::Hephaestus,
+    God::Hermes,
+    God::Minotaur,
+    God::Pan,
+    God::Prometheus,
+];
+
+impl God {
+    // gods whose moves the fast bitboard generator in next_moves produces as is
+    pub fn standard_moves(&self) -> bool {
+        match *self {
+            God::Mortal | God::Athena | God::Pan => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Step {
+    Walk,
+    Swap,
+    Push,
+}
+
+impl StandardBoard {
+    // slower generator for everything the bitboard path doesn't cover, god powers and athena's block
+    pub fn next_god_moves<T : MoveSink<Move>>(&self, state:&State, move_sink: &mut T) {
+        let player_to_move = state.to_move;
+        let god = state.gods[player_to_move.0 as usize];
+        let builders = state.builders[player_to_move.0 as usize];
+        let opponents = state.builders[state.next_player().0 as usize];
+        let collision = state.collision();
+
+        for from in builders.iter() {
+            let from_mask = Packed1(1 << from.0);
+
+            if god == God::Prometheus {
+                self.prometheus_moves(state, from, move_sink);
+            }
+
+            let mut walk_destinations = PACKED1_EMPTY;
+
+            for to in self.reachable(state, from, collision).iter() {
+                walk_destinations.0 |= 1 << to.0;
+            }
+
+            for to in (self.packed_adjacencies[from.0 as usize] & opponents).iter() {
+                if !self.can_step(state, from, to) {
+                    continue;
+                }
+                match god {
+                    God::Apollo => {
+                        // the opponent takes our old square, so occupancy is unchanged
+                        self.sink_god_builds(state, god, Step::Swap, from, to, collision, move_sink);
+                    },
+                    God::Minotaur => {
+                        if let Some(pushed_to) = self.push_target(from, to) {
+                            let pushed_mask = Packed1(1 << pushed_to.0);
+                            if (collision & pushed_mask).any() {
+                                continue;
+                            }
+                            let occupied = collision ^ from_mask ^ pushed_mask;
+                            self.sink_god_builds(state, god, Step::Push, from, to, occupied, move_sink);
+                        }
+                    },
+                    _ => (),
+                }
+            }
+
+            match god {
+                God::Artemis => {
+                    let first_steps = walk_destinations;
+                    for via in first_steps.iter() {
+                        let vacated = collision ^ from_mask;
+                        let second_steps = self.reachable(state, via, vacated) & !from_mask;
+                        walk_destinations |= second_steps;
+                    }
+                },
+                _ => (),
+            }
+
+            for to in walk_destinations.iter() {
+                let occupied = collision ^ from_mask ^ Packed1(1 << to.0);
+                self.sink_god_builds(state, god, Step::Walk, from, to, occupied, move_sink);
+            }
+        }
+
+        if god == God::Hermes {
+            self.hermes_travels(state, move_sink);
+        }
+    }
+
+    // every pair of squares the two builders can reach stepping one at a time on their own levels, once per pair
+    // since the builders are interchangeable, with the lower builder on the lower square when it can get there.
+    // a pair a single step Move also gets to, building next to the mover, is left to that Move
+    fn hermes_travels<T : MoveSink<Move>>(&self, state:&State, move_sink: &mut T) {
+        let builders = state.current_builders();
+        if builders.count() != 2 {
+            return;
+        }
+        let mut starts = builders.iter();
+        let a = starts.next().unwrap();
+        let b = starts.next().unwrap();
+
+        let blocked = state.builders[state.next_player().0 as usize] | state.domes;
+        let levels = state.building_map();
+        let a_level = levels[state.get_building_height(a) as usize] & !blocked;
+        let b_level = levels[state.get_building_height(b) as usize] & !blocked;
+        let adjacent = |slot: Slot| self.packed_adjacencies[slot.0 as usize];
+
+        // reached[p] holds every q with a on p and b on q at once
+        let mut reached = vec![PACKED1_EMPTY; self.slots.len()];
+        reached[a.0 as usize].set(b, 1);
+        let mut pairs = vec![(a, b)];
+        let mut next = 0;
+        while next < pairs.len() {
+            let (p, q) = pairs[next];
+            next += 1;
+            for p_to in (adjacent(p) & a_level).iter() {
+                if p_to != q && reached[p_to.0 as usize].get(q) == 0 {
+                    reached[p_to.0 as usize].set(q, 1);
+                    pairs.push((p_to, q));
+                }
+            }
+            for q_to in (adjacent(q) & b_level).iter() {
+                if q_to != p && reached[p.0 as usize].get(q_to) == 0 {
+                    reached[p.0 as usize].set(q_to, 1);
+                    pairs.push((p, q_to));
+                }
+            }
+        }
+
+        let mut sunk = vec![PACKED1_EMPTY; self.slots.len()];
+        for &(p, q) in &pairs {
+            let (low, high) = if p.0 < q.0 { (p, q) } else { (q, p) };
+            if sunk[low.0 as usize].get(high) > 0 {
+                continue;
+            }
+            sunk[low.0 as usize].set(high, 1);
+            let (p, q) = if reached[low.0 as usize].get(high) > 0 { (low, high) } else { (high, low) };
+
+            // with a start still covered, the other end is where a single step from the other start would go
+            let other_end = |start: Slot| if p == start { Some(q) } else if q == start { Some(p) } else { None };
+            let stepped = match (other_end(a), other_end(b)) {
+                (Some(end), _) if adjacent(b).get(end) > 0 => Some(end),
+                (_, Some(end)) if adjacent(a).get(end) > 0 => Some(end),
+                _ => None,
+            };
+
+            let ends = Packed1(1 << p.0 | 1 << q.0);
+            for build in ((adjacent(p) | adjacent(q)) & !(blocked | ends)).iter() {
+                if let Some(stepped) = stepped {
+                    if adjacent(stepped).get(build) > 0 {
+                        continue;
+                    }
+                }
+                move_sink.sink(Move::Travel { from: a, to: p, other_from: b, other_to: q, build: build });
+            }
+        }
+    }
+
+    // single step destinations from a slot, honoring climbing limits and athena
+    fn reachable(&self, state:&State, from:Slot, collision:Packed1) -> Packed1 {
+        let mut out = PACKED1_EMPTY;
+        for to in (self.packed_adjacencies[from.0 as usize] & !collision).iter() {
+            if self.can_step(state, from, to) {
+                out.0 |= 1 << to.0;
+            }
+        }
+        out
+    }
+
+    fn can_step(&self, state:&State, from:Slot, to:Slot) -> bool {
+        let height = state.get_building_height(from);
+        let max_height = if state.athena_blocks { height } else { height + 1 };
+        state.domes.get(to) == 0 && state.get_building_height(to) <= max_height
+    }
+
+    pub fn push_target(&self, from:Slot, to:Slot) -> Option<Slot> {
+        let from_position = self.position(from);
+        let to_position = self.position(to);
+        self.slot_for(Position { x: to_position.x * 2 - from_position.x, y: to_position.y * 2 - from_position.y })
+    }
+
+    fn sink_god_builds<T : MoveSink<Move>>(&self, state:&State, god: God, step: Step, from:Slot, to:Slot, occupied:Packed1, move_sink: &mut T) {
+        let buildable = self.packed_adjacencies[to.0 as usize] & !occupied;
+
+        for build in buildable.iter() {
+            match step {
+                Step::Swap => move_sink.sink(Move::Swap { from: from, to: to, build: build }),
+                Step::Push => move_sink.sink(Move::Push { from: from, to: to, build: build }),
+                Step::Walk => {
+                    move_sink.sink(Move::Move { from: from, to: to, build: build });
+                    let height = state.get_building_height(build);
+                    match god {
+                        God::Atlas => {
+                            if height < 3 {
+                                move_sink.sink(Move::Dome { from: from, to: to, build: build });
+                            }
+                        },
+                        God::Demeter => {
+                            // only one ordering of the two builds, they produce the same state
+                            for second in buildable.iter() {
+                                if second.0 > build.0 {
+                                    move_sink.sink(Move::DoubleBuild { from: from, to: to, build: build, second: second });
+                                }
+                            }
+                        },
+                        God::Hephaestus => {
+                            if height < 2 {
+                                move_sink.sink(Move::DoubleBuild { from: from, to: to, build: build, second: build });
+                            }
+                        },
+                        _ => (),
+                    }
+                },
+            }
+        }
+    }
+
+    fn prometheus_moves<T : MoveSink<Move>>(&self, state:&State, from:Slot, move_sink: &mut T) {
+        let height = state.get_building_height(from);
+        let collision = state.collision();
+        let from_mask = Packed1(1 << from.0);
+
+        for first in (self.packed_adjacencies[from.0 as usize] & !collision).iter() {
+            let mut built = state.clone();
+            built.build_at(first);
+            let built_collision = built.collision();
+
+            for to in (self.packed_adjacencies[from.0 as usize] & !built_collision).iter() {
+                if built.get_building_height(to) > height {
+                    continue;
+                }
+                let occupied = built_collision ^ from_mask ^ Packed1(1 << to.0);
+                for build in (self.packed_adjacencies[to.0 as usize] & !occupied).iter() {
+                    move_sink.sink(Move::BuildFirst { first: first, from: from, to: to, build: build });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn god_state(board: &StandardBoard, gods: [God; 2]) -> State {
+        let mut state = State::with_gods(gods);
+        for &mve in &vec![Move::PlaceBuilders { a: Slot(6), b: Slot(18) },
+                          Move::PlaceBuilders { a: Slot(7), b: Slot(24) }] {
+            state = board.apply(mve, &state);
+        }
+        state
+    }
+
+    fn moves_for(board: &StandardBoard, state: &State) -> Vec<Move> {
+        let mut moves = Vec::new();
+        board.next_moves(state, &mut moves);
+        moves
+    }
+
+    #[test]
+    fn mortal_matches_exhaustive() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = god_state(&board, [God::Mortal, God::Mortal]);
+
+        let mut fast = moves_for(&board, &state);
+        let mut slow = Vec::new();
+        board.next_god_moves(&state, &mut slow);
+
+        fast.sort_by_key(|m| m.to_slots());
+        slow.sort_by_key(|m| m.to_slots());
+        assert_eq!(fast, slow);
+    }
+
+    #[test]
+    fn apollo_swaps() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = god_state(&board, [God::Apollo, God::Mortal]);
+
+        let moves = moves_for(&board, &state);
+        let swap = Move::Swap { from: Slot(6), to: Slot(7), build: Slot(8) };
+        assert!(moves.contains(&swap));
+        assert!(!moves.contains(&Move::Swap { from: Slot(6), to: Slot(7), build: Slot(6) }));
+
+        let new_state = board.apply(swap, &state);
+        assert_eq!(new_state.builders[0].get(Slot(7)), 1);
+        assert_eq!(new_state.builders[1].get(Slot(6)), 1);
+        assert_eq!(new_state.get_building_height(Slot(8)), 1);
+    }
+
+    #[test]
+    fn minotaur_pushes() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = god_state(&board, [God::Minotaur, God::Mortal]);
+
+        let push = Move::Push { from: Slot(6), to: Slot(7), build: Slot(6) };
+        assert!(moves_for(&board, &state).contains(&push));
+
+        let new_state = board.apply(push, &state);
+        assert_eq!(new_state.builders[0].get(Slot(7)), 1);
+        assert_eq!(new_state.builders[1].get(Slot(8)), 1);
+    }
+
+    #[test]
+    fn athena_blocks_climbing() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = god_state(&board, [God::Athena, God::Mortal]);
+        state.set_building_height(Slot(11), 1);
+        state.set_building_height(Slot(13), 1);
+
+        let climb = Move::Move { from: Slot(6), to: Slot(11), build: Slot(10) };
+        let new_state = board.apply(climb, &state);
+        assert!(new_state.athena_blocks);
+
+        for mve in moves_for(&board, &new_state) {
+            if let Some((_, to)) = mve.movement() {
+                assert!(to != Slot(13), "opponent climbed while athena blocks");
+            }
+        }
+    }
+
+    #[test]
+    fn pan_wins_by_falling() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = god_state(&board, [God::Pan, God::Mortal]);
+        state.set_building_height(Slot(6), 2);
+
+        let fall = Move::Move { from: Slot(6), to: Slot(5), build: Slot(0) };
+        assert!(board.ascension_winning_move(&state, fall));
+
+        state.gods[0] = God::Mortal;
+        assert!(!board.ascension_winning_move(&state, fall));
+    }
+
+    #[test]
+    fn hermes_travels_with_both_builders() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = god_state(&board, [God::Hermes, God::Mortal]);
+        let moves = moves_for(&board, &state);
+
+        let travel = Move::Travel { from: Slot(6), to: Slot(0), other_from: Slot(18), other_to: Slot(20), build: Slot(15) };
+        assert!(moves.contains(&travel));
+        let new_state = board.apply(travel, &state);
+        assert_eq!(new_state.builders[0], Packed1(1 << 0 | 1 << 20));
+        assert_eq!(new_state.get_building_height(Slot(15)), 1);
+
+        // the same as a single step, so only the Move
+        assert!(moves.contains(&Move::Move { from: Slot(6), to: Slot(12), build: Slot(13) }));
+        assert!(!moves.contains(&Move::Travel { from: Slot(6), to: Slot(12), other_from: Slot(18), other_to: Slot(18), build: Slot(13) }));
+
+        for mve in moves {
+            let mut undone = state.clone();
+            let undo = board.make_move(&mut undone, mve);
+            assert_eq!(undone.builders[0].count(), 2, "{:?}", mve);
+            board.unmake_move(&mut undone, mve, undo);
+            assert_eq!(undone, state, "{:?}", mve);
+        }
+    }
+
+    #[test]
+    fn hermes_stays_put_and_builds() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = god_state(&board, [God::Hermes, God::Mortal]);
+        // nowhere to step from b2 or d4, everything around them is two levels up
+        for &slot in &[0, 1, 2, 5, 10, 11, 12, 13, 14, 17, 19, 22, 23] {
+            state.set_building_height(Slot(slot), 2);
+        }
+
+        let moves = moves_for(&board, &state);
+        assert!(moves.contains(&Move::Travel { from: Slot(6), to: Slot(6), other_from: Slot(18), other_to: Slot(18), build: Slot(12) }));
+        for mve in moves {
+            match mve {
+                Move::Travel { from, to, other_from, other_to, .. } => assert!(from == to && other_from == other_to, "{:?}", mve),
+                _ => panic!("{:?} steps somewhere", mve),
+            }
+        }
+    }
+
+    #[test]
+    fn delta_hash_matches_hash() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+
+        for &god in GODS.iter() {
+            let mut state = god_state(&board, [god, God::Mortal]);
+            state.set_building_height(Slot(11), 1);
+            state.set_building_height(Slot(12), 3);
+            let hash = board.hash(&state);
+
+            for mve in moves_for(&board, &state) {
+                let new_state = board.apply(mve, &state);
+                assert_eq!(hash ^ board.delta_hash(&state, mve), board.hash(&new_state), "{:?} {:?}", god, mve);
+            }
+        }
+    }
+}
@@ -2,19 +2,10 @@ use game::santorini::*;
 // use game::*;
 use game::util::Packed;
 use game::packed::*;
-use std;
 use std::cmp::{min};
 
 use aphid::Milliseconds;
 
-pub const VICTORY : HeuristicValue = 32_000;
-
-pub const BEST : HeuristicValue = std::i16::MAX;
-pub const WORST : HeuristicValue = -std::i16::MAX; // to prevent overflow on negation
-
-pub const PLAYER_0_WIN : HeuristicValue = std::i16::MAX;
-pub const PLAYER_1_WIN : HeuristicValue = -std::i16::MAX; // to prevent overflow on negation
-
 #[derive(Eq, PartialEq, Copy, Clone, Debug, Hash, Serialize, Deserialize)]
 pub enum HeuristicName {
     Simple,
@@ -31,7 +22,7 @@ pub struct AIProfile {
 
 pub struct SimpleHeightHeuristic {}
 
-impl Heuristic for SimpleHeightHeuristic {
+impl Heuristic<StandardBoard> for SimpleHeightHeuristic {
     fn name() -> String {
         "SimpleHeightHeuristic".into()
     }
@@ -64,7 +55,7 @@ impl Heuristic for SimpleHeightHeuristic {
 
 pub struct NeighbourHeuristic {}
 
-impl Heuristic for NeighbourHeuristic {
+impl Heuristic<StandardBoard> for NeighbourHeuristic {
     fn name() -> String {
         "NeighbourHeuristic".into()
     }
@@ -105,7 +96,7 @@ impl NeighbourHeuristic {
 
 pub struct AdjustedNeighbourHeuristic { }
 
-impl Heuristic for AdjustedNeighbourHeuristic {
+impl Heuristic<StandardBoard> for AdjustedNeighbourHeuristic {
     fn name() -> String {
         "AdjustedNeighbourHeuristic".into()
     }
@@ -0,0 +1,252 @@
+use game::santorini::*;
+
+use std;
+use std::fmt;
+use rand::{XorShiftRng, SeedableRng};
+
+// what a hand built state can get wrong, and random states that can't, for tests and fuzzers to share.
+// heights are two bits, so they can't go past 3
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StateViolation {
+    OffBoard(Slot), // a builder, building or dome past the last slot
+    UnknownPlayer(Player), // to move
+    BuilderCount { player: Player, count: u32 }, // 0 before placement, 2 after
+    SharedSlot(Slot), // both players have a builder there
+    BuilderOnDome(Slot),
+    BuiltBeforePlacement(Slot), // nothing is built until both players have placed
+    TurnOrder, // to move doesn't fit with who has placed
+    AthenaBlocks, // only possible straight after athena has moved
+}
+
+impl fmt::Display for StateViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &StateViolation::OffBoard(slot) => write!(f, "{:?} is off the board but has something on it", slot),
+            &StateViolation::UnknownPlayer(player) => write!(f, "{:?} can't be to move in a two player game", player),
+            &StateViolation::BuilderCount { player, count } => write!(f, "{:?} has {} builders, expected 0 or 2", player, count),
+            &StateViolation::SharedSlot(slot) => write!(f, "both players have a builder on {:?}", slot),
+            &StateViolation::BuilderOnDome(slot) => write!(f, "a builder is standing on the dome at {:?}", slot),
+            &StateViolation::BuiltBeforePlacement(slot) => write!(f, "{:?} is built on before both players have placed", slot),
+            &StateViolation::TurnOrder => write!(f, "the player to move doesn't match who has placed"),
+            &StateViolation::AthenaBlocks => write!(f, "athena blocks climbing but didn't move last"),
+        }
+    }
+}
+
+impl std::error::Error for StateViolation {}
+
+impl State {
+    // everything wrong with the state on a size x size board, empty if it's fine
+    pub fn validate(&self, size: usize) -> Vec<StateViolation> {
+        let mut violations = Vec::new();
+        let slot_count = size * size;
+
+        let everything = self.builders[0] | self.builders[1] | self.building_major | self.building_minor | self.domes;
+        for slot in everything.iter().filter(|slot| slot.0 as usize >= slot_count) {
+            violations.push(StateViolation::OffBoard(slot));
+        }
+
+        if self.to_move != Player(0) && self.to_move != Player(1) {
+            violations.push(StateViolation::UnknownPlayer(self.to_move));
+        }
+
+        for i in 0..2 {
+            let count = self.builders[i].count();
+            if count != 0 && count != 2 {
+                violations.push(StateViolation::BuilderCount { player: Player(i as i8), count: count });
+            }
+        }
+
+        for slot in (self.builders[0] & self.builders[1]).iter() {
+            violations.push(StateViolation::SharedSlot(slot));
+        }
+
+        for slot in ((self.builders[0] | self.builders[1]) & self.domes).iter() {
+            violations.push(StateViolation::BuilderOnDome(slot));
+        }
+
+        let placed = (self.builders[0].any(), self.builders[1].any());
+        if placed != (true, true) {
+            for slot in (self.building_major | self.building_minor | self.domes).iter() {
+                violations.push(StateViolation::BuiltBeforePlacement(slot));
+            }
+        }
+
+        let turn_order_ok = match placed {
+            (false, false) => self.to_move == Player(0),
+            (true, false) => self.to_move == Player(1),
+            (false, true) => false,
+            (true, true) => true,
+        };
+        if !turn_order_ok {
+            violations.push(StateViolation::TurnOrder);
+        }
+
+        if self.athena_blocks && self.gods[self.next_player().0 as usize] != God::Athena {
+            violations.push(StateViolation::AthenaBlocks);
+        }
+
+        violations
+    }
+
+    // just the first thing wrong, for parsers that stop at one
+    pub fn check(&self, size: usize) -> Result<(), StateViolation> {
+        match self.validate(size).into_iter().next() {
+            Some(violation) => Err(violation),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GamePhase {
+    Placement, // someone still has builders to place
+    Opening, // up to 10 moves in
+    Midgame, // 10 to 30 moves
+    Endgame, // 30 moves or more, or as far as the game went
+}
+
+pub const GAME_PHASES : [GamePhase; 4] = [GamePhase::Placement, GamePhase::Opening, GamePhase::Midgame, GamePhase::Endgame];
+
+impl GamePhase {
+    // plies from the start, placement included
+    pub fn plies(&self) -> (usize, usize) {
+        match *self {
+            GamePhase::Placement => (0, 1),
+            GamePhase::Opening => (2, 12),
+            GamePhase::Midgame => (12, 32),
+            GamePhase::Endgame => (32, 80),
+        }
+    }
+}
+
+pub fn seeded_rng(seed: u64) -> XorShiftRng {
+    // xorshift can't start from all zeros, the constants make sure it doesn't
+    XorShiftRng::from_seed([seed as u32, (seed >> 32) as u32, 0x9E37_79B9, 0x7F4A_7C15])
+}
+
+// random positions reached by playing random legal moves from the start, the same ones for the same seed
+pub struct StateGenerator {
+    pub rng: XorShiftRng,
+    pub gods: bool, // random gods for both players, otherwise mortals
+}
+
+impl StateGenerator {
+    pub fn new(seed: u64) -> StateGenerator {
+        StateGenerator {
+            rng: seeded_rng(seed),
+            gods: true,
+        }
+    }
+
+    pub fn mortals(seed: u64) -> StateGenerator {
+        StateGenerator {
+            gods: false,
+            ..StateGenerator::new(seed)
+        }
+    }
+
+    // up to plies random moves, stopping early rather than play a win or when stuck
+    pub fn playout(&mut self, board: &StandardBoard, plies: usize) -> State {
+        let mut state = if self.gods {
+            let mut gods = GODS.to_vec();
+            gods.push(God::Mortal);
+            State::with_gods([*self.rng.choose(&gods).unwrap(), *self.rng.choose(&gods).unwrap()])
+        } else {
+            INITIAL_STATE
+        };
+
+        for _ in 0..plies {
+            let mut moves = Vec::new();
+            board.next_moves_for_player(&state, &mut moves);
+            moves.retain(|&mve| !board.ascension_winning_move(&state, mve));
+            match self.rng.choose(&moves) {
+                Some(&mve) => board.make_move(&mut state, mve),
+                None => break,
+            };
+        }
+
+        state
+    }
+
+    // games can end before the endgame, those come back as late as they got
+    pub fn state(&mut self, board: &StandardBoard, phase: GamePhase) -> State {
+        let (min_plies, max_plies) = phase.plies();
+        let plies = self.rng.gen_range(min_plies, max_plies + 1);
+        self.playout(board, plies)
+    }
+
+    pub fn any_state(&mut self, board: &StandardBoard) -> State {
+        let phase = *self.rng.choose(&GAME_PHASES).unwrap();
+        self.state(board, phase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::*;
+
+    #[test]
+    fn valid() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        for state in vec![INITIAL_STATE, a_in_1(&board, Player(1)), any_trap_in_1(&board, Player(0))] {
+            assert_eq!(state.validate(5), vec![]);
+        }
+    }
+
+    #[test]
+    fn violations() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+
+        let mut state = distant_state(&board);
+        state.builders[0].set(Slot(2), 1);
+        state.builders[1].set(Slot(1), 1);
+        state.set_building_height(Slot(24), 3);
+        state.domes.set(Slot(24), 1);
+        assert_eq!(state.validate(5), vec![
+            StateViolation::BuilderCount { player: Player(0), count: 3 },
+            StateViolation::BuilderCount { player: Player(1), count: 3 },
+            StateViolation::SharedSlot(Slot(1)),
+            StateViolation::BuilderOnDome(Slot(24)),
+        ]);
+        assert_eq!(state.validate(4)[0], StateViolation::OffBoard(Slot(23)));
+
+        let mut state = board.apply(Move::PlaceBuilders { a: Slot(0), b: Slot(1) }, &INITIAL_STATE);
+        state.set_building_height(Slot(7), 1);
+        state.athena_blocks = true;
+        state.to_move = Player(0);
+        assert_eq!(state.validate(5), vec![StateViolation::BuiltBeforePlacement(Slot(7)), StateViolation::TurnOrder, StateViolation::AthenaBlocks]);
+
+        state.to_move = Player(3);
+        assert!(state.validate(5).contains(&StateViolation::UnknownPlayer(Player(3))));
+    }
+
+    #[test]
+    fn generated_states() {
+        for size in MIN_BOARD_SIZE..(MAX_BOARD_SIZE + 1) {
+            let board = StandardBoard::with_size(size, ZobristHash::new_unseeded());
+            let mut generator = StateGenerator::new(size as u64);
+
+            for &phase in &GAME_PHASES {
+                for _ in 0..20 {
+                    let state = generator.state(&board, phase);
+                    assert_eq!(state.validate(size), vec![], "{:?}\n{}", phase, board.print(&state));
+
+                    let placed = state.builders[0].any() && state.builders[1].any();
+                    assert_eq!(placed, phase != GamePhase::Placement, "{:?}\n{}", phase, board.print(&state));
+                }
+            }
+        }
+
+        // the same seed gives the same states
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let (mut a, mut b) = (StateGenerator::mortals(3), StateGenerator::mortals(3));
+        for _ in 0..10 {
+            let state = a.any_state(&board);
+            assert_eq!(state.gods, [God::Mortal; 2]);
+            assert_eq!(state, b.any_state(&board));
+        }
+    }
+}
@@ -3,39 +3,59 @@
 // extern crate pad;
 // pub mod move_builder;
 pub mod perft;
-pub mod evaluator {
-    pub mod minimax;
-    pub mod minimax_alphabeta;
-    pub mod negamax;
-    pub mod negamax_alphabeta;
-    pub mod negamax_alphabeta_exp;    
-}
-pub mod move_stack;
 pub mod heuristic;
 pub mod board;
 pub mod state;
 pub mod transposition;
-pub mod evaluator_info;
 pub mod playout;
+pub mod god;
+pub mod multiplayer;
+pub mod notation;
+pub mod fen;
+pub mod diagram;
+pub mod record;
+pub mod validation;
+pub mod outcome;
+pub mod symmetry;
+pub mod move_tables;
+pub mod invariants;
+pub mod fuzz;
 
 pub mod tests;
 
 // pub use self::move_builder::*;
 pub use self::heuristic::*;
-pub use self::evaluator::negamax::*;
-pub use self::evaluator::negamax_alphabeta::*;
-pub use self::evaluator::negamax_alphabeta_exp::*;
-pub use self::evaluator::minimax::*;
-pub use self::evaluator::minimax_alphabeta::*;
 pub use self::board::*;
 pub use self::state::*;
 pub use self::transposition::*;
-pub use self::move_stack::*;
 pub use self::playout::*;
+pub use self::god::*;
+pub use self::multiplayer::*;
+pub use self::notation::*;
+pub use self::fen::*;
+pub use self::diagram::*;
+pub use self::record::*;
+pub use self::validation::*;
+pub use self::outcome::*;
+pub use self::move_tables::*;
+pub use self::invariants::*;
+pub use self::fuzz::*;
+
+pub use game::engine::*;
+pub use game::evaluator::negamax::*;
+pub use game::evaluator::negamax_alphabeta::*;
+pub use game::evaluator::negamax_alphabeta_exp::*;
+pub use game::evaluator::negamax_pvs::*;
+pub use game::evaluator::minimax::*;
+pub use game::evaluator::minimax_alphabeta::*;
+pub use game::evaluator_info::*;
+pub use game::move_stack::*;
+pub use game::staged_moves::*;
+pub use game::search_limits::*;
+pub use game::transposition::*;
+pub use game::perft::*;
+pub use game::playout::*;
 
-pub use self::evaluator_info::*;
-
-use time;
 use std::cmp::max;
 
 use aphid::HashSet;
@@ -44,55 +64,6 @@ use super::util::*;
 use pad::Alignment;
 pub use rand::Rng;
 
-pub type MoveCount = u32;
-
-pub type HeuristicValue = i16;
-
-pub type BranchFactor = f64;
-
-pub type Depth = i8;
-
-pub trait Heuristic {
-    fn name() -> String;
-    fn evaluate(board: &StandardBoard, state: &State) -> HeuristicValue;
-}
-
-pub trait Evaluator {
-    type EvaluatorState;
-    fn name() -> String;
-    fn new_state() -> Self::EvaluatorState;
-    fn new_search(evaluator_state: &mut Self::EvaluatorState);
-    fn reset(evaluator_state: &mut Self::EvaluatorState);
-
-    fn evaluate_moves<H>(evaluator_state: &mut Self::EvaluatorState, board:&StandardBoard, state: &State, depth: Depth) -> (Option<(Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic {
-        let start_time = time::precise_time_ns();
-        let (best_move, mut info) = Self::evaluate_moves_impl::<H>(evaluator_state, board, state, depth);
-        let duration_seconds = (time::precise_time_ns() - start_time) as f64 / 1_000_000_000f64;
-        info.time += duration_seconds;
-        (best_move, info)
-    }
-    fn evaluate_moves_impl<H>(evaluator_state: &mut Self::EvaluatorState, board:&StandardBoard, state: &State, depth: Depth) -> (Option<(Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic;
-}
-
-// the manual/crap way
-pub fn principal_variant<E, H>(evaluator_state: &mut E::EvaluatorState, board:&StandardBoard, state:&State, depth:Depth) where E: Evaluator, H: Heuristic {
-    println!("about to playout {}", board.print(state));
-    let mut current_state = state.clone();
-    for d in (1..(depth+1)).rev() {
-        let (best_move, _) = E::evaluate_moves::<H>(evaluator_state, board, &current_state, d);
-        println!("best move -> {:?}", best_move);
-        if let Some((mve, _)) = best_move {
-            current_state = board.apply(mve, &current_state);
-            let h = H::evaluate(board, &current_state);
-            println!("depth {:?} playing move {:?} score {:?}", d, mve, h);
-            println!("{}", board.print(&current_state));
-        } else {
-            println!("no move at depth {:?}", d);
-        }
-    }
-    // println!("what is shit -> {:?}", shit);
-}
-
 
 
 
@@ -106,41 +77,40 @@ pub type BuilderLocations = [[Slot; 2]; 2];
 
 const PLAYERS : usize = 2;
 const BUILDERS : usize = 2;
-const BOARD_SIZE : usize = 5;
 
-const PRE_ROTATE : Position = Position { x: -2, y: -2 };
-const POST_ROTATE : Position = Position { x: 2, y: 2 };
+pub const DEFAULT_BOARD_SIZE : usize = 5;
+pub const MIN_BOARD_SIZE : usize = 4;
+pub const MAX_BOARD_SIZE : usize = 7;
+pub const MAX_SLOT_COUNT : usize = MAX_BOARD_SIZE * MAX_BOARD_SIZE;
 
-pub fn rotate_90(pos: Position) -> Position {
-    (ROTATE_90 * (pos + PRE_ROTATE)) + POST_ROTATE
+// symmetries of a size x size board, rotations are about the board's center
+pub fn rotate_90(pos: Position, size: i8) -> Position {
+    (ROTATE_90 * pos) + Position { x: size - 1, y: 0 }
 }
 
-pub fn rotate_180(pos: Position) -> Position {
-    (ROTATE_180 * (pos + PRE_ROTATE)) + POST_ROTATE
+pub fn rotate_180(pos: Position, size: i8) -> Position {
+    (ROTATE_180 * pos) + Position { x: size - 1, y: size - 1 }
 }
 
-pub fn rotate_270(pos: Position) -> Position {
-    (ROTATE_270 * (pos + PRE_ROTATE)) + POST_ROTATE
+pub fn rotate_270(pos: Position, size: i8) -> Position {
+    (ROTATE_270 * pos) + Position { x: 0, y: size - 1 }
 }
 
-pub fn reflect_x(pos: Position) -> Position {
-    let mut p = pos;
-    p.x = (p.x - 2) * -1 + 2;
-    p
+pub fn reflect_x(pos: Position, size: i8) -> Position {
+    Position { x: size - 1 - pos.x, y: pos.y }
 }
 
-pub fn reflect_y(pos: Position) -> Position {
-    let mut p = pos;
-    p.y = (p.y - 2) * -1 + 2;
-    p
+pub fn reflect_y(pos: Position, size: i8) -> Position {
+    Position { x: pos.x, y: size - 1 - pos.y }
 }
 
-pub fn reflect_diag_a(pos: Position) -> Position {
+#[allow(unused_variables)]
+pub fn reflect_diag_a(pos: Position, size: i8) -> Position {
     Position { x: pos.y, y: pos.x }
 }
 
-pub fn reflect_diag_b(pos: Position) -> Position {
-    reflect_diag_a(rotate_180(pos))
+pub fn reflect_diag_b(pos: Position, size: i8) -> Position {
+    reflect_diag_a(rotate_180(pos, size), size)
 }
 
 
@@ -148,6 +118,12 @@ pub fn reflect_diag_b(pos: Position) -> Position {
 pub enum Move {
     PlaceBuilders { a: Slot, b: Slot },
     Move { from: Slot, to:Slot, build: Slot },
+    DoubleBuild { from: Slot, to: Slot, build: Slot, second: Slot }, // demeter (elsewhere) + hephaestus (same slot)
+    Swap { from: Slot, to: Slot, build: Slot }, // apollo, opponent ends up on from
+    Push { from: Slot, to: Slot, build: Slot }, // minotaur, opponent is pushed on past to
+    Dome { from: Slot, to: Slot, build: Slot }, // atlas, dome at any level
+    BuildFirst { first: Slot, from: Slot, to: Slot, build: Slot }, // prometheus, build before moving
+    Travel { from: Slot, to: Slot, other_from: Slot, other_to: Slot, build: Slot }, // hermes, both walk their own level (or stay), either builds
 }
 
 impl Move {
@@ -155,6 +131,39 @@ impl Move {
         match self {
             &Move::PlaceBuilders { a, b } => vec![a, b],
             &Move::Move { from, to, build } => vec![from, to, build],
+            &Move::DoubleBuild { from, to, build, second } => vec![from, to, build, second],
+            &Move::Swap { from, to, build } => vec![from, to, build],
+            &Move::Push { from, to, build } => vec![from, to, build],
+            &Move::Dome { from, to, build } => vec![from, to, build],
+            &Move::BuildFirst { first, from, to, build } => vec![first, from, to, build],
+            &Move::Travel { from, to, other_from, other_to, build } => vec![from, to, other_from, other_to, build],
+        }
+    }
+
+    pub fn movement(&self) -> Option<(Slot, Slot)> {
+        match self {
+            &Move::PlaceBuilders { .. } => None,
+            &Move::Move { from, to, .. } |
+            &Move::DoubleBuild { from, to, .. } |
+            &Move::Swap { from, to, .. } |
+            &Move::Push { from, to, .. } |
+            &Move::Dome { from, to, .. } |
+            &Move::BuildFirst { from, to, .. } |
+            &Move::Travel { from, to, .. } => Some((from, to)), // travel's other builder comes second
+        }
+    }
+
+    // the build after moving, every move but placement has one
+    pub fn build(&self) -> Option<Slot> {
+        match self {
+            &Move::PlaceBuilders { .. } => None,
+            &Move::Move { build, .. } |
+            &Move::DoubleBuild { build, .. } |
+            &Move::Swap { build, .. } |
+            &Move::Push { build, .. } |
+            &Move::Dome { build, .. } |
+            &Move::BuildFirst { build, .. } |
+            &Move::Travel { build, .. } => Some(build),
         }
     }
 }
@@ -0,0 +1,197 @@
+use game::santorini::*;
+use game::*;
+
+use aphid::HashMap;
+use std::cmp::{min, max};
+
+// placement moves worked out once per board rather than deduping with a HashSet on every call.
+// the pairs a player can place only depend on where the opponent's builders are, and symmetric opponent
+// placements give symmetric pairs, so one list is kept per class of opponent placements and transformed out.
+// moves likewise get each slot's destinations along with the squares around each destination to build on
+#[derive(Debug, Clone)]
+pub struct MoveTables {
+    pub destinations : Vec<Vec<(Slot, Packed1)>>, // builder's slot -> each square it could step to, with the build squares from there
+    pub placements : HashMap<Packed1, (Symmetry, usize)>, // opponent's builders -> the symmetry from their class, and the class
+    pub placement_classes : Vec<Vec<(Slot, Slot)>>, // placement pairs for each class' first member, one per symmetric group
+}
+
+impl MoveTables {
+    pub fn empty() -> MoveTables {
+        MoveTables {
+            destinations: Vec::new(),
+            placements: HashMap::default(),
+            placement_classes: Vec::new(),
+        }
+    }
+
+    // needs the board's transforms
+    pub fn new(board: &StandardBoard) -> MoveTables {
+        let mut tables = MoveTables::empty();
+        let slot_count = board.slots.len() as i8;
+
+        // the builder's own slot is always among the build squares, it's empty once they've left
+        tables.destinations = board.packed_adjacencies.iter().map(|adjacent| {
+            adjacent.iter().map(|to| (to, board.packed_adjacencies[to.0 as usize])).collect()
+        }).collect();
+
+        // placing first is its own class
+        tables.add_class(board, PACKED1_EMPTY);
+
+        for a in 0..slot_count {
+            for b in (a+1)..slot_count {
+                let occupied = Packed1((1 << a) | (1 << b));
+                if !tables.placements.contains_key(&occupied) {
+                    tables.add_class(board, occupied);
+                }
+            }
+        }
+
+        tables
+    }
+
+    fn add_class(&mut self, board: &StandardBoard, occupied: Packed1) {
+        let class = self.placement_classes.len();
+        self.placement_classes.push(board.placement_pairs(occupied, occupied));
+        self.placements.insert(occupied, (0, class));
+
+        for (t, transform) in board.transforms.iter().enumerate() {
+            let image = StandardBoard::transform_packed(transform, occupied);
+            self.placements.entry(image).or_insert(((t + 1) as Symmetry, class));
+        }
+    }
+}
+
+impl StandardBoard {
+    // one pair per symmetric group, for placing around the opponent's builders (which aren't moved by the symmetry)
+    pub fn placement_pairs(&self, opponents: Packed1, collision: Packed1) -> Vec<(Slot, Slot)> {
+        let mut pairs = Vec::new();
+        let mut seen : HashSet<[Packed1; 2]> = HashSet::default();
+
+        let slot_count = self.slots.len() as i8;
+        for a in 0..slot_count {
+            let a_mask = 1 << a;
+            if a_mask & collision.0 == 0 {
+                for b in (a+1)..slot_count {
+                    let b_mask = 1 << b;
+                    if b_mask & collision.0 == 0 {
+                        let builders = [Packed1(a_mask | b_mask), opponents];
+                        let dupe = self.transforms.iter().any(|slot_transform| seen.contains(&StandardBoard::transform_packed2(slot_transform, builders)));
+
+                        if !dupe {
+                            pairs.push((Slot(a), Slot(b)));
+                            seen.insert(builders);
+                        }
+                    }
+                }
+            }
+        }
+
+        pairs
+    }
+
+    pub fn sink_placements<T : MoveSink<Move>>(&self, state: &State, move_sink: &mut T) {
+        let opponents = state.builders[state.next_player().0 as usize];
+        let built = state.building_major | state.building_minor | state.domes;
+
+        // anything built before placing breaks the symmetry, that only happens in hand made positions
+        let class = if built.any() { None } else { self.tables.placements.get(&opponents) };
+
+        match class {
+            Some(&(symmetry, class)) => {
+                for &(a, b) in &self.tables.placement_classes[class] {
+                    let (a, b) = match symmetry {
+                        0 => (a, b),
+                        s => {
+                            let transform = &self.transforms[s as usize - 1];
+                            (transform.slots[a.0 as usize], transform.slots[b.0 as usize])
+                        },
+                    };
+                    move_sink.sink(Move::PlaceBuilders { a: min(a, b), b: max(a, b) });
+                }
+            },
+            None => {
+                for (a, b) in self.placement_pairs(opponents, state.collision()) {
+                    move_sink.sink(Move::PlaceBuilders { a: a, b: b });
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::*;
+    use time;
+
+    #[test]
+    fn tables_match_deduping() {
+        for size in MIN_BOARD_SIZE..(MAX_BOARD_SIZE + 1) {
+            let board = StandardBoard::with_size(size, ZobristHash::new_unseeded());
+            let pair_count = size * size * (size * size - 1) / 2;
+            assert_eq!(board.tables.placements.len(), pair_count + 1);
+
+            let mut generator = StateGenerator::new(size as u64);
+            for _ in 0..40 {
+                let state = generator.state(&board, GamePhase::Placement);
+                let mut tabled = Vec::new();
+                board.next_moves(&state, &mut tabled);
+
+                let opponents = state.builders[state.next_player().0 as usize];
+                assert_eq!(tabled.len(), board.placement_pairs(opponents, state.collision()).len());
+                assert_eq!(compare_generators(&board, &state), None);
+            }
+        }
+    }
+
+    #[test]
+    fn destinations_match_adjacencies() {
+        for size in MIN_BOARD_SIZE..(MAX_BOARD_SIZE + 1) {
+            let board = StandardBoard::with_size(size, ZobristHash::new_unseeded());
+            for from in &board.slots {
+                let destinations = &board.tables.destinations[from.0 as usize];
+                assert_eq!(destinations.len(), board.packed_adjacencies[from.0 as usize].count() as usize);
+                for &(to, build_squares) in destinations {
+                    assert!(board.packed_adjacencies[from.0 as usize].get(to) > 0);
+                    assert!(build_squares.get(*from) > 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn built_before_placing() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = INITIAL_STATE;
+        state.domes.set(Slot(12), 1);
+
+        let mut moves = Vec::new();
+        board.next_moves(&state, &mut moves);
+        assert!(moves.iter().all(|mve| !mve.to_slots().contains(&Slot(12))));
+        assert_eq!(moves.len(), board.placement_pairs(PACKED1_EMPTY, state.collision()).len());
+    }
+
+    // wall clock, so it's run by hand (cargo test --release -- --ignored) rather than failing on a busy machine
+    #[test]
+    #[ignore]
+    fn movegen_throughput() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut move_stack = MoveStack::new();
+        let mut generator = StateGenerator::mortals(19);
+        let states : Vec<State> = (0..200).map(|_| generator.any_state(&board)).chain(vec![distant_state(&board), INITIAL_STATE]).collect();
+
+        let rounds = 200;
+        let mut moves = 0;
+        let start = time::precise_time_ns();
+        for _ in 0..rounds {
+            for state in &states {
+                board.next_moves(state, &mut move_stack);
+                moves += move_stack.next;
+                move_stack.next = 0;
+            }
+        }
+        let as_seconds = (time::precise_time_ns() - start) as f64 / 1_000_000_000f64;
+        let per_second = moves as f64 / 1_000_000f64 / as_seconds;
+        println!("=== movegen === {} moves ({:.2}M/second) in {:.2} seconds", moves, per_second, as_seconds);
+    }
+}
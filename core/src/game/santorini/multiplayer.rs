@@ -0,0 +1,821 @@
+use game::santorini::*;
+use game::*;
+
+use std;
+
+use time;
+use rand::XorShiftRng;
+
+// free for all with 3 or 4 players, or 2v2 where teammates share a team's two builders and take alternate turns,
+// on any board size StandardBoard supports. mortals only, no gods.
+// this sits beside the two player State rather than generalising it. State, ZobristHash, the bitboard movegen and
+// everything keyed on two players (notation, fen, validation, the heuristics, the service) would all have to carry a
+// player count, and the god powers would need rules for several opponents that the cards don't give, to support a
+// variant the ui doesn't offer. so a multiplayer game is its own Game over the shared Geometry.
+// a free for all has more than two sides, so it's searched with MaxN, or with Paranoid and the minimax evaluators
+pub const MAX_PLAYERS : usize = 4;
+
+pub type Scores = [HeuristicValue; MAX_PLAYERS];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Mode {
+    FreeForAll { players: usize },
+    Teams, // 4 seats, seat 0 & 2 vs seat 1 & 3
+}
+
+impl Mode {
+    pub fn seats(&self) -> usize {
+        match *self {
+            Mode::FreeForAll { players } => players,
+            Mode::Teams => 4,
+        }
+    }
+
+    // whoever owns builders and wins or loses together
+    pub fn sides(&self) -> usize {
+        match *self {
+            Mode::FreeForAll { players } => players,
+            Mode::Teams => 2,
+        }
+    }
+
+    pub fn side(&self, seat: Player) -> usize {
+        match *self {
+            Mode::FreeForAll { .. } => seat.0 as usize,
+            Mode::Teams => (seat.0 as usize) % 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
+pub struct MultiState {
+    pub builders: [Packed1; MAX_PLAYERS], // per side
+    pub building_major : Packed1,
+    pub building_minor : Packed1,
+    pub domes : Packed1,
+    pub to_move : Player, // seat
+    pub eliminated : u8, // per side, trapped players are removed from the game with their builders
+}
+
+pub const INITIAL_MULTI_STATE : MultiState = MultiState {
+    builders: [PACKED1_EMPTY; MAX_PLAYERS],
+    building_major: PACKED1_EMPTY,
+    building_minor: PACKED1_EMPTY,
+    domes: PACKED1_EMPTY,
+    to_move: Player(0),
+    eliminated: 0,
+};
+
+impl MultiState {
+    pub fn collision(&self) -> Packed1 {
+        self.builders[0] | self.builders[1] | self.builders[2] | self.builders[3] | self.domes
+    }
+
+    // the side with a builder there
+    pub fn occupant(&self, slot: Slot) -> Option<usize> {
+        (0..MAX_PLAYERS).find(|&side| self.builders[side].get(slot) > 0)
+    }
+
+    pub fn is_eliminated(&self, side: usize) -> bool {
+        self.eliminated & (1 << side) > 0
+    }
+
+    pub fn get_building_height(&self, slot:Slot) -> u8 {
+        self.building_major.get(slot) * 2 + self.building_minor.get(slot)
+    }
+
+    pub fn set_building_height(&mut self, slot:Slot, height: u8) {
+        self.building_major.set(slot, (height >> 1) & 1);
+        self.building_minor.set(slot, height & 1);
+    }
+
+    pub fn hash_height(&self, slot:Slot) -> usize {
+        if self.domes.get(slot) == 1 {
+            4
+        } else {
+            self.get_building_height(slot) as usize
+        }
+    }
+
+    pub fn build_at(&mut self, slot:Slot) {
+        let height = self.get_building_height(slot);
+        if height == 3 {
+            self.domes.toggle(slot);
+        } else {
+            self.set_building_height(slot, height + 1);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MultiZobristHash {
+    pub to_move : [StateHash; MAX_PLAYERS],
+    pub builders : [Vec<StateHash>; MAX_PLAYERS], // sized for the largest board like ZobristHash
+    pub buildings : Vec<[StateHash; 5]>,
+    pub eliminated : [StateHash; MAX_PLAYERS],
+}
+
+impl MultiZobristHash {
+    pub fn new_unseeded() -> MultiZobristHash {
+        Self::new(&mut XorShiftRng::new_unseeded())
+    }
+
+    pub fn new<R : Rng>(r: &mut R) -> MultiZobristHash {
+        let mut hash = MultiZobristHash {
+            to_move: [STATE_HASH_ZERO; MAX_PLAYERS],
+            builders: [vec![STATE_HASH_ZERO; MAX_SLOT_COUNT], vec![STATE_HASH_ZERO; MAX_SLOT_COUNT], vec![STATE_HASH_ZERO; MAX_SLOT_COUNT], vec![STATE_HASH_ZERO; MAX_SLOT_COUNT]],
+            buildings: vec![[STATE_HASH_ZERO; 5]; MAX_SLOT_COUNT],
+            eliminated: [STATE_HASH_ZERO; MAX_PLAYERS],
+        };
+
+        for to_move in &mut hash.to_move {
+            *to_move = StateHash(r.next_u64());
+        }
+
+        for builder_hashes in &mut hash.builders {
+            for i in builder_hashes.iter_mut() {
+                *i = StateHash(r.next_u64());
+            }
+        }
+
+        for building in hash.buildings.iter_mut() {
+            for height in building {
+                *height = StateHash(r.next_u64());
+            }
+        }
+
+        for eliminated in &mut hash.eliminated {
+            *eliminated = StateHash(r.next_u64());
+        }
+
+        hash
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MultiBoard {
+    pub geometry : Geometry,
+    pub mode : Mode,
+    pub hash : MultiZobristHash,
+}
+
+// only has two sides with teams, free for all goes through Paranoid or MaxN
+impl Game for MultiBoard {
+    type State = MultiState;
+    type Move = Move;
+    type Player = Player;
+    type Undo = MultiState; // eliminations make the old state the simplest undo
+
+    fn initial_state(&self) -> MultiState {
+        INITIAL_MULTI_STATE
+    }
+
+    fn to_move(&self, state: &MultiState) -> Player {
+        state.to_move
+    }
+
+    fn next_player(&self, state: &MultiState) -> Player {
+        self.next_seat(state, state.to_move)
+    }
+
+    fn player_index(&self, player: Player) -> usize {
+        self.mode.side(player)
+    }
+
+    fn color(&self, player: Player) -> HeuristicValue {
+        if self.mode.sides() != 2 {
+            panic!("fn color needs two sides, {:?} has {}", self.mode, self.mode.sides());
+        }
+        match self.mode.side(player) {
+            0 => 1,
+            _ => -1,
+        }
+    }
+
+    fn next_moves<T : MoveSink<Move>>(&self, state: &MultiState, move_sink: &mut T) {
+        MultiBoard::next_moves(self, state, move_sink)
+    }
+
+    fn apply(&self, mve: Move, state: &MultiState) -> MultiState {
+        MultiBoard::apply(self, mve, state)
+    }
+
+    fn make_move(&self, state: &mut MultiState, mve: Move) -> MultiState {
+        let new_state = MultiBoard::apply(self, mve, state);
+        std::mem::replace(state, new_state)
+    }
+
+    #[allow(unused_variables)]
+    fn unmake_move(&self, state: &mut MultiState, mve: Move, undo: MultiState) {
+        *state = undo;
+    }
+
+    fn hash(&self, state: &MultiState) -> StateHash {
+        MultiBoard::hash(self, state)
+    }
+
+    fn delta_hash(&self, state: &MultiState, mve: Move) -> StateHash {
+        self.hash(state) ^ self.hash(&self.apply(mve, state))
+    }
+
+    fn winning_move(&self, state: &MultiState, mve: Move) -> bool {
+        MultiBoard::winning_move(self, state, mve)
+    }
+
+    #[allow(unused_variables)]
+    fn no_moves_value(&self, state: &MultiState) -> HeuristicValue {
+        WORST
+    }
+
+    fn alternating(&self) -> bool {
+        self.mode.sides() == 2
+    }
+
+    fn print(&self, state: &MultiState) -> String {
+        MultiBoard::print(self, state)
+    }
+}
+
+impl MultiBoard {
+    pub fn new(mode: Mode, hash: MultiZobristHash) -> MultiBoard {
+        MultiBoard::with_size(mode, DEFAULT_BOARD_SIZE, hash)
+    }
+
+    pub fn with_size(mode: Mode, size: usize, hash: MultiZobristHash) -> MultiBoard {
+        let sides = mode.sides();
+        assert!(sides >= 2 && sides <= MAX_PLAYERS, "{:?} needs 2 to {} sides", mode, MAX_PLAYERS);
+
+        MultiBoard {
+            geometry: Geometry::new(size),
+            mode: mode,
+            hash: hash,
+        }
+    }
+
+    pub fn free_for_all(players: usize) -> MultiBoard {
+        MultiBoard::new(Mode::FreeForAll { players: players }, MultiZobristHash::new_unseeded())
+    }
+
+    pub fn teams() -> MultiBoard {
+        MultiBoard::new(Mode::Teams, MultiZobristHash::new_unseeded())
+    }
+
+    pub fn side(&self, seat: Player) -> usize {
+        self.mode.side(seat)
+    }
+
+    // next seat around the table whose side is still in the game
+    pub fn next_seat(&self, state: &MultiState, seat: Player) -> Player {
+        let seats = self.mode.seats() as i8;
+        let mut next = seat;
+        for _ in 0..seats {
+            next = Player((next.0 + 1) % seats);
+            if !state.is_eliminated(self.side(next)) {
+                return next;
+            }
+        }
+        seat
+    }
+
+    pub fn sides_remaining(&self, state: &MultiState) -> usize {
+        (0..self.mode.sides()).filter(|&side| !state.is_eliminated(side)).count()
+    }
+
+    pub fn winner(&self, state: &MultiState) -> Option<usize> {
+        if self.sides_remaining(state) == 1 {
+            (0..self.mode.sides()).find(|&side| !state.is_eliminated(side))
+        } else {
+            None
+        }
+    }
+
+    pub fn placed(&self, state: &MultiState) -> bool {
+        (0..self.mode.sides()).all(|side| state.builders[side].any() || state.is_eliminated(side))
+    }
+
+    pub fn next_moves<T : MoveSink<Move>>(&self, state: &MultiState, move_sink: &mut T) {
+        if self.winner(state).is_some() {
+            return;
+        }
+
+        let side = self.side(state.to_move);
+        let builders = state.builders[side];
+        let collision = state.collision();
+        let available = !collision;
+
+        if builders.0 == 0 {
+            // every placement up to symmetry, there are no buildings yet
+            let mut seen : Vec<[Packed1; MAX_PLAYERS]> = Vec::new();
+
+            let slot_count = self.geometry.slots.len() as i8;
+            for a in 0..slot_count {
+                if collision.get(Slot(a)) > 0 {
+                    continue;
+                }
+                for b in (a+1)..slot_count {
+                    if collision.get(Slot(b)) > 0 {
+                        continue;
+                    }
+                    let mut new_builders = state.builders;
+                    new_builders[side] = Packed1(1 << a | 1 << b);
+
+                    let dupe = self.geometry.transforms.iter().any(|slot_transform| {
+                        let mut transformed = [PACKED1_EMPTY; MAX_PLAYERS];
+                        for i in 0..MAX_PLAYERS {
+                            transformed[i] = StandardBoard::transform_packed(slot_transform, new_builders[i]);
+                        }
+                        seen.contains(&transformed)
+                    });
+
+                    if !dupe {
+                        seen.push(new_builders);
+                        move_sink.sink(Move::PlaceBuilders { a: Slot(a), b: Slot(b) });
+                    }
+                }
+            }
+        } else {
+            for move_from in builders.iter() {
+                let current_height = state.get_building_height(move_from);
+                for move_to in (self.geometry.packed_adjacencies[move_from.0 as usize] & available).iter() {
+                    if state.get_building_height(move_to) <= current_height + 1 {
+                        let buildable_adjacencies = self.geometry.packed_adjacencies[move_to.0 as usize] & available ^ Packed1(1 << move_from.0);
+                        for build_at in buildable_adjacencies.iter() {
+                            move_sink.sink(Move::Move { from: move_from, to: move_to, build: build_at });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // there's always somewhere to build after moving, the square you left
+    pub fn has_moves(&self, state: &MultiState, side: usize) -> bool {
+        let available = !state.collision();
+        state.builders[side].iter().any(|from| {
+            let max_height = state.get_building_height(from) + 1;
+            (self.geometry.packed_adjacencies[from.0 as usize] & available).iter().any(|to| state.get_building_height(to) <= max_height)
+        })
+    }
+
+    // players are knocked out when their turn comes around and they're trapped. like StandardBoard::apply it
+    // trusts the move
+    pub fn apply(&self, mve: Move, state: &MultiState) -> MultiState {
+        let side = self.side(state.to_move);
+        let mut new_state = state.clone();
+
+        match mve {
+            Move::PlaceBuilders { a, b } => {
+                new_state.builders[side] |= Packed1(1 << a.0 | 1 << b.0);
+            },
+            Move::Move { from, to, build } => {
+                new_state.builders[side] ^= Packed1(1 << from.0 | 1 << to.0);
+                new_state.build_at(build);
+            },
+            _ => panic!("{:?} needs a god, multiplayer games are mortal", mve),
+        }
+
+        let mut seat = state.to_move;
+        loop {
+            seat = self.next_seat(&new_state, seat);
+            let next_side = self.side(seat);
+            if next_side == side || !new_state.builders[next_side].any() || self.has_moves(&new_state, next_side) {
+                break;
+            }
+            new_state.builders[next_side] = PACKED1_EMPTY;
+            new_state.eliminated |= 1 << next_side;
+        }
+
+        new_state.to_move = seat;
+        new_state
+    }
+
+    pub fn hash(&self, state: &MultiState) -> StateHash {
+        let mut hash = self.hash.to_move[state.to_move.0 as usize];
+
+        for side in 0..MAX_PLAYERS {
+            for bl in state.builders[side].iter() {
+                hash = hash ^ self.hash.builders[side][bl.0 as usize];
+            }
+            if state.is_eliminated(side) {
+                hash = hash ^ self.hash.eliminated[side];
+            }
+        }
+
+        for &sl in &self.geometry.slots {
+            hash = hash ^ self.hash.buildings[sl.0 as usize][state.hash_height(sl)];
+        }
+
+        hash
+    }
+
+    // climbing onto a third level, or trapping everybody else
+    pub fn winning_move(&self, state: &MultiState, mve: Move) -> bool {
+        match mve.movement() {
+            Some((_, to)) if state.get_building_height(to) == 3 => true,
+            Some(_) => self.winner(&self.apply(mve, state)).is_some(),
+            None => false,
+        }
+    }
+
+    // every side wins or loses outright, teammates share a result
+    pub fn win_scores(&self, side: usize) -> Scores {
+        let mut scores = [WORST; MAX_PLAYERS];
+        scores[side] = BEST;
+        scores
+    }
+
+    pub fn print(&self, state: &MultiState) -> String {
+        let mut out = String::new();
+
+        out.push_str(&format!(" === To move {:?} ({:?}) === \n", state.to_move, self.mode));
+        let size = self.geometry.size as i8;
+        let divider = format!("{}+\n", "+---".repeat(size as usize));
+
+        for y in 0..size {
+            out.push_str(&divider);
+            let mut cells : Vec<String> = Vec::new();
+            for x in 0..size {
+                let slot = self.geometry.slot(Position { x: x, y: y });
+                let terrain = if state.domes.get(slot) > 0 {
+                    "D".to_string()
+                } else {
+                    state.get_building_height(slot).to_string()
+                };
+                let builder = (0..MAX_PLAYERS).find(|&side| state.builders[side].get(slot) > 0)
+                                              .map(|side| format!("{}", side))
+                                              .unwrap_or(" ".into());
+                cells.push(format!("{}{} ", terrain, builder));
+            }
+            out.push_str("|");
+            out.push_str(&cells.join("|"));
+            out.push_str("|\n");
+        }
+        out.push_str(&divider);
+        out
+    }
+}
+
+// a 2 sided view of a free for all, everybody else is assumed to be playing against us, so the minimax evaluators apply.
+// only those, the side to move doesn't alternate (two opponents move in a row) and the negamax evaluators refuse it
+pub struct Paranoid<'a> {
+    pub board : &'a MultiBoard,
+    pub side : usize,
+}
+
+impl<'a> Game for Paranoid<'a> {
+    type State = MultiState;
+    type Move = Move;
+    type Player = Player;
+    type Undo = MultiState;
+
+    fn initial_state(&self) -> MultiState {
+        self.board.initial_state()
+    }
+
+    fn to_move(&self, state: &MultiState) -> Player {
+        state.to_move
+    }
+
+    fn next_player(&self, state: &MultiState) -> Player {
+        self.board.next_seat(state, state.to_move)
+    }
+
+    fn player_index(&self, player: Player) -> usize {
+        self.board.side(player)
+    }
+
+    fn color(&self, player: Player) -> HeuristicValue {
+        if self.board.side(player) == self.side {
+            1
+        } else {
+            -1
+        }
+    }
+
+    fn next_moves<T : MoveSink<Move>>(&self, state: &MultiState, move_sink: &mut T) {
+        self.board.next_moves(state, move_sink)
+    }
+
+    fn apply(&self, mve: Move, state: &MultiState) -> MultiState {
+        self.board.apply(mve, state)
+    }
+
+    fn make_move(&self, state: &mut MultiState, mve: Move) -> MultiState {
+        self.board.make_move(state, mve)
+    }
+
+    fn unmake_move(&self, state: &mut MultiState, mve: Move, undo: MultiState) {
+        self.board.unmake_move(state, mve, undo)
+    }
+
+    fn hash(&self, state: &MultiState) -> StateHash {
+        self.board.hash(state)
+    }
+
+    fn delta_hash(&self, state: &MultiState, mve: Move) -> StateHash {
+        Game::delta_hash(self.board, state, mve)
+    }
+
+    fn winning_move(&self, state: &MultiState, mve: Move) -> bool {
+        self.board.winning_move(state, mve)
+    }
+
+    #[allow(unused_variables)]
+    fn no_moves_value(&self, state: &MultiState) -> HeuristicValue {
+        WORST
+    }
+
+    fn alternating(&self) -> bool {
+        self.board.mode.sides() == 2
+    }
+
+    fn print(&self, state: &MultiState) -> String {
+        self.board.print(state)
+    }
+}
+
+pub trait MultiHeuristic {
+    fn name() -> String;
+    fn evaluate(board: &MultiBoard, state: &MultiState) -> Scores;
+}
+
+// how high each side stands and how much room it has to climb, relative to everybody else
+pub struct MultiHeightHeuristic {}
+
+impl MultiHeightHeuristic {
+    fn strength(board: &MultiBoard, state: &MultiState, side: usize, available: Packed1) -> HeuristicValue {
+        let mut n : HeuristicValue = 0;
+        for bl in state.builders[side].iter() {
+            let height = state.get_building_height(bl);
+            n += STANDING_SCORE[height as usize];
+            for move_to in (board.geometry.packed_adjacencies[bl.0 as usize] & available).iter() {
+                let target_height = state.get_building_height(move_to);
+                if target_height <= height + 1 {
+                    n += NEIGHBOUR_SCORE[target_height as usize];
+                }
+            }
+        }
+        n
+    }
+}
+
+impl MultiHeuristic for MultiHeightHeuristic {
+    fn name() -> String {
+        "MultiHeightHeuristic".into()
+    }
+
+    fn evaluate(board: &MultiBoard, state: &MultiState) -> Scores {
+        let available = !state.collision();
+        let sides = board.mode.sides();
+
+        let mut strengths = [0; MAX_PLAYERS];
+        let mut total = 0;
+        for side in 0..sides {
+            strengths[side] = Self::strength(board, state, side, available);
+            total += strengths[side];
+        }
+
+        let mut scores = [0; MAX_PLAYERS];
+        for side in 0..sides {
+            scores[side] = strengths[side] * (sides as HeuristicValue) - total;
+        }
+        scores
+    }
+}
+
+impl Heuristic<MultiBoard> for MultiHeightHeuristic {
+    fn name() -> String {
+        "MultiHeightHeuristic".into()
+    }
+
+    fn evaluate(board: &MultiBoard, state: &MultiState) -> HeuristicValue {
+        <MultiHeightHeuristic as MultiHeuristic>::evaluate(board, state)[0]
+    }
+}
+
+impl<'a> Heuristic<Paranoid<'a>> for MultiHeightHeuristic {
+    fn name() -> String {
+        "MultiHeightHeuristic".into()
+    }
+
+    fn evaluate(paranoid: &Paranoid<'a>, state: &MultiState) -> HeuristicValue {
+        <MultiHeightHeuristic as MultiHeuristic>::evaluate(paranoid.board, state)[paranoid.side]
+    }
+}
+
+// every side maximises its own entry of the score vector, no pruning
+pub struct MaxN {}
+
+impl MaxN {
+    pub fn name() -> String {
+        "MaxN".into()
+    }
+
+    pub fn evaluate_moves<H>(board: &MultiBoard, state: &MultiState, depth: Depth) -> (Option<(Move, Scores)>, EvaluatorInfo) where H: MultiHeuristic {
+        let start_time = time::precise_time_ns();
+        let side = board.side(state.to_move);
+
+        let mut moves : Vec<Move> = Vec::with_capacity(200);
+        board.next_moves(state, &mut moves);
+
+        let mut total_moves = 0;
+        let mut best : Option<(Move, Scores)> = None;
+
+        for &mve in &moves {
+            let (scores, move_count) = if board.winning_move(state, mve) {
+                (board.win_scores(side), 1)
+            } else {
+                let new_state = board.apply(mve, state);
+                MaxN::eval::<H>(board, &new_state, depth - 1)
+            };
+            total_moves += move_count;
+
+            let better = match best {
+                Some((_, best_scores)) => scores[side] > best_scores[side],
+                None => true,
+            };
+            if better {
+                best = Some((mve, scores));
+            }
+        }
+
+        let mut info = EvaluatorInfo::from_moves_depth(total_moves, depth);
+        info.time += (time::precise_time_ns() - start_time) as f64 / 1_000_000_000f64;
+        (best, info)
+    }
+
+    pub fn eval<H>(board: &MultiBoard, state: &MultiState, depth: Depth) -> (Scores, MoveCount) where H: MultiHeuristic {
+        if let Some(winner) = board.winner(state) {
+            return (board.win_scores(winner), 1);
+        }
+
+        if depth == 0 {
+            return (H::evaluate(board, state), 1);
+        }
+
+        let side = board.side(state.to_move);
+
+        let mut moves : Vec<Move> = Vec::with_capacity(200);
+        board.next_moves(state, &mut moves);
+
+        let mut total_moves = 0;
+        let mut best : Option<Scores> = None;
+
+        for &mve in &moves {
+            if board.winning_move(state, mve) {
+                return (board.win_scores(side), total_moves + 1);
+            }
+            let new_state = board.apply(mve, state);
+            let (scores, move_count) = MaxN::eval::<H>(board, &new_state, depth - 1);
+            total_moves += move_count;
+
+            let better = match best {
+                Some(best_scores) => scores[side] > best_scores[side],
+                None => true,
+            };
+            if better {
+                best = Some(scores);
+            }
+        }
+
+        (best.unwrap_or_else(|| H::evaluate(board, state)), total_moves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placed(board: &MultiBoard, placements: &[(i8, i8)]) -> MultiState {
+        let mut state = INITIAL_MULTI_STATE;
+        for &(a, b) in placements {
+            state = board.apply(Move::PlaceBuilders { a: Slot(a), b: Slot(b) }, &state);
+        }
+        state
+    }
+
+    #[test]
+    fn free_for_all_turn_order() {
+        let board = MultiBoard::free_for_all(3);
+        let state = placed(&board, &[(0, 4), (20, 24), (12, 13)]);
+        assert_eq!(state.to_move, Player(0));
+        assert!(board.placed(&state));
+
+        let state = board.apply(Move::Move { from: Slot(0), to: Slot(1), build: Slot(0) }, &state);
+        assert_eq!(state.to_move, Player(1));
+    }
+
+    #[test]
+    fn teammates_share_builders() {
+        let board = MultiBoard::teams();
+        let state = placed(&board, &[(0, 4), (20, 24)]);
+        assert_eq!(state.to_move, Player(2));
+
+        let mut moves = Vec::new();
+        board.next_moves(&state, &mut moves);
+        assert!(moves.contains(&Move::Move { from: Slot(0), to: Slot(1), build: Slot(0) }));
+        assert_eq!(board.color(Player(2)), 1);
+        assert_eq!(board.color(Player(3)), -1);
+    }
+
+    #[test]
+    fn trapped_players_are_eliminated() {
+        let board = MultiBoard::free_for_all(3);
+        let mut state = placed(&board, &[(12, 13), (0, 1), (23, 24)]);
+        // player 1 in the corner, boxed in by domes except for slot 7
+        for &sl in &[2, 5, 6] {
+            state.domes.set(Slot(sl), 1);
+        }
+        state.set_building_height(Slot(7), 2);
+
+        let new_state = board.apply(Move::Move { from: Slot(12), to: Slot(11), build: Slot(7) }, &state);
+        assert!(new_state.is_eliminated(1));
+        assert_eq!(new_state.builders[1], PACKED1_EMPTY);
+        assert_eq!(new_state.to_move, Player(2));
+        assert_eq!(board.winner(&new_state), None);
+    }
+
+    #[test]
+    fn delta_hash_matches_hash() {
+        let board = MultiBoard::free_for_all(4);
+        let mut state = placed(&board, &[(0, 4), (20, 24), (12, 13), (6, 8)]);
+        state.set_building_height(Slot(7), 2);
+        let hash = board.hash(&state);
+
+        let mut moves = Vec::new();
+        board.next_moves(&state, &mut moves);
+        for mve in moves {
+            assert_eq!(hash ^ Game::delta_hash(&board, &state, mve), board.hash(&board.apply(mve, &state)));
+        }
+    }
+
+    #[test]
+    fn max_n_ascends() {
+        let board = MultiBoard::free_for_all(3);
+        let mut state = placed(&board, &[(0, 4), (20, 24), (12, 22)]);
+        state.set_building_height(Slot(0), 2);
+        state.set_building_height(Slot(1), 3);
+
+        let (best, _) = MaxN::evaluate_moves::<MultiHeightHeuristic>(&board, &state, 3);
+        let (mve, scores) = best.expect("a move");
+        assert_eq!(mve.movement(), Some((Slot(0), Slot(1))));
+        assert_eq!(scores[0], BEST);
+    }
+
+    #[test]
+    fn paranoid_blocks_ascension() {
+        let board = MultiBoard::free_for_all(3);
+        let mut state = placed(&board, &[(10, 14), (0, 4), (20, 24)]);
+        // player 1 threatens to climb 0 -> 1, player 0 has to step next to it and dome slot 1
+        state.set_building_height(Slot(0), 2);
+        state.set_building_height(Slot(1), 3);
+
+        let paranoid = Paranoid { board: &board, side: 0 };
+        let (best, _) = MiniMaxAlphaBeta::evaluate_moves::<MultiHeightHeuristic>(&mut (), &paranoid, &state, 2);
+        let (mve, _) = best.expect("a move");
+        assert_eq!(mve.build(), Some(Slot(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "needs a minimax evaluator")]
+    fn paranoid_refuses_negamax() {
+        let board = MultiBoard::free_for_all(3);
+        let state = placed(&board, &[(10, 14), (0, 4), (20, 24)]);
+        let paranoid = Paranoid { board: &board, side: 0 };
+        let mut ev_state = EvState::new();
+        NegaMaxAlphaBetaExp::evaluate_moves::<MultiHeightHeuristic>(&mut ev_state, &paranoid, &state, 2);
+    }
+
+    #[test]
+    fn other_board_sizes() {
+        let board = MultiBoard::with_size(Mode::FreeForAll { players: 3 }, 7, MultiZobristHash::new_unseeded());
+        let mut state = placed(&board, &[(0, 48), (6, 42), (24, 25)]);
+        state.set_building_height(Slot(47), 1);
+        assert_eq!(board.print(&state).lines().count(), 1 + 7 * 2 + 1);
+
+        let hash = board.hash(&state);
+        let mut moves = Vec::new();
+        board.next_moves(&state, &mut moves);
+        assert!(moves.contains(&Move::Move { from: Slot(48), to: Slot(47), build: Slot(48) }));
+        for mve in moves {
+            assert_eq!(hash ^ Game::delta_hash(&board, &state, mve), board.hash(&board.apply(mve, &state)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "multiplayer games are mortal")]
+    fn refuses_god_moves() {
+        let board = MultiBoard::free_for_all(3);
+        let state = placed(&board, &[(0, 4), (1, 24), (20, 22)]);
+        board.apply(Move::Swap { from: Slot(0), to: Slot(1), build: Slot(2) }, &state);
+    }
+
+    #[test]
+    fn teams_search() {
+        let board = MultiBoard::teams();
+        let state = placed(&board, &[(6, 18), (8, 16)]);
+        let mut ev_state = EvState::new();
+        let (best, _) = NegaMaxAlphaBetaExp::evaluate_moves::<MultiHeightHeuristic>(&mut ev_state, &board, &state, 3);
+        assert!(best.is_some());
+    }
+}
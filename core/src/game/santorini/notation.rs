@@ -0,0 +1,247 @@
+use game::santorini::*;
+
+use std;
+use std::fmt;
+use std::str::FromStr;
+
+// coordinate notation, columns from a and rows from 1 starting top left
+//   a1,b3       placement
+//   a1b2c3      move a1 to b2, build c3
+//   a1b2c3+d4   a second build (demeter, or hephaestus on the same square)
+//   a1b2c3D     dome (atlas)
+//   a1b2c3S     swap (apollo)
+//   a1b2c3P     push (minotaur)
+//   d4:a1b2c3   build d4 before moving (prometheus)
+//   a1b2&c3c3d4 both builders walk their level, c3 staying put, then build d4 (hermes)
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationError {
+    Empty,
+    BadSquare(String), // not a column letter followed by a row digit
+    OffBoard(String), // a square past the edge of the board
+    Malformed(String), // the squares are fine, but it isn't the shape of any move
+    Illegal(Move, usize), // well formed, but not a legal move in the position. the board size, to write it back out
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &NotationError::Empty => write!(f, "no move given"),
+            &NotationError::BadSquare(ref text) => write!(f, "{:?} isn't a square, expected something like a1", text),
+            &NotationError::OffBoard(ref text) => write!(f, "{} is off the board", text),
+            &NotationError::Malformed(ref text) => write!(f, "{:?} isn't a move", text),
+            &NotationError::Illegal(mve, size) => write!(f, "{} isn't legal here", move_notation(mve, size)),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+pub fn square_name(slot: Slot, size: usize) -> String {
+    let size = size as i8;
+    let column = (b'a' + (slot.0 % size) as u8) as char;
+    format!("{}{}", column, slot.0 / size + 1)
+}
+
+fn take_square<'a>(text: &'a str, whole: &str, size: usize) -> Result<(Slot, &'a str), NotationError> {
+    let mut chars = text.chars();
+    let (column, row) = match (chars.next(), chars.next()) {
+        (Some(column), Some(row)) => (column, row),
+        _ => return Err(NotationError::Malformed(whole.into())),
+    };
+
+    if !column.is_ascii_lowercase() || !row.is_ascii_digit() {
+        let bad : String = text.chars().take(2).collect();
+        return Err(NotationError::BadSquare(bad));
+    }
+
+    let x = (column as u8 - b'a') as usize;
+    let y = (row as u8 - b'0') as usize;
+    if x >= size || y < 1 || y > size {
+        return Err(NotationError::OffBoard(text[..2].into()));
+    }
+
+    Ok((Slot((x + (y - 1) * size) as i8), &text[2..]))
+}
+
+pub fn parse_square(text: &str, size: usize) -> Result<Slot, NotationError> {
+    let (slot, rest) = take_square(text, text, size)?;
+    if rest.is_empty() {
+        Ok(slot)
+    } else {
+        Err(NotationError::BadSquare(text.into()))
+    }
+}
+
+pub fn move_notation(mve: Move, size: usize) -> String {
+    let sq = |slot: Slot| square_name(slot, size);
+    match mve {
+        Move::PlaceBuilders { a, b } => format!("{},{}", sq(a), sq(b)),
+        Move::Move { from, to, build } => format!("{}{}{}", sq(from), sq(to), sq(build)),
+        Move::DoubleBuild { from, to, build, second } => format!("{}{}{}+{}", sq(from), sq(to), sq(build), sq(second)),
+        Move::Dome { from, to, build } => format!("{}{}{}D", sq(from), sq(to), sq(build)),
+        Move::Swap { from, to, build } => format!("{}{}{}S", sq(from), sq(to), sq(build)),
+        Move::Push { from, to, build } => format!("{}{}{}P", sq(from), sq(to), sq(build)),
+        Move::BuildFirst { first, from, to, build } => format!("{}:{}{}{}", sq(first), sq(from), sq(to), sq(build)),
+        Move::Travel { from, to, other_from, other_to, build } => format!("{}{}&{}{}{}", sq(from), sq(to), sq(other_from), sq(other_to), sq(build)),
+    }
+}
+
+pub fn parse_move_notation(text: &str, size: usize) -> Result<Move, NotationError> {
+    let whole = text.trim();
+    if whole.is_empty() {
+        return Err(NotationError::Empty);
+    }
+
+    let (first, rest) = match whole.find(':') {
+        Some(idx) => (Some(parse_square(&whole[..idx], size)?), &whole[(idx + 1)..]),
+        None => (None, whole),
+    };
+
+    let (from, rest) = take_square(rest, whole, size)?;
+
+    if rest.starts_with(',') {
+        if first.is_some() {
+            return Err(NotationError::Malformed(whole.into()));
+        }
+        let (b, rest) = take_square(&rest[1..], whole, size)?;
+        if !rest.is_empty() {
+            return Err(NotationError::Malformed(whole.into()));
+        }
+        return Ok(Move::PlaceBuilders { a: from, b: b });
+    }
+
+    let (to, rest) = take_square(rest, whole, size)?;
+
+    if rest.starts_with('&') {
+        if first.is_some() {
+            return Err(NotationError::Malformed(whole.into()));
+        }
+        let (other_from, rest) = take_square(&rest[1..], whole, size)?;
+        let (other_to, rest) = take_square(rest, whole, size)?;
+        let build = parse_square(rest, size)?;
+        return Ok(Move::Travel { from: from, to: to, other_from: other_from, other_to: other_to, build: build });
+    }
+
+    let (build, rest) = take_square(rest, whole, size)?;
+
+    match (first, rest) {
+        (Some(first), "") => Ok(Move::BuildFirst { first: first, from: from, to: to, build: build }),
+        (None, "") => Ok(Move::Move { from: from, to: to, build: build }),
+        (None, "D") => Ok(Move::Dome { from: from, to: to, build: build }),
+        (None, "S") => Ok(Move::Swap { from: from, to: to, build: build }),
+        (None, "P") => Ok(Move::Push { from: from, to: to, build: build }),
+        (None, second) if second.starts_with('+') => {
+            let second = parse_square(&second[1..], size)?;
+            Ok(Move::DoubleBuild { from: from, to: to, build: build, second: second })
+        },
+        _ => Err(NotationError::Malformed(whole.into())),
+    }
+}
+
+impl StandardBoard {
+    pub fn square_name(&self, slot: Slot) -> String {
+        square_name(slot, self.size)
+    }
+
+    pub fn move_notation(&self, mve: Move) -> String {
+        move_notation(mve, self.size)
+    }
+
+    pub fn parse_move(&self, text: &str) -> Result<Move, NotationError> {
+        parse_move_notation(text, self.size)
+    }
+
+    // against every legal move, not just the ones left after symmetric placements are pruned
+    pub fn parse_legal_move(&self, state: &State, text: &str) -> Result<Move, NotationError> {
+        let mve = self.parse_move(text)?;
+        let mut legal_moves = Vec::new();
+        self.next_moves_for_player(state, &mut legal_moves);
+        if legal_moves.contains(&mve) {
+            Ok(mve)
+        } else {
+            Err(NotationError::Illegal(mve, self.size))
+        }
+    }
+}
+
+// the standard 5x5 board, boards of other sizes go through StandardBoard
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&move_notation(*self, DEFAULT_BOARD_SIZE))
+    }
+}
+
+impl FromStr for Move {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Move, NotationError> {
+        parse_move_notation(s, DEFAULT_BOARD_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_kinds() -> Vec<Move> {
+        vec![
+            Move::PlaceBuilders { a: Slot(0), b: Slot(11) },
+            Move::Move { from: Slot(0), to: Slot(6), build: Slot(12) },
+            Move::DoubleBuild { from: Slot(0), to: Slot(6), build: Slot(12), second: Slot(7) },
+            Move::DoubleBuild { from: Slot(0), to: Slot(6), build: Slot(12), second: Slot(12) },
+            Move::Dome { from: Slot(0), to: Slot(6), build: Slot(12) },
+            Move::Swap { from: Slot(0), to: Slot(6), build: Slot(12) },
+            Move::Push { from: Slot(0), to: Slot(6), build: Slot(12) },
+            Move::BuildFirst { first: Slot(1), from: Slot(0), to: Slot(6), build: Slot(12) },
+            Move::Travel { from: Slot(0), to: Slot(2), other_from: Slot(12), other_to: Slot(12), build: Slot(18) },
+        ]
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(Move::Move { from: Slot(0), to: Slot(6), build: Slot(12) }.to_string(), "a1b2c3");
+        assert_eq!(Move::PlaceBuilders { a: Slot(0), b: Slot(11) }.to_string(), "a1,b3");
+        assert_eq!(Move::Travel { from: Slot(0), to: Slot(2), other_from: Slot(12), other_to: Slot(12), build: Slot(18) }.to_string(), "a1c1&c3c3d4");
+        assert_eq!(square_name(Slot(24), 5), "e5");
+        assert_eq!(square_name(Slot(48), 7), "g7");
+    }
+
+    #[test]
+    fn round_trips() {
+        for mve in all_kinds() {
+            assert_eq!(mve.to_string().parse::<Move>(), Ok(mve));
+        }
+
+        let board = StandardBoard::with_size(7, ZobristHash::new_unseeded());
+        let far = Move::Move { from: Slot(40), to: Slot(48), build: Slot(47) };
+        assert_eq!(board.move_notation(far), "f6g7f7");
+        assert_eq!(board.parse_move("f6g7f7"), Ok(far));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!("".parse::<Move>(), Err(NotationError::Empty));
+        assert_eq!("a1b2".parse::<Move>(), Err(NotationError::Malformed("a1b2".into())));
+        assert_eq!("a1b2c3X".parse::<Move>(), Err(NotationError::Malformed("a1b2c3X".into())));
+        assert_eq!("a1,".parse::<Move>(), Err(NotationError::Malformed("a1,".into())));
+        assert_eq!("A1b2c3".parse::<Move>(), Err(NotationError::BadSquare("A1".into())));
+        assert_eq!("a1b2f3".parse::<Move>(), Err(NotationError::OffBoard("f3".into())));
+        assert_eq!("a1b2c6".parse::<Move>(), Err(NotationError::OffBoard("c6".into())));
+        assert_eq!("a1:a1,b2".parse::<Move>(), Err(NotationError::Malformed("a1:a1,b2".into())));
+        assert_eq!("a1:a2&b2b2c3".parse::<Move>(), Err(NotationError::Malformed("a1:a2&b2b2c3".into())));
+    }
+
+    #[test]
+    fn legality() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = INITIAL_STATE;
+        assert_eq!(board.parse_legal_move(&state, "c3,a1"), Ok(Move::PlaceBuilders { a: Slot(12), b: Slot(0) }));
+        assert_eq!(board.parse_legal_move(&state, "a1b2c3"), Err(NotationError::Illegal(Move::Move { from: Slot(0), to: Slot(6), build: Slot(12) }, 5)));
+
+        // written back out for the board it was read on
+        let large = StandardBoard::with_size(7, ZobristHash::new_unseeded());
+        let error = large.parse_legal_move(&INITIAL_STATE, "f6g7f7").unwrap_err();
+        assert_eq!(error.to_string(), "f6g7f7 isn't legal here");
+    }
+}
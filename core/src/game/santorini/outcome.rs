@@ -0,0 +1,116 @@
+use game::santorini::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WinReason {
+    Ascension, // moved up onto the third level, or pan dropping two
+    Trapped, // the player to move has no moves
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Outcome {
+    Ongoing,
+    Won { player: Player, reason: WinReason },
+}
+
+impl Outcome {
+    pub fn winner(&self) -> Option<Player> {
+        match *self {
+            Outcome::Ongoing => None,
+            Outcome::Won { player, .. } => Some(player),
+        }
+    }
+}
+
+impl StandardBoard {
+    // last_move is the move that led to state, without it a builder standing on the third level counts as
+    // having climbed there, which is only wrong after minotaur or apollo forced one up, and pan can't be seen
+    pub fn outcome(&self, state: &State, last_move: Option<Move>) -> Outcome {
+        let mover = state.next_player();
+
+        let ascended = match last_move {
+            None => (state.builders[mover.0 as usize] & state.building_map()[3]).any(),
+            Some(mve) => match mve.movement() {
+                None => false,
+                Some((from, to)) => {
+                    let to_height = state.get_building_height(to);
+                    // pan only plays plain moves, which can build back on the square it left
+                    let from_height = match mve {
+                        Move::Move { build, .. } if build == from => {
+                            if state.domes.get(from) > 0 { 3 } else { state.get_building_height(from) - 1 }
+                        },
+                        _ => state.get_building_height(from),
+                    };
+                    to_height == 3 || (state.gods[mover.0 as usize] == God::Pan && from_height >= to_height + 2)
+                },
+            },
+        };
+        if ascended {
+            return Outcome::Won { player: mover, reason: WinReason::Ascension };
+        }
+
+        // nobody is trapped while placing
+        if state.current_builders().any() {
+            let mut moves = Vec::new();
+            self.next_moves_for_player(state, &mut moves);
+            if moves.is_empty() {
+                return Outcome::Won { player: mover, reason: WinReason::Trapped };
+            }
+        }
+
+        Outcome::Ongoing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::*;
+
+    #[test]
+    fn ongoing() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        assert_eq!(board.outcome(&INITIAL_STATE, None), Outcome::Ongoing);
+        assert_eq!(board.outcome(&a_in_1(&board, Player(0)), None), Outcome::Ongoing);
+    }
+
+    #[test]
+    fn ascension() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = a_in_1(&board, Player(0));
+        let climb = Move::Move { from: Slot(1), to: Slot(2), build: Slot(3) };
+        assert!(board.ascension_winning_move(&state, climb));
+
+        let won = Outcome::Won { player: Player(0), reason: WinReason::Ascension };
+        let after = board.apply(climb, &state);
+        assert_eq!(board.outcome(&after, Some(climb)), won);
+        assert_eq!(board.outcome(&after, None), won);
+        assert_eq!(won.winner(), Some(Player(0)));
+    }
+
+    #[test]
+    fn pan_falls() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = distant_state(&board);
+        state.gods[0] = God::Pan;
+        state.set_building_height(Slot(1), 2);
+
+        let fall = Move::Move { from: Slot(1), to: Slot(2), build: Slot(1) };
+        assert!(board.ascension_winning_move(&state, fall));
+        assert_eq!(board.outcome(&board.apply(fall, &state), Some(fall)), Outcome::Won { player: Player(0), reason: WinReason::Ascension });
+
+        state.gods[0] = God::Mortal;
+        assert_eq!(board.outcome(&board.apply(fall, &state), Some(fall)), Outcome::Ongoing);
+    }
+
+    #[test]
+    fn trapped() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = distant_state(&board);
+        for &slot in &[17, 18, 19, 22] {
+            state.set_building_height(Slot(slot), 3);
+            state.domes.set(Slot(slot), 1);
+        }
+        state.to_move = Player(1);
+        assert_eq!(board.outcome(&state, None), Outcome::Won { player: Player(0), reason: WinReason::Trapped });
+    }
+}
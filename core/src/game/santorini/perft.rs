@@ -1,66 +1,9 @@
 
 use game::santorini::*;
-// use time;
+use time;
 use game::*;
 
 impl StandardBoard {
-    pub fn perft(&self, state: &State, depth: usize, move_stack : &mut MoveStack) -> u64 {
-        if depth == 0 {
-            return 1;
-        }
-
-        let mut n = 0;
-
-        let stack_begin = move_stack.next;
-        self.next_moves(state, move_stack);
-        let stack_end = move_stack.next;
-
-        for idx in stack_begin..stack_end {
-            let mve = move_stack.moves[idx];
-
-            if self.ascension_winning_move(state, mve) {
-                n += 1;
-            } else {
-                let new_state = self.apply(mve, state);
-                n += self.perft(&new_state, depth - 1, move_stack);
-            }
-        }
-        
-        move_stack.next = stack_begin;
-
-        n
-    }
-
-    pub fn perft_heuristic<H>(&self, state: &State, depth: usize, move_stack : &mut MoveStack) -> (u64, i64) where H : Heuristic {
-        if depth == 0 {
-            return (1, H::evaluate(self,state) as i64);
-        }
-
-        let mut n = 0;
-        let mut h = 0;
-
-        let stack_begin = move_stack.next;
-        self.next_moves(state, move_stack);
-        let stack_end = move_stack.next;
-
-        for idx in stack_begin..stack_end {
-            let mve = move_stack.moves[idx];
-
-            if self.ascension_winning_move(state, mve) {
-                n += 1;
-            } else {
-                let new_state = self.apply(mve, state);
-                let (moves, total_h) =  self.perft_heuristic::<H>(&new_state, depth - 1, move_stack);
-                h += total_h;
-                n += moves;
-            }
-        }
-        
-        move_stack.next = stack_begin;
-
-        (n, h)
-    }
-
     pub fn transform_packed2(transform: &SlotTransform, packed:[Packed1; 2]) -> [Packed1; 2] {
         let mut out = [PACKED1_EMPTY; 2];
 
@@ -79,7 +22,32 @@ use game::santorini::tests::test_cases;
 
 pub const PERFT_DEPTH : usize = 4;
 
-fn run_heuristic_count<H>() where H : Heuristic {
+// named positions with their perft counts by depth, from perft_positions.txt
+pub const PERFT_POSITIONS : &str = include_str!("perft_positions.txt");
+
+pub struct PerftPosition {
+    pub name: String,
+    pub fen: String,
+    pub counts: Vec<u64>, // depth 1 first
+}
+
+pub fn perft_positions() -> Vec<PerftPosition> {
+    PERFT_POSITIONS.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields : Vec<&str> = line.split('|').map(|field| field.trim()).collect();
+            assert_eq!(fields.len(), 3, "perft position line {:?} should be name | fen | counts", line);
+            PerftPosition {
+                name: fields[0].into(),
+                fen: fields[1].into(),
+                counts: fields[2].split_whitespace().map(|count| count.parse().expect("perft counts are numbers")).collect(),
+            }
+        })
+        .collect()
+}
+
+fn run_heuristic_count<H>() where H : Heuristic<StandardBoard> {
     let mut move_stack = MoveStack::new();
     let board = StandardBoard::new(ZobristHash::new_unseeded());
 
@@ -88,7 +56,7 @@ fn run_heuristic_count<H>() where H : Heuristic {
 
     let start = time::precise_time_ns();
     for test_case in test_cases(&board) {
-        let (m, h) = board.perft_heuristic::<H>(&test_case.state, PERFT_DEPTH, &mut move_stack);    
+        let (m, h) = perft_heuristic::<StandardBoard, H>(&board, &test_case.state, PERFT_DEPTH, &mut move_stack);    
         moves +=  m;
         h_value += h;
     }
@@ -116,16 +84,66 @@ mod tests {
         let mut move_stack = MoveStack::new();
         let board = StandardBoard::new(ZobristHash::new_unseeded());
 
-        let mut moves = 0;
+        let mut timed = |name: &str, count: &Fn(&State, &mut MoveStack<Move>) -> u64| {
+            let mut moves = 0;
+            let start = time::precise_time_ns();
+            for test_case in test_cases(&board) {
+                moves += count(&test_case.state, &mut move_stack);
+            }
+            let duration = time::precise_time_ns() - start;
+            let as_seconds = (duration as f64) / 1_000_000_000f64;
+
+            let million_moves_per_second = moves as f64 / 1_000_000f64 / as_seconds;
+            println!("=== {} === perft {} moves ({:.2}M/second) in {:.2} seconds", name, moves, million_moves_per_second, as_seconds);
+            moves
+        };
+
+        let made = timed("make/unmake", &|state: &State, move_stack: &mut MoveStack<Move>| ::game::perft::perft(&board, state, PERFT_DEPTH, move_stack));
+        let applied = timed("apply", &|state: &State, move_stack: &mut MoveStack<Move>| perft_apply(&board, state, PERFT_DEPTH, move_stack));
+        assert_eq!(made, applied);
+    }
+
+    // larger counts are left for running by hand
+    const MAX_CHECKED_COUNT : u64 = 5_000_000;
+
+    #[test]
+    fn perft_regression() {
+        let mut move_stack = MoveStack::new();
+
+        for position in perft_positions() {
+            let (state, size) = parse_fen_sized(&position.fen).expect("perft positions are valid fens");
+            let board = StandardBoard::with_size(size, ZobristHash::new_unseeded());
+            // hashes don't know the board size, so nothing is shared between positions
+            let mut cache = PerftCache::new(20);
+
+            for (i, &expected) in position.counts.iter().enumerate().filter(|&(_, &count)| count <= MAX_CHECKED_COUNT) {
+                let depth = i + 1;
+                let count = ::game::perft::perft(&board, &state, depth, &mut move_stack);
+                assert_eq!(count, expected, "{} at depth {}", position.name, depth);
+                assert_eq!(perft_hashed(&board, &state, depth, &mut move_stack, &mut cache), expected, "{} hashed at depth {}", position.name, depth);
+            }
+            println!("{} perft cache hits -> {}", position.name, cache.hits);
+        }
+    }
 
-        let start = time::precise_time_ns();
-        for test_case in test_cases(&board) {
-            moves += board.perft(&test_case.state, PERFT_DEPTH, &mut move_stack);    
+    #[test]
+    fn divide() {
+        use game::santorini::tests::any_in_1;
+
+        let mut move_stack = MoveStack::new();
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = any_in_1(&board, Player(0));
+
+        let divided = perft_divide(&board, &state, 3, &mut move_stack);
+        for &(mve, count) in &divided {
+            println!("{} -> {}", board.move_notation(mve), count);
         }
-        let duration = time::precise_time_ns() - start;
-        let as_seconds = (duration as f64) / 1_000_000_000f64;
-        
-        let million_moves_per_second = moves as f64 / 1_000_000f64 / as_seconds;
-        println!("=== OLD === perft {} moves ({:.2}M/second) in {:.2} seconds", moves, million_moves_per_second, as_seconds);
+
+        let mut moves = Vec::new();
+        board.next_moves(&state, &mut moves);
+        assert_eq!(divided.iter().map(|&(mve, _)| mve).collect::<Vec<_>>(), moves);
+        assert_eq!(divided.iter().map(|&(_, count)| count).sum::<u64>(), ::game::perft::perft(&board, &state, 3, &mut move_stack));
+        assert!(divided.iter().any(|&(mve, count)| board.winning_move(&state, mve) && count == 1));
+        assert!(perft_divide(&board, &state, 0, &mut move_stack).is_empty());
     }
 }
\ No newline at end of file
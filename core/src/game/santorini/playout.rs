@@ -2,18 +2,12 @@
 use game::santorini::*;
 
 pub fn adversarial_playout<F>(board: &StandardBoard, ai_profiles: [AIProfile; 2], mut on_move: F) -> (Player, [EvaluatorInfo; 2]) where F : FnMut(&State, &Move, HeuristicValue) {
-    let mut state = INITIAL_STATE;
-
-    let mut winner : Option<Player> = None;
-
-    let mut infos = [EvaluatorInfo::new(), EvaluatorInfo::new()];
-
-    let mut evaluator_states : [EvState; 2] = [NegaMaxAlphaBetaExp::new_state(), NegaMaxAlphaBetaExp::new_state()];
+    let mut evaluator_states : [EvState<Move>; 2] = [EvState::new(), EvState::new()];
 
     let mut move_count = 0;
+    let mut last = (INITIAL_STATE, None);
 
-    while winner == None {
-        let player_idx = state.to_move.0 as usize;
+    let (_, infos) = adversarial_playout_with(board, &INITIAL_STATE, |player_idx, state| {
         let ai_profile = ai_profiles[player_idx];
 
         let depth : Depth = if move_count < 2 {
@@ -22,38 +16,29 @@ pub fn adversarial_playout<F>(board: &StandardBoard, ai_profiles: [AIProfile; 2]
             ai_profile.depth
         };
 
+        move_count += 1;
+
+        let mut info = EvaluatorInfo::new();
         let mut best_move : Option<(Move, HeuristicValue)> = None;
         for d in 1 ..(depth+1) {
-            let (best_move_for_depth, info) = match ai_profile.heuristic {
-                HeuristicName::Simple => NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut evaluator_states[player_idx], &board, &state, d),
-                HeuristicName::Neighbour => NegaMaxAlphaBetaExp::evaluate_moves::<NeighbourHeuristic>(&mut evaluator_states[player_idx], &board, &state, d),
-                HeuristicName::AdjustedNeighbour => NegaMaxAlphaBetaExp::evaluate_moves::<AdjustedNeighbourHeuristic>(&mut evaluator_states[player_idx], &board, &state, d),
+            let (best_move_for_depth, depth_info) = match ai_profile.heuristic {
+                HeuristicName::Simple => NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut evaluator_states[player_idx], board, state, d),
+                HeuristicName::Neighbour => NegaMaxAlphaBetaExp::evaluate_moves::<NeighbourHeuristic>(&mut evaluator_states[player_idx], board, state, d),
+                HeuristicName::AdjustedNeighbour => NegaMaxAlphaBetaExp::evaluate_moves::<AdjustedNeighbourHeuristic>(&mut evaluator_states[player_idx], board, state, d),
             };
-            infos[player_idx] += info;
+            info += depth_info;
             best_move = best_move_for_depth;
         }
-        
-        winner = if let Some((mve, score)) = best_move {
-            let is_winning_move = board.ascension_winning_move(&state, mve);
-            if is_winning_move {
-                let winner = state.to_move;
-                state = board.apply(mve, &state);
-                on_move(&state, &mve, score);
-                Some(winner)
-            } else {
-                state = board.apply(mve, &state);
-                on_move(&state, &mve, score);
-                None
-            }
-
-        } else {
-            Some(state.next_player())
-        };
 
-        move_count += 1;
-    }
+        (best_move, info)
+    }, |state, mve, score| {
+        last = (state.clone(), Some(*mve));
+        on_move(state, mve, score);
+    });
 
-    (winner.unwrap(), infos)
+    let (final_state, last_move) = last;
+    let winner = board.outcome(&final_state, last_move).winner().expect("santorini games can't be drawn");
+    (winner, infos)
 }
 
 fn sample_principal_variant(depth:Depth) {
@@ -64,7 +49,7 @@ fn sample_principal_variant(depth:Depth) {
     new_state_b.set_building_height(Slot(5), 1);
 
 
-    principal_variant::<MiniMax, SimpleHeightHeuristic>(&mut (), &board, &new_state_b, depth);
+    principal_variant::<StandardBoard, MiniMax, SimpleHeightHeuristic>(&mut (), &board, &new_state_b, depth);
 }
 
 pub fn pairs<T>(items: &Vec<T>) -> Vec<(T, T)> where T : Copy {
@@ -84,7 +69,7 @@ mod tests {
 
     use super::*;
 
-    use HashMap;
+    use aphid::HashMap;
 
     #[test]
     fn heuristic_check() {
@@ -103,8 +88,8 @@ mod tests {
             let mut won_games : HashMap<HeuristicName, u32> = HashMap::default();
 
             for &(a_heuristic, b_heuristic) in &heuristic_pairs {
-                let a_profile = AIProfile { depth: d, heuristic: a_heuristic };
-                let b_profile = AIProfile { depth: d, heuristic: b_heuristic };
+                let a_profile = AIProfile { depth: d, heuristic: a_heuristic, time_limit: None };
+                let b_profile = AIProfile { depth: d, heuristic: b_heuristic, time_limit: None };
 
                 let ai_profiles = [a_profile, b_profile];
                 let (a_first_winner, _)= adversarial_playout(&board, ai_profiles, |_, _, _| { });
@@ -137,8 +122,8 @@ mod tests {
         let mut won_games : HashMap<Depth, u32> = HashMap::default();
 
         for &(a_depth, b_depth) in &depth_pairs {
-            let a_profile = AIProfile { depth: a_depth, heuristic: HeuristicName::AdjustedNeighbour };
-            let b_profile = AIProfile { depth: b_depth, heuristic: HeuristicName::AdjustedNeighbour };
+            let a_profile = AIProfile { depth: a_depth, heuristic: HeuristicName::AdjustedNeighbour, time_limit: None };
+            let b_profile = AIProfile { depth: b_depth, heuristic: HeuristicName::AdjustedNeighbour, time_limit: None };
 
             let ai_profiles = [a_profile, b_profile];
             let (a_first_winner, _)= adversarial_playout(&board, ai_profiles, |_, _, _| { });
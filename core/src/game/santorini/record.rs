@@ -0,0 +1,533 @@
+use game::santorini::*;
+
+use std;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+// pgn style game records, any number of games to a file
+//   [PlayerA "michael"]
+//   [PlayerB "AI"]
+//   [Date "2018.03.14"]
+//   [Result "0-1"]
+//   [Termination "Ascension"]
+//   [AIProfileB "depth=6 heuristic=AdjustedNeighbour time_limit=10000"]
+//
+//   1. c3,a1 b4,d4 2. c3c2b1 {[%eval -40] too slow} d4d3c4 ... 0-1
+// moves are in coordinate notation, a Position tag holds the starting position when it isn't the initial one,
+// and tags we don't know are kept as they are. comments can't contain }
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Termination {
+    Ascension, // climbed to the third level
+    Trapped, // no moves left
+    Resignation,
+    Abandoned,
+    Unterminated, // still in progress, or nobody knows
+}
+
+const TERMINATIONS : [Termination; 5] = [
+    Termination::Ascension,
+    Termination::Trapped,
+    Termination::Resignation,
+    Termination::Abandoned,
+    Termination::Unterminated,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedMove {
+    pub mve: Move,
+    pub eval: Option<HeuristicValue>, // from the point of view of the player making the move
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    pub players: [String; 2],
+    pub ai_profiles: [Option<AIProfile>; 2],
+    pub date: Option<String>, // yyyy.mm.dd
+    pub result: Option<Player>, // the winner, none while it's unfinished
+    pub termination: Termination,
+    pub initial: State,
+    pub moves: Vec<RecordedMove>,
+    pub tags: Vec<(String, String)>, // everything else
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    UnterminatedTag(usize), // line numbers
+    UnterminatedComment(usize),
+    BadTag { line: usize, text: String },
+    BadValue { tag: String, value: String },
+    Position(FenError),
+    Move { line: usize, error: NotationError },
+    ResultMismatch(usize), // the result after the moves doesn't match the tag
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &RecordError::UnterminatedTag(line) => write!(f, "tag on line {} is never closed", line),
+            &RecordError::UnterminatedComment(line) => write!(f, "comment on line {} is never closed", line),
+            &RecordError::BadTag { line, ref text } => write!(f, "line {} {:?} isn't a tag like [Name \"value\"]", line, text),
+            &RecordError::BadValue { ref tag, ref value } => write!(f, "{:?} isn't a valid {}", value, tag),
+            &RecordError::Position(ref error) => write!(f, "bad starting position, {}", error),
+            &RecordError::Move { line, ref error } => write!(f, "line {}, {}", line, error),
+            &RecordError::ResultMismatch(line) => write!(f, "result on line {} doesn't match the Result tag", line),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+impl GameRecord {
+    pub fn new(players: [String; 2], initial: State) -> GameRecord {
+        GameRecord {
+            players: players,
+            ai_profiles: [None, None],
+            date: None,
+            result: None,
+            termination: Termination::Unterminated,
+            initial: initial,
+            moves: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, mve: Move) {
+        self.moves.push(RecordedMove { mve: mve, eval: None, comment: None });
+    }
+
+    pub fn final_state(&self, board: &StandardBoard) -> State {
+        self.moves.iter().fold(self.initial.clone(), |state, recorded| board.apply(recorded.mve, &state))
+    }
+
+    pub fn write(&self, board: &StandardBoard) -> String {
+        let mut out = String::new();
+        let mut tag = |name: &str, value: &str| {
+            out.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+        };
+
+        tag("PlayerA", &self.players[0]);
+        tag("PlayerB", &self.players[1]);
+        tag("Date", self.date.as_ref().map(|d| d.as_str()).unwrap_or("????.??.??"));
+        tag("Result", result_text(self.result));
+        tag("Termination", &format!("{:?}", self.termination));
+        for (i, name) in ["AIProfileA", "AIProfileB"].iter().enumerate() {
+            if let Some(profile) = self.ai_profiles[i] {
+                tag(*name, &profile_text(&profile));
+            }
+        }
+        if self.initial != INITIAL_STATE || board.size != DEFAULT_BOARD_SIZE {
+            tag("Position", &board.fen(&self.initial));
+        }
+        for &(ref name, ref value) in &self.tags {
+            tag(name, value);
+        }
+        out.push('\n');
+
+        let mut words : Vec<String> = Vec::new();
+        for (idx, recorded) in self.moves.iter().enumerate() {
+            if idx % 2 == 0 {
+                words.push(format!("{}.", idx / 2 + 1));
+            }
+            words.push(board.move_notation(recorded.mve));
+
+            let mut annotation : Vec<String> = Vec::new();
+            if let Some(eval) = recorded.eval {
+                annotation.push(format!("[%eval {}]", eval));
+            }
+            if let Some(ref comment) = recorded.comment {
+                annotation.push(comment.replace('}', ")"));
+            }
+            if !annotation.is_empty() {
+                words.push(format!("{{{}}}", annotation.join(" ")));
+            }
+        }
+        words.push(result_text(self.result).into());
+
+        // wrapped like pgn, 80 columns
+        let mut line = String::new();
+        for word in words {
+            if !line.is_empty() && line.len() + word.len() + 1 > 80 {
+                out.push_str(&line);
+                out.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        out.push_str(&line);
+        out.push('\n');
+        out
+    }
+}
+
+fn result_text(result: Option<Player>) -> &'static str {
+    match result {
+        Some(Player(0)) => "1-0",
+        Some(_) => "0-1",
+        None => "*",
+    }
+}
+
+fn parse_result(text: &str) -> Option<Option<Player>> {
+    match text {
+        "1-0" => Some(Some(Player(0))),
+        "0-1" => Some(Some(Player(1))),
+        "*" => Some(None),
+        _ => None,
+    }
+}
+
+pub fn profile_text(profile: &AIProfile) -> String {
+    let mut out = format!("depth={} heuristic={:?}", profile.depth, profile.heuristic);
+    if let Some(time_limit) = profile.time_limit {
+        out.push_str(&format!(" time_limit={}", time_limit));
+    }
+    out
+}
+
+pub fn parse_profile(text: &str) -> Option<AIProfile> {
+    let mut depth = None;
+    let mut heuristic = None;
+    let mut time_limit = None;
+
+    for pair in text.split_whitespace() {
+        let mut parts = pair.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("depth"), Some(value)) => depth = Some(value.parse().ok()?),
+            (Some("heuristic"), Some(value)) => heuristic = Some(match value {
+                "Simple" => HeuristicName::Simple,
+                "Neighbour" => HeuristicName::Neighbour,
+                "AdjustedNeighbour" => HeuristicName::AdjustedNeighbour,
+                _ => return None,
+            }),
+            (Some("time_limit"), Some(value)) => time_limit = Some(value.parse().ok()?),
+            _ => return None,
+        }
+    }
+
+    Some(AIProfile { depth: depth?, heuristic: heuristic?, time_limit: time_limit })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    MoveNumber,
+    Result(Option<Player>),
+    Word(String),
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    pending: Option<(usize, Token)>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Lexer<'a> {
+        Lexer { chars: text.chars().peekable(), line: 1, pending: None }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn push_back(&mut self, token: (usize, Token)) {
+        self.pending = Some(token);
+    }
+
+    fn next_token(&mut self) -> Result<Option<(usize, Token)>, RecordError> {
+        if let Some(token) = self.pending.take() {
+            return Ok(Some(token));
+        }
+
+        loop {
+            match self.chars.peek().cloned() {
+                Some(c) if c.is_whitespace() => { self.bump(); },
+                Some(';') => { // rest of line comment, dropped
+                    while self.bump().map(|c| c != '\n').unwrap_or(false) {}
+                },
+                _ => break,
+            }
+        }
+
+        let line = self.line;
+        let token = match self.bump() {
+            None => return Ok(None),
+            Some('[') => self.tag(line)?,
+            Some('{') => {
+                let mut text = String::new();
+                loop {
+                    match self.bump() {
+                        Some('}') => break,
+                        Some(c) => text.push(c),
+                        None => return Err(RecordError::UnterminatedComment(line)),
+                    }
+                }
+                Token::Comment(text)
+            },
+            Some(first) => {
+                let mut word = first.to_string();
+                while let Some(c) = self.chars.peek().cloned() {
+                    if c.is_whitespace() || c == '{' || c == '[' {
+                        break;
+                    }
+                    word.push(c);
+                    self.bump();
+                }
+                match parse_result(&word) {
+                    Some(result) => Token::Result(result),
+                    None => {
+                        // 12. or 12... and possibly a move straight after it
+                        let rest = word.trim_start_matches(|c: char| c.is_ascii_digit());
+                        if rest.len() < word.len() && rest.starts_with('.') {
+                            let rest = rest.trim_start_matches('.');
+                            if rest.is_empty() { Token::MoveNumber } else { Token::Word(rest.into()) }
+                        } else {
+                            Token::Word(word)
+                        }
+                    },
+                }
+            },
+        };
+        Ok(Some((line, token)))
+    }
+
+    fn tag(&mut self, line: usize) -> Result<Token, RecordError> {
+        let mut raw = String::from("[");
+        let mut value = String::new();
+        let mut in_value = false;
+        let mut quotes = 0;
+
+        loop {
+            let c = self.bump().ok_or(RecordError::UnterminatedTag(line))?;
+            raw.push(c);
+            if in_value {
+                match c {
+                    '\\' => {
+                        let escaped = self.bump().ok_or(RecordError::UnterminatedTag(line))?;
+                        raw.push(escaped);
+                        value.push(escaped);
+                    },
+                    '"' => in_value = false,
+                    _ => value.push(c),
+                }
+            } else if c == '"' {
+                in_value = true;
+                quotes += 1;
+            } else if c == ']' {
+                break;
+            } else if c == '\n' {
+                return Err(RecordError::UnterminatedTag(line));
+            }
+        }
+
+        let name : String = raw[1..].chars().take_while(|c| !c.is_whitespace() && *c != '"').collect();
+        let well_formed = !name.is_empty() && quotes == 1 && raw.trim_end_matches(|c: char| c == ']' || c.is_whitespace()).ends_with('"');
+        if !well_formed {
+            return Err(RecordError::BadTag { line: line, text: raw });
+        }
+        Ok(Token::Tag(name, value))
+    }
+}
+
+fn split_annotation(text: &str) -> (Option<HeuristicValue>, Option<String>) {
+    let text = text.trim();
+    let (eval, rest) = if text.starts_with("[%eval ") {
+        match text.find(']') {
+            Some(end) => match text["[%eval ".len()..end].trim().parse() {
+                Ok(eval) => (Some(eval), text[(end + 1)..].trim()),
+                Err(_) => (None, text),
+            },
+            None => (None, text),
+        }
+    } else {
+        (None, text)
+    };
+    (eval, if rest.is_empty() { None } else { Some(rest.into()) })
+}
+
+fn read_record(board: &StandardBoard, lexer: &mut Lexer) -> Result<Option<GameRecord>, RecordError> {
+    let mut record = GameRecord::new(["?".into(), "?".into()], INITIAL_STATE);
+    let mut result_tag = false;
+    let mut any = false;
+
+    loop {
+        match lexer.next_token()? {
+            Some((_, Token::Tag(name, value))) => {
+                any = true;
+                let bad_value = || RecordError::BadValue { tag: name.clone(), value: value.clone() };
+                match name.as_str() {
+                    "PlayerA" => record.players[0] = value.clone(),
+                    "PlayerB" => record.players[1] = value.clone(),
+                    "Date" => record.date = if value == "????.??.??" { None } else { Some(value.clone()) },
+                    "Result" => {
+                        record.result = parse_result(&value).ok_or_else(bad_value)?;
+                        result_tag = true;
+                    },
+                    "Termination" => {
+                        record.termination = *TERMINATIONS.iter().find(|t| format!("{:?}", t) == value).ok_or_else(bad_value)?;
+                    },
+                    "AIProfileA" => record.ai_profiles[0] = Some(parse_profile(&value).ok_or_else(bad_value)?),
+                    "AIProfileB" => record.ai_profiles[1] = Some(parse_profile(&value).ok_or_else(bad_value)?),
+                    "Position" => record.initial = board.parse_fen(&value).map_err(RecordError::Position)?,
+                    _ => record.tags.push((name.clone(), value.clone())),
+                }
+            },
+            Some(token) => {
+                lexer.push_back(token);
+                break;
+            },
+            None => break,
+        }
+    }
+
+    let mut state = record.initial.clone();
+    loop {
+        match lexer.next_token()? {
+            None => break,
+            Some((line, Token::Tag(name, value))) => {
+                // the next game, this one had no result
+                lexer.push_back((line, Token::Tag(name, value)));
+                break;
+            },
+            Some((_, Token::MoveNumber)) => (),
+            Some((_, Token::Comment(text))) => {
+                // comments before the first move have nowhere to go
+                if let Some(recorded) = record.moves.last_mut() {
+                    let (eval, comment) = split_annotation(&text);
+                    recorded.eval = recorded.eval.or(eval);
+                    recorded.comment = match (recorded.comment.take(), comment) {
+                        (Some(a), Some(b)) => Some(format!("{} {}", a, b)),
+                        (a, b) => a.or(b),
+                    };
+                }
+            },
+            Some((line, Token::Result(result))) => {
+                any = true;
+                if result_tag && result != record.result {
+                    return Err(RecordError::ResultMismatch(line));
+                }
+                record.result = result;
+                break;
+            },
+            Some((line, Token::Word(text))) => {
+                any = true;
+                let mve = board.parse_legal_move(&state, &text).map_err(|error| RecordError::Move { line: line, error: error })?;
+                state = board.apply(mve, &state);
+                record.push(mve);
+            },
+        }
+    }
+
+    Ok(if any { Some(record) } else { None })
+}
+
+pub fn read_records(board: &StandardBoard, text: &str) -> Result<Vec<GameRecord>, RecordError> {
+    let mut lexer = Lexer::new(text);
+    let mut records = Vec::new();
+    while let Some(record) = read_record(board, &mut lexer)? {
+        records.push(record);
+    }
+    Ok(records)
+}
+
+pub fn write_records(board: &StandardBoard, records: &[GameRecord]) -> String {
+    let games : Vec<String> = records.iter().map(|record| record.write(board)).collect();
+    games.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(board: &StandardBoard) -> GameRecord {
+        let mut record = GameRecord::new(["michael".into(), "AI".into()], INITIAL_STATE);
+        record.date = Some("2018.03.14".into());
+        record.ai_profiles[1] = Some(AIProfile { depth: 6, heuristic: HeuristicName::AdjustedNeighbour, time_limit: Some(10_000) });
+        record.tags.push(("Event".into(), "the \"big\" one".into()));
+
+        let mut state = INITIAL_STATE;
+        for text in &["c3,a1", "b4,d4", "c3c2b1", "d4d3c4"] {
+            let mve = board.parse_legal_move(&state, text).unwrap();
+            state = board.apply(mve, &state);
+            record.push(mve);
+        }
+        record.moves[2].eval = Some(-40);
+        record.moves[2].comment = Some("too slow".into());
+        record.moves[3].comment = Some("threat".into());
+        record
+    }
+
+    #[test]
+    fn writes() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let text = sample(&board).write(&board);
+        assert!(text.starts_with("[PlayerA \"michael\"]\n[PlayerB \"AI\"]\n[Date \"2018.03.14\"]\n[Result \"*\"]\n"), "{}", text);
+        assert!(text.contains("[AIProfileB \"depth=6 heuristic=AdjustedNeighbour time_limit=10000\"]"), "{}", text);
+        assert!(text.contains("[Event \"the \\\"big\\\" one\"]"), "{}", text);
+        assert!(text.contains("\n\n1. c3,a1 b4,d4 2. c3c2b1 {[%eval -40] too slow} d4d3c4 {threat} *\n"), "{}", text);
+    }
+
+    #[test]
+    fn round_trips() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let unfinished = sample(&board);
+
+        let mut finished = GameRecord::new(["AI".into(), "AI".into()], a_in_1_start(&board));
+        finished.push(Move::Move { from: Slot(1), to: Slot(2), build: Slot(3) });
+        finished.result = Some(Player(0));
+        finished.termination = Termination::Ascension;
+
+        let records = vec![unfinished, finished];
+        let text = write_records(&board, &records);
+        assert!(text.contains("[Position \"0A2A300/00000/00000/00000/0000B0B A\"]"), "{}", text);
+        assert_eq!(read_records(&board, &text), Ok(records));
+    }
+
+    fn a_in_1_start(board: &StandardBoard) -> State {
+        board.parse_fen("0A2A300/00000/00000/00000/0000B0B A").unwrap()
+    }
+
+    #[test]
+    fn reads_loosely() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let text = "
+            ; a game with no tags, numbers stuck to moves, and no result
+            1.c3,a1 b4,d4 {opening} {[%eval 3]}
+            2.c3c2b1
+            [PlayerA \"next\"]
+            1. c3,a1 1-0
+        ";
+        let records = read_records(&board, text).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].moves.len(), 3);
+        assert_eq!(records[0].moves[1].comment, Some("opening".into()));
+        assert_eq!(records[0].moves[1].eval, Some(3));
+        assert_eq!(records[0].result, None);
+        assert_eq!(records[1].players[0], "next");
+        assert_eq!(records[1].result, Some(Player(0)));
+        assert_eq!(read_records(&board, "  \n "), Ok(vec![]));
+    }
+
+    #[test]
+    fn errors() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        assert_eq!(read_records(&board, "[PlayerA \"x"), Err(RecordError::UnterminatedTag(1)));
+        assert_eq!(read_records(&board, "\n1. c3,a1 {oops"), Err(RecordError::UnterminatedComment(2)));
+        assert_eq!(read_records(&board, "[PlayerA x]"), Err(RecordError::BadTag { line: 1, text: "[PlayerA x]".into() }));
+        assert_eq!(read_records(&board, "[Result \"2-0\"]"), Err(RecordError::BadValue { tag: "Result".into(), value: "2-0".into() }));
+        assert_eq!(read_records(&board, "[Position \"00000 A\"]"), Err(RecordError::Position(FenError::BoardSize(1))));
+        assert_eq!(read_records(&board, "1. c3,a1\n2. a1b2c3"), Err(RecordError::Move { line: 2, error: NotationError::Illegal(Move::Move { from: Slot(0), to: Slot(6), build: Slot(12) }, 5) }));
+        assert_eq!(read_records(&board, "[Result \"1-0\"]\n1. c3,a1 0-1"), Err(RecordError::ResultMismatch(2)));
+    }
+}
@@ -12,6 +12,8 @@ pub const INITIAL_STATE : State = State {
     building_minor: PACKED1_EMPTY,
     domes: PACKED1_EMPTY,
     to_move: Player(0),
+    gods: [God::Mortal; 2],
+    athena_blocks: false,
 };
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
@@ -21,9 +23,21 @@ pub struct State {
     pub building_minor : Packed1,
     pub domes : Packed1,
     pub to_move : Player,
+    pub gods : [God; 2],
+    pub athena_blocks : bool, // athena moved up last turn, so the player to move can't
 }
 
 impl State {
+    pub fn with_gods(gods: [God; 2]) -> State {
+        let mut state = INITIAL_STATE;
+        state.gods = gods;
+        state
+    }
+
+    pub fn god(&self) -> God {
+        self.gods[self.to_move.0 as usize]
+    }
+
     pub fn building_map(&self) -> [Packed1; 4] {
         [
             !self.building_major & !self.building_minor,
@@ -58,7 +72,7 @@ impl State {
 
     pub fn without_builder_at(&self, slot:Slot) -> State {
         let mut new_state = self.clone();
-        let mask = (1 << slot.0) ^ ALL_MASK_32;
+        let mask = (1 << slot.0) ^ ALL_MASK_64;
         new_state.builders[0].0 &= mask;
         new_state.builders[1].0 &= mask;
         new_state
@@ -83,5 +97,15 @@ impl State {
             self.set_building_height(slot, height + 1);
         }
     }
+
+    // reverses build_at, a dome is always the last thing built on a slot
+    pub fn unbuild_at(&mut self, slot:Slot) {
+        if self.domes.get(slot) == 1 {
+            self.domes.toggle(slot);
+        } else {
+            let height = self.get_building_height(slot);
+            self.set_building_height(slot, height - 1);
+        }
+    }
 }
 
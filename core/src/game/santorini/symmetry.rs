@@ -0,0 +1,174 @@
+use game::santorini::*;
+
+// the board's 8 symmetries give the same game, so searches can share transposition entries between them.
+// symmetry 0 is the identity, symmetry i is transforms[i - 1]. gods and athena don't care about orientation
+
+impl Move {
+    pub fn transform(&self, transform: &SlotTransform) -> Move {
+        let t = |slot: Slot| transform.slots[slot.0 as usize];
+        match *self {
+            Move::PlaceBuilders { a, b } => Move::PlaceBuilders { a: t(a), b: t(b) },
+            Move::Move { from, to, build } => Move::Move { from: t(from), to: t(to), build: t(build) },
+            Move::DoubleBuild { from, to, build, second } => Move::DoubleBuild { from: t(from), to: t(to), build: t(build), second: t(second) },
+            Move::Swap { from, to, build } => Move::Swap { from: t(from), to: t(to), build: t(build) },
+            Move::Push { from, to, build } => Move::Push { from: t(from), to: t(to), build: t(build) },
+            Move::Dome { from, to, build } => Move::Dome { from: t(from), to: t(to), build: t(build) },
+            Move::BuildFirst { first, from, to, build } => Move::BuildFirst { first: t(first), from: t(from), to: t(to), build: t(build) },
+            Move::Travel { from, to, other_from, other_to, build } => {
+                // the generator puts the lower numbered builder first
+                let (from, to, other_from, other_to) = if t(from).0 < t(other_from).0 {
+                    (t(from), t(to), t(other_from), t(other_to))
+                } else {
+                    (t(other_from), t(other_to), t(from), t(to))
+                };
+                Move::Travel { from: from, to: to, other_from: other_from, other_to: other_to, build: t(build) }
+            },
+        }
+    }
+}
+
+impl StandardBoard {
+    // the smallest hash over all 8 orientations, built in one pass rather than transforming the state 7 times
+    pub fn canonical_hash(&self, state: &State) -> (StateHash, Symmetry) {
+        let mut hashes = [STATE_HASH_ZERO; 8];
+
+        for i in 0..PLAYERS {
+            for bl in state.builders[i].iter() {
+                hashes[0] = hashes[0] ^ self.hash.builders[i][bl.0 as usize];
+                for (t, transform) in self.transforms.iter().enumerate() {
+                    hashes[t + 1] = hashes[t + 1] ^ self.hash.builders[i][transform.slots[bl.0 as usize].0 as usize];
+                }
+            }
+        }
+
+        for &sl in &self.slots {
+            // height 0 has its own random value in the table too, hash puts it in like the rest
+            let height = state.hash_height(sl);
+            hashes[0] = hashes[0] ^ self.hash.buildings[sl.0 as usize][height];
+            for (t, transform) in self.transforms.iter().enumerate() {
+                hashes[t + 1] = hashes[t + 1] ^ self.hash.buildings[transform.slots[sl.0 as usize].0 as usize][height];
+            }
+        }
+
+        let mut best = 0;
+        for t in 1..hashes.len() {
+            if hashes[t].0 < hashes[best].0 {
+                best = t;
+            }
+        }
+
+        let mut hash = hashes[best] ^ self.hash.to_move[state.to_move.0 as usize];
+        if state.athena_blocks {
+            hash = hash ^ self.hash.athena_blocks;
+        }
+
+        (hash, best as Symmetry)
+    }
+
+    // the state canonical_hash hashed
+    pub fn canonical_state(&self, state: &State, symmetry: Symmetry) -> State {
+        match symmetry {
+            0 => state.clone(),
+            s => self.transform_state(state, &self.transforms[s as usize - 1]),
+        }
+    }
+
+    pub fn to_canonical_move(&self, mve: Move, symmetry: Symmetry) -> Move {
+        match symmetry {
+            0 => mve,
+            s => mve.transform(&self.transforms[s as usize - 1]),
+        }
+    }
+
+    pub fn from_canonical_move(&self, mve: Move, symmetry: Symmetry) -> Move {
+        match symmetry {
+            0 => mve,
+            s => mve.transform(&self.inverse_transforms[s as usize - 1]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::*;
+
+    #[test]
+    fn symmetric_states_share_a_hash() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        for state in vec![a_in_1(&board, Player(0)), b_blockable(&board, Player(1)), any_trap_in_1(&board, Player(0))] {
+            let (hash, symmetry) = board.canonical_hash(&state);
+            assert_eq!(board.hash(&board.canonical_state(&state, symmetry)), hash);
+
+            let mut permuted = Vec::new();
+            board.permute(&state, &mut permuted);
+            for other in &permuted {
+                assert_eq!(board.canonical_hash(other).0, hash);
+            }
+        }
+
+        // the side to move still counts
+        let a = a_in_1(&board, Player(0));
+        let b = a_in_1(&board, Player(1));
+        assert!(board.canonical_hash(&a).0 != board.canonical_hash(&b).0);
+    }
+
+    #[test]
+    fn moves_round_trip() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = b_blockable(&board, Player(0));
+        state.gods = [God::Prometheus, God::Minotaur];
+
+        let mut permuted = vec![state.clone()];
+        board.permute(&state, &mut permuted);
+
+        for other in &permuted {
+            let (hash, symmetry) = board.canonical_hash(other);
+            let canonical = board.canonical_state(other, symmetry);
+
+            let mut moves = Vec::new();
+            board.next_moves(other, &mut moves);
+            let mut canonical_moves = Vec::new();
+            board.next_moves(&canonical, &mut canonical_moves);
+
+            for &mve in &moves {
+                let canonical_move = board.to_canonical_move(mve, symmetry);
+                assert!(canonical_moves.contains(&canonical_move), "{:?} -> {:?}", mve, canonical_move);
+                assert_eq!(board.from_canonical_move(canonical_move, symmetry), mve);
+
+                // the same position comes out either way round
+                let after = board.canonical_hash(&board.apply(mve, other)).0;
+                assert_eq!(after, board.canonical_hash(&board.apply(canonical_move, &canonical)).0);
+            }
+            assert_eq!(board.canonical_hash(&canonical).0, hash);
+        }
+    }
+
+    #[test]
+    fn search_shares_entries() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = b_blockable(&board, Player(1));
+        let depth = 3;
+
+        let mut plain = EvState::new();
+        let (expected, _) = NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut plain, &board, &state, depth);
+        let expected_value = expected.expect("b has moves").1;
+
+        let mut ev_state = EvState::canonical();
+        let (best, _) = NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut ev_state, &board, &state, depth);
+        assert_eq!(best.map(|(_, v)| v), Some(expected_value));
+
+        // every orientation is answered straight from the table, with a move that's legal there
+        let (stored, _) = NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut ev_state, &board, &state, depth);
+        let stored_value = stored.expect("b has moves").1;
+        let mut permuted = Vec::new();
+        board.permute(&state, &mut permuted);
+        for other in &permuted {
+            let (best, info) = NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut ev_state, &board, other, depth);
+            let (mve, value) = best.expect("b has moves");
+            assert_eq!(info.tt_valid, 1);
+            assert_eq!(value, stored_value);
+            assert_eq!(board.validate_move(other, mve), Ok(()));
+        }
+    }
+}
@@ -181,7 +181,7 @@ pub fn focus_test_cases(board:&StandardBoard) -> Vec<TestCase> {
     ]
 }
 
-pub fn evaluate_state<E, H>(evaluator_state: &mut E::EvaluatorState, board:&StandardBoard, state:&State, max_depth: Depth) -> (Vec<HeuristicValue>, EvaluatorInfo) where E: Evaluator, H:Heuristic {
+pub fn evaluate_state<E, H>(evaluator_state: &mut E::EvaluatorState, board:&StandardBoard, state:&State, max_depth: Depth) -> (Vec<HeuristicValue>, EvaluatorInfo) where E: Evaluator<StandardBoard>, H:Heuristic<StandardBoard> {
     let mut info = EvaluatorInfo::new();
     let heuristic_values : Vec<_> = (1..(max_depth+1)).flat_map(|depth| {
         E::new_search(evaluator_state);
@@ -192,7 +192,7 @@ pub fn evaluate_state<E, H>(evaluator_state: &mut E::EvaluatorState, board:&Stan
     (heuristic_values,info)
 }
 
-pub fn test_all_cases<E, H>() -> (u32, EvaluatorInfo) where E: Evaluator, H: Heuristic {
+pub fn test_all_cases<E, H>() -> (u32, EvaluatorInfo) where E: Evaluator<StandardBoard>, H: Heuristic<StandardBoard> {
     println!("==== Testing {} all cases =====", E::name());
     let mut info = EvaluatorInfo::new();
     let board = StandardBoard::new(ZobristHash::new_unseeded());
@@ -210,6 +210,8 @@ pub fn test_all_cases<E, H>() -> (u32, EvaluatorInfo) where E: Evaluator, H: Heu
 
             let (scores, new_info) = evaluate_state::<E, H>(&mut evaluator_state, &board, &case.state, case.scores.len() as Depth);
             info += new_info.clone();
+            // the cases only say who wins, how soon is up to the evaluator
+            let scores : Vec<_> = scores.into_iter().map(without_distance).collect();
 
             if scores != case.scores {
                 // playout::<E, H>(&board, &case.state, case.scores.len() as u8);
@@ -228,7 +230,7 @@ pub fn test_all_cases<E, H>() -> (u32, EvaluatorInfo) where E: Evaluator, H: Heu
     (error_cases, info)
 }
 
-pub fn time_test_cases<E, H>() -> bool where E: Evaluator, H: Heuristic {
+pub fn time_test_cases<E, H>() -> bool where E: Evaluator<StandardBoard>, H: Heuristic<StandardBoard> {
 
     let (v, info) = test_all_cases::<E, H>();
 
@@ -243,7 +245,7 @@ pub fn time_test_cases<E, H>() -> bool where E: Evaluator, H: Heuristic {
     v == 0
 }
 
-pub fn time_exploration<E, H>(depth:Depth) -> EvaluatorInfo where E: Evaluator, H: Heuristic  {
+pub fn time_exploration<E, H>(depth:Depth) -> EvaluatorInfo where E: Evaluator<StandardBoard>, H: Heuristic<StandardBoard>  {
     let mut info = EvaluatorInfo::new();
     let board = StandardBoard::new(ZobristHash::new_unseeded());
     let cases = test_cases(&board);
@@ -309,7 +311,7 @@ mod tests {
         println!("starting negamax_ab_exp adversarial playout");
 
 
-        let ai_profile = AIProfile { depth: depth, heuristic: HeuristicName::AdjustedNeighbour };
+        let ai_profile = AIProfile { depth: depth, heuristic: HeuristicName::AdjustedNeighbour, time_limit: None };
 
         let (winner, infos) = adversarial_playout(&board, [ai_profile; 2], |state, mve, score| {
             move_number += 1;
@@ -336,7 +338,7 @@ mod tests {
         state = board.apply(Move::PlaceBuilders { a: Slot(1), b: Slot(11) }, &state);
         state = board.apply(Move::PlaceBuilders { a: Slot(2), b: Slot(12) }, &state);
 
-        let mut ev_state = NegaMaxAlphaBetaExp::new_state();
+        let mut ev_state = EvState::new();
         let depth = 4;
 
         let (res_a, a_info) = NegaMaxAlphaBetaExp::evaluate_moves::<NeighbourHeuristic>(&mut ev_state, &board, &state, depth);
@@ -349,6 +351,120 @@ mod tests {
         
     }
 
+    #[test]
+    fn principal_variation() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+
+        let state = distant_state(&board);
+        let mut ev_state = EvState::new();
+        let (best_move, _) = evaluate_state_to::<SimpleHeightHeuristic>(&mut ev_state, &board, &state, 4);
+        let line = NegaMaxAlphaBetaExp::principal_variation(&ev_state, &board, &state, 4);
+        println!("line -> {:?}", line);
+        assert!(!line.is_empty() && line.len() <= 4);
+        assert_eq!(Some(line[0]), best_move);
+
+        let mut replayed = state.clone();
+        for &(mve, _) in &line {
+            assert!(board.legal_move(&replayed, mve), "{:?} in {:?}", mve, line);
+            replayed = board.apply(mve, &replayed);
+        }
+
+        // every score along a forced win is the same win 3 plies from the root, from player 0's side
+        let state = a_in_2(&board, Player(0));
+        let mut ev_state = EvState::new();
+        evaluate_state_to::<SimpleHeightHeuristic>(&mut ev_state, &board, &state, 3);
+        let line = NegaMaxAlphaBetaExp::principal_variation(&ev_state, &board, &state, 6);
+        assert_eq!(line.len(), 3, "{:?}", line);
+        assert!(line.iter().all(|&(_, score)| score == victory_in(3)), "{:?}", line);
+        let before_win = line[..2].iter().fold(state.clone(), |s, &(mve, _)| board.apply(mve, &s));
+        assert!(board.winning_move(&before_win, line[2].0));
+
+        // nothing searched, no line
+        assert_eq!(NegaMaxAlphaBetaExp::principal_variation(&EvState::new(), &board, &state, 6), vec![]);
+    }
+
+    fn evaluate_state_to<H>(ev_state: &mut EvState<Move>, board: &StandardBoard, state: &State, max_depth: Depth) -> (Option<(Move, HeuristicValue)>, EvaluatorInfo) where H: Heuristic<StandardBoard> {
+        let mut result = (None, EvaluatorInfo::new());
+        for depth in 1..(max_depth + 1) {
+            result = NegaMaxAlphaBetaExp::evaluate_moves::<H>(ev_state, board, state, depth);
+        }
+        result
+    }
+
+    #[test]
+    fn threat_extensions() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+
+        // nothing stops these, but at depth 1 the win is past the horizon without the extension
+        for &(ref state, winner) in &[(a_in_1(&board, Player(1)), victory_in(2)), (b_in_1(&board, Player(0)), -victory_in(2))] {
+            let (plain, _) = NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut EvState::new(), &board, state, 1);
+            assert!(plain.unwrap().1 != winner);
+
+            let (extended, _) = NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut EvState::extending_threats(), &board, state, 1);
+            assert_eq!(extended.unwrap().1, winner);
+
+            let mut pvs_state = PvsState::new();
+            pvs_state.search.threat_extensions = true;
+            let (extended, _) = NegaMaxPVS::evaluate_moves::<SimpleHeightHeuristic>(&mut pvs_state, &board, state, 1);
+            assert_eq!(extended.unwrap().1, winner);
+        }
+
+        // these can be stopped, and at depth 1 they are
+        for &(ref state, threatening) in &[(a_blockable(&board, Player(1)), Player(0)), (b_blockable(&board, Player(0)), Player(1))] {
+            assert!(board.ascension_threat(state, threatening));
+            let (best_move, info) = NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut EvState::extending_threats(), &board, state, 1);
+            let (mve, score) = best_move.unwrap();
+            println!("{:?} blocks with {:?} ({}) -> {:?}", state.to_move, mve, score, info);
+            assert!(!decided(score));
+            assert!(!board.ascension_threat(&board.apply(mve, state), threatening), "{:?}", mve);
+        }
+
+        // no one is close enough to level 3 for anything to extend
+        for state in &[any_trap_in_1(&board, Player(0)), any_trap_in_1(&board, Player(1))] {
+            for depth in 1..3 {
+                let (plain, plain_info) = NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut EvState::new(), &board, state, depth);
+                let (extended, extended_info) = NegaMaxAlphaBetaExp::evaluate_moves::<SimpleHeightHeuristic>(&mut EvState::extending_threats(), &board, state, depth);
+                assert_eq!(plain.map(|(_, score)| score), extended.map(|(_, score)| score));
+                assert_eq!(plain_info.move_count, extended_info.move_count);
+            }
+        }
+    }
+
+    #[test]
+    fn wins_soonest() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+
+        // (state, depth, plies until player 0 wins or, negative, player 1 does)
+        let cases = vec![
+            (a_in_1(&board, Player(0)), 1, victory_in(1)),
+            (a_in_1(&board, Player(0)), 3, victory_in(1)),
+            (a_in_1(&board, Player(1)), 2, victory_in(2)),
+            (a_in_2(&board, Player(0)), 3, victory_in(3)),
+            (a_in_2(&board, Player(0)), 5, victory_in(3)),
+            (a_in_2(&board, Player(1)), 4, victory_in(4)),
+            (b_in_2(&board, Player(1)), 4, -victory_in(3)),
+        ];
+
+        for &(ref state, depth, expected) in &cases {
+            let mut ev_state = EvState::new();
+            let (best_move, _) = evaluate_state_to::<SimpleHeightHeuristic>(&mut ev_state, &board, state, depth);
+            assert_eq!(best_move.map(|(_, score)| score), Some(expected), "{:?} to move at depth {}", state.to_move, depth);
+
+            let mut pvs_state = PvsState::new();
+            let mut best_move = None;
+            for d in 1..(depth + 1) {
+                best_move = NegaMaxPVS::evaluate_moves::<SimpleHeightHeuristic>(&mut pvs_state, &board, state, d).0;
+            }
+            assert_eq!(best_move.map(|(_, score)| score), Some(expected), "pvs, {:?} to move at depth {}", state.to_move, depth);
+        }
+
+        assert_eq!(plies_to_end(victory_in(3)), Some(3));
+        assert_eq!(plies_to_end(-victory_in(4)), Some(4));
+        assert_eq!(plies_to_end(12), None);
+        assert_eq!(plies_to_end(BEST), None);
+        assert!(decided(BEST) && decided(-victory_in(4)) && !decided(12));
+    }
+
     #[test]
     fn minimax_vs_negamax() {
         let board = StandardBoard::new(ZobristHash::new_unseeded());
@@ -372,6 +488,11 @@ mod tests {
         assert!(time_test_cases::<NegaMaxAlphaBetaExp, SimpleHeightHeuristic>());
     }
 
+    #[test]
+    fn negamax_pvs() {
+        assert!(time_test_cases::<NegaMaxPVS, SimpleHeightHeuristic>());
+    }
+
     #[test]
     fn minimax() {
         assert!(time_test_cases::<MiniMax, SimpleHeightHeuristic>());
@@ -393,6 +514,17 @@ mod tests {
            time_exploration::<NegaMax, NeighbourHeuristic>(4);
            time_exploration::<NegaMaxAlphaBeta, NeighbourHeuristic>(4);
            time_exploration::<NegaMaxAlphaBetaExp, NeighbourHeuristic>( 4);
+           time_exploration::<NegaMaxPVS, NeighbourHeuristic>(4);
+        }
+
+        // the same test cases deeper, where the null windows should start to pay off
+        #[test]
+        fn pvs_against_exp() {
+            let depth = 5;
+            let exp = time_exploration::<NegaMaxAlphaBetaExp, AdjustedNeighbourHeuristic>(depth);
+            let pvs = time_exploration::<NegaMaxPVS, AdjustedNeighbourHeuristic>(depth);
+            println!("depth {} exp {} moves in {:.2}s, pvs {} moves in {:.2}s ({:.2}x the moves)", depth,
+                exp.move_count, exp.time, pvs.move_count, pvs.time, pvs.move_count as f64 / exp.move_count as f64);
         }
     }
 }
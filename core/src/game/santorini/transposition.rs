@@ -1,160 +1,12 @@
 use game::santorini::*;
 
-use std::mem;
-
-#[derive(Eq, Copy, PartialEq, Clone, Debug)]
-pub enum EntryType {
-    Exact,
-    Lower,
-    Upper,
-}
-
-#[derive(Eq, Copy, PartialEq, Clone, Debug)]
-pub struct TranspositionEntry {
-    // pub state: State,
-    pub hash: StateHash, // 8 bytes
-    pub value: HeuristicValue, // 2 bytes
-    pub entry_type: EntryType, // 1 byte
-    pub depth: i8, // 1 byte
-    pub generation: Generation, // 1 byte
-    pub best_move: Option<Move>, // 5 bytes
-    
-    // move in theory could be reduced to 2 bytes + flag -> 3 bytes  + optionality == 3-4 ish bytes ...
-}
-
-impl TranspositionEntry {
-    pub fn value(&self, current_generation: Generation) -> i8 { // i feel this numerical type isn't correct
-        self.depth - (current_generation - self.generation) as i8 * 2
-    }
-}
-
-pub const NULL_ENTRY : TranspositionEntry = TranspositionEntry {
-    // state: INITIAL_STATE,
-    hash: StateHash(0),
-    value: 0,
-    entry_type: EntryType::Lower,
-    depth: 0, 
-    generation: 0,
-    best_move: None,
-};
-
-
-#[derive(Eq, Copy, PartialEq, Clone, Debug)]
-pub struct StateHash(pub u64);
-
-pub const BUCKET_SIZE : usize = 4; // 24 x 4 = 96 bytes .... that's 3 cache lines ... my cache alignment sucks, i need to get to 16 bytes per entry
-pub const STATE_HASH_ZERO : StateHash = StateHash(0);
-
-use std::ops::BitXor;
-
-pub type Generation = u8;
-
-impl BitXor for StateHash {
-    type Output = StateHash;
-
-    fn bitxor(self, other: StateHash) -> StateHash {
-        StateHash(self.0 ^ other.0)
-    }
-}
-
-#[derive(Clone)]
-pub struct TranspositionTable {
-    pub generation : Generation,
-    pub bucket_mask: u64,
-    pub entries : Vec<TranspositionEntry>,
-}
-
-impl TranspositionTable {
-    #[inline]
-    pub fn bucket_location_for(&self, hash:StateHash) -> usize {
-        ((hash.0 & self.bucket_mask) as usize) * BUCKET_SIZE
-    }
-
-    pub fn increment_generation(&mut self) {
-        self.generation = self.generation.wrapping_add(1)
-    }
-
-    pub fn probe(&self, hash:StateHash) -> (usize, bool) { // entry_location, found/match
-        let bucket_location = self.bucket_location_for(hash);
-        
-        // look for null or same position
-        for i in 0..BUCKET_SIZE {
-            let entry_location = bucket_location + i;
-            let entry = &self.entries[entry_location];
-            let same_hash = entry.hash == hash;
-            if entry.hash.0 == 0 || same_hash {
-                return (entry_location, same_hash)
-            }
-        }
-
-        // ok nothing the same, let's replace the least valuable entry
-        let current_generation = self.generation;
-
-        let mut replace_idx : usize = bucket_location;
-        for i in 0..BUCKET_SIZE {
-            let entry_location = bucket_location + i;
-            if self.entries[replace_idx].value(current_generation) > self.entries[entry_location].value(current_generation) { // if current replacement slot is newer than existing
-                replace_idx = entry_location;
-            }
-        }
-
-        return (replace_idx, false)
-    }
-
-    pub fn store(&mut self, idx: usize, hash:StateHash, value:HeuristicValue, depth: i8, entry_type: EntryType, best_move: Option<Move>) {
-        let entry = &mut self.entries[idx];
-
-        if entry.hash != hash || depth > (entry.depth - 4) || entry_type == EntryType::Exact {
-            entry.hash = hash;
-            entry.value = value;
-            entry.entry_type = entry_type;
-            entry.depth = depth;
-            entry.generation = self.generation;
-            entry.best_move = best_move;
-        }
-    }
-
-    pub fn size_bytes(&self) -> usize {
-        TranspositionTable::approx_size_bytes(self.entries.capacity())
-    }
-
-    pub fn approx_size_bytes(entry_count: usize) -> usize {
-        mem::size_of::<TranspositionEntry>() * entry_count
-    }
-
-    pub fn reset(&mut self) {
-        self.generation = 0;
-        for i in 0..self.entries.len() {
-            self.entries[i] = NULL_ENTRY;
-        }
-    }
-
-    pub fn new(power_of_two:usize) -> TranspositionTable {
-        let size = 1 << power_of_two;
-        let mut bucket_mask : usize = 1;
-        for _ in 0..(power_of_two-1-2) { // -1 is normal, the -2 is for buckets
-            bucket_mask = bucket_mask | (bucket_mask << 1);
-        }
-
-        let mut entries = Vec::with_capacity(size);
-        entries.resize(size, NULL_ENTRY);
-
-        TranspositionTable {
-            generation: 0,
-            bucket_mask: bucket_mask as u64,
-            entries: entries,
-        }
-    }
-}
-
-
-
 #[derive(Debug, Clone)]
 pub struct ZobristHash {
     pub to_move : [StateHash; PLAYERS],
     pub switch_move : StateHash,
-    pub builders : [[StateHash; SLOT_COUNT]; 2],
-    pub buildings : [[StateHash; 5]; SLOT_COUNT], // 0 is 0 (no flip) to remove branching
+    pub builders : [Vec<StateHash>; 2], // sized for the largest board, smaller boards use a prefix
+    pub buildings : Vec<[StateHash; 5]>, // 0 is 0 (no flip) to remove branching
+    pub athena_blocks : StateHash,
 }
 
 use rand::Rng;
@@ -173,8 +25,9 @@ impl ZobristHash {
         let mut hash = ZobristHash {
             to_move: [STATE_HASH_ZERO; PLAYERS],
             switch_move : STATE_HASH_ZERO,
-            builders : [[STATE_HASH_ZERO; SLOT_COUNT]; 2],
-            buildings : [[STATE_HASH_ZERO; 5]; SLOT_COUNT],
+            builders : [vec![STATE_HASH_ZERO; MAX_SLOT_COUNT], vec![STATE_HASH_ZERO; MAX_SLOT_COUNT]],
+            buildings : vec![[STATE_HASH_ZERO; 5]; MAX_SLOT_COUNT],
+            athena_blocks : STATE_HASH_ZERO,
         };
 
         for to_move in &mut hash.to_move {
@@ -190,17 +43,19 @@ impl ZobristHash {
             // for i in 1..5 { // leave first one null
             //     builder_hashes[i] = StateHash(r.next_u64());
             // }
-            for i in builder_hashes {
+            for i in builder_hashes.iter_mut() {
                 *i = StateHash(r.next_u64());
             }
         }
 
-        for building in &mut hash.buildings {
+        for building in hash.buildings.iter_mut() {
             for height in building {
                 *height = StateHash(r.next_u64());    
             }
         }
 
+        hash.athena_blocks = StateHash(r.next_u64());
+
         hash
     }
 }
@@ -211,24 +66,6 @@ mod tests {
     use std::mem;
     use super::*;
 
-    #[test]
-    fn table() {
-        let mut table = TranspositionTable::new(5); // 8 boxes in theory
-        println!("ok we have a table, entry count -> {:?}, mask -> {:b}", table.entries.len(), table.bucket_mask);
-
-        
-
-
-        for i in 1..40 {
-            let hash = StateHash(i);
-            let (idx, found) = table.probe(hash);
-            println!("state {:?} -> idx {:?} found {:?}", i, idx, found);
-
-            table.store(idx, hash, 12, 4, EntryType::Exact, None);
-        }
-
-    }
-
     #[test]
     fn my_zobist() {
         use super::Move::*;
@@ -253,10 +90,10 @@ mod tests {
         println!("Move size -> {}", mem::size_of::<Move>());
         println!("Option<Move> size -> {}", mem::size_of::<Option<Move>>());
         println!("EntryType size -> {}", mem::size_of::<EntryType>());
-        println!("TranspositionEntry size -> {}", mem::size_of::<TranspositionEntry>());
+        println!("TranspositionEntry size -> {}", mem::size_of::<TranspositionEntry<Move>>());
 
         // println!("talble entry count -> {}", TABLE_ENTRY_COUNT);
-        println!("size of table -> {}", mem::size_of::<TranspositionTable>());
+        println!("size of table -> {}", mem::size_of::<TranspositionTable<Move>>());
 
 
         println!("size of unit -> {}", mem::size_of::<()>())
@@ -268,7 +105,7 @@ mod tests {
     fn hash() {
         let new_hash = ZobristHash::new_unseeded();
         println!("constructing table");
-        let table = TranspositionTable::new(26);
+        let table : TranspositionTable<Move> = TranspositionTable::new(26);
         println!("mask -> {:#b}", table.bucket_mask);
         println!("capacity -> {}", table.entries.capacity());
         println!("approx size -> {}", table.size_bytes());
@@ -279,10 +116,10 @@ mod tests {
     
 
         let start = time::precise_time_ns();
-        let mut growable : Vec<TranspositionEntry> = Vec::with_capacity(MAH_CAP);
+        let mut growable : Vec<TranspositionEntry<Move>> = Vec::with_capacity(MAH_CAP);
         // unsafe { growable.set_len(MAH_CAP) };
         for _ in 0..MAH_CAP {
-            growable.push(NULL_ENTRY);
+            growable.push(TranspositionEntry::null());
             // growable[i] = NULL_ENTRY;
         }
         println!("what is stupid -> {:?}", growable[3000]);
@@ -0,0 +1,342 @@
+use game::santorini::*;
+use game::*;
+
+use std;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    WrongPhase, // placing once builders are down, or moving before they are
+    OffBoard(Slot),
+    NotYourBuilder(Slot),
+    Occupied(Slot),
+    ClimbTooHigh { from: Slot, to: Slot },
+    BuildOnDome(Slot),
+    NotAdjacent { from: Slot, to: Slot },
+    NeedsGod(God), // a power the player doesn't have
+    Illegal, // breaks a rule none of the above cover
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &MoveError::WrongPhase => write!(f, "wrong phase, builders are placed once at the start"),
+            &MoveError::OffBoard(slot) => write!(f, "{:?} is off the board", slot),
+            &MoveError::NotYourBuilder(slot) => write!(f, "no builder of yours on {:?}", slot),
+            &MoveError::Occupied(slot) => write!(f, "{:?} is occupied", slot),
+            &MoveError::ClimbTooHigh { from, to } => write!(f, "can't climb from {:?} to {:?}", from, to),
+            &MoveError::BuildOnDome(slot) => write!(f, "can't build on the dome at {:?}", slot),
+            &MoveError::NotAdjacent { from, to } => write!(f, "{:?} isn't next to {:?}", to, from),
+            &MoveError::NeedsGod(god) => write!(f, "only {:?} can do that", god),
+            &MoveError::Illegal => write!(f, "not a legal move"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+impl StandardBoard {
+    pub fn validate_move(&self, state: &State, mve: Move) -> Result<(), MoveError> {
+        let mut legal_moves = Vec::new();
+        self.next_moves_for_player(state, &mut legal_moves);
+        if legal_moves.contains(&mve) {
+            Ok(())
+        } else {
+            Err(self.explain_illegal(state, mve))
+        }
+    }
+
+    // the first rule an illegal move breaks, for when the legal moves are already known
+    pub fn explain_illegal(&self, state: &State, mve: Move) -> MoveError {
+        match self.check_rules(state, mve) {
+            Err(error) => error,
+            Ok(()) => MoveError::Illegal,
+        }
+    }
+
+    fn adjacent(&self, a: Slot, b: Slot) -> bool {
+        self.packed_adjacencies[a.0 as usize].get(b) > 0
+    }
+
+    // the rules worked out from the move itself rather than from the generated moves, so the god move generator
+    // has something to be checked against. the generator still leaves out some moves these allow that only
+    // repeat another: demeter's builds in the other order, and hermes travels a single step already covers
+    pub fn check_rules(&self, state: &State, mve: Move) -> Result<(), MoveError> {
+        for slot in mve.to_slots() {
+            if slot.0 < 0 || slot.0 as usize >= self.slots.len() {
+                return Err(MoveError::OffBoard(slot));
+            }
+        }
+
+        let placing = !state.current_builders().any();
+        let collision = state.collision();
+
+        if let Move::PlaceBuilders { a, b } = mve {
+            if !placing {
+                return Err(MoveError::WrongPhase);
+            }
+            for &slot in &[a, b] {
+                if collision.get(slot) > 0 {
+                    return Err(MoveError::Occupied(slot));
+                }
+            }
+            return if a == b { Err(MoveError::Occupied(b)) } else { Ok(()) };
+        }
+        if placing {
+            return Err(MoveError::WrongPhase);
+        }
+
+        let god = state.god();
+        let needed = match mve {
+            Move::Swap { .. } => Some(God::Apollo),
+            Move::Push { .. } => Some(God::Minotaur),
+            Move::Dome { .. } => Some(God::Atlas),
+            Move::BuildFirst { .. } => Some(God::Prometheus),
+            Move::Travel { .. } => Some(God::Hermes),
+            Move::DoubleBuild { build, second, .. } => Some(if build == second { God::Hephaestus } else { God::Demeter }),
+            _ => None,
+        };
+        if let Some(needed) = needed {
+            if god != needed {
+                return Err(MoveError::NeedsGod(needed));
+            }
+        }
+
+        if let Move::Travel { from, to, other_from, other_to, build } = mve {
+            self.check_walks(state, [(from, to), (other_from, other_to)])?;
+            return self.check_travel_build(state, [to, other_to], build);
+        }
+
+        let (from, to) = mve.movement().expect("everything but placement moves a builder");
+        if state.current_builders().get(from) == 0 {
+            return Err(MoveError::NotYourBuilder(from));
+        }
+
+        let mut state = state.clone();
+        if let Move::BuildFirst { first, .. } = mve {
+            self.check_build(&state, from, first, collision)?;
+            state.build_at(first);
+        }
+
+        // artemis can end up two steps away
+        if !self.adjacent(from, to) && god != God::Artemis {
+            return Err(MoveError::NotAdjacent { from: from, to: to });
+        }
+
+        let from_mask = Packed1(1 << from.0);
+        let to_mask = Packed1(1 << to.0);
+        let opponents = state.builders[state.next_player().0 as usize];
+        let occupied = match mve {
+            Move::Swap { .. } | Move::Push { .. } => {
+                if opponents.get(to) == 0 {
+                    return Err(MoveError::Illegal);
+                }
+                match mve {
+                    Move::Push { .. } => {
+                        let pushed_to = self.push_target(from, to).ok_or(MoveError::Illegal)?;
+                        if state.collision().get(pushed_to) > 0 {
+                            return Err(MoveError::Occupied(pushed_to));
+                        }
+                        collision ^ from_mask ^ Packed1(1 << pushed_to.0)
+                    },
+                    _ => collision,
+                }
+            },
+            _ => {
+                if state.collision().get(to) > 0 {
+                    return Err(MoveError::Occupied(to));
+                }
+                (collision & !from_mask) | to_mask
+            },
+        };
+
+        // prometheus gives up climbing to build first
+        let built_first = if let Move::BuildFirst { .. } = mve { true } else { false };
+        let max_climb = if state.athena_blocks || built_first { 0 } else { 1 };
+        let climbable = |from: Slot, to: Slot| state.get_building_height(to) <= state.get_building_height(from) + max_climb;
+        if !(self.adjacent(from, to) && climbable(from, to)) {
+            // artemis can also get there in two, through a free square next to both she could have stopped on
+            let between = if god == God::Artemis {
+                self.packed_adjacencies[from.0 as usize] & self.packed_adjacencies[to.0 as usize] & !state.collision()
+            } else {
+                PACKED1_EMPTY
+            };
+            if !between.iter().any(|via| climbable(from, via) && climbable(via, to)) {
+                return Err(if self.adjacent(from, to) || between.any() {
+                    MoveError::ClimbTooHigh { from: from, to: to }
+                } else {
+                    MoveError::NotAdjacent { from: from, to: to }
+                });
+            }
+        }
+
+        if let Some(build) = mve.build() {
+            self.check_build(&state, to, build, occupied)?;
+        }
+        match mve {
+            Move::DoubleBuild { build, second, .. } => {
+                self.check_build(&state, to, second, occupied)?;
+                // hephaestus's extra block can't be a dome
+                if build == second && state.get_building_height(build) >= 2 {
+                    return Err(MoveError::Illegal);
+                }
+            },
+            Move::Dome { build, .. } => {
+                // a dome on level 3 is an ordinary build
+                if state.get_building_height(build) == 3 {
+                    return Err(MoveError::Illegal);
+                }
+            },
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    // hermes's builders each stay on their own level, stepping one at a time around opponents, domes and each other
+    pub fn check_walks(&self, state: &State, walks: [(Slot, Slot); 2]) -> Result<(), MoveError> {
+        let opponents = state.builders[state.next_player().0 as usize];
+        for &(from, to) in &walks {
+            if state.current_builders().get(from) == 0 {
+                return Err(MoveError::NotYourBuilder(from));
+            }
+            if state.domes.get(to) > 0 || opponents.get(to) > 0 {
+                return Err(MoveError::Occupied(to));
+            }
+            if state.get_building_height(to) > state.get_building_height(from) {
+                return Err(MoveError::ClimbTooHigh { from: from, to: to });
+            }
+            if state.get_building_height(to) != state.get_building_height(from) {
+                return Err(MoveError::Illegal);
+            }
+        }
+        let (from, to) = walks[0];
+        let (other_from, other_to) = walks[1];
+        if from == other_from {
+            return Err(MoveError::Illegal);
+        }
+        if to == other_to {
+            return Err(MoveError::Occupied(to));
+        }
+
+        let open = |builder: Slot, slot: Slot, other: Slot| {
+            slot != other && state.domes.get(slot) == 0 && opponents.get(slot) == 0 &&
+            state.get_building_height(slot) == state.get_building_height(builder)
+        };
+        // seen[a] has every b the builders have been on together
+        let mut seen = vec![PACKED1_EMPTY; self.slots.len()];
+        seen[from.0 as usize].set(other_from, 1);
+        let mut stack = vec![(from, other_from)];
+        while let Some((a, b)) = stack.pop() {
+            if a == to && b == other_to {
+                return Ok(());
+            }
+            let mut steps = Vec::new();
+            for &next in self.adjacencies[a.0 as usize].iter().filter(|&&s| Self::valid(s) && open(from, s, b)) {
+                steps.push((next, b));
+            }
+            for &next in self.adjacencies[b.0 as usize].iter().filter(|&&s| Self::valid(s) && open(other_from, s, a)) {
+                steps.push((a, next));
+            }
+            for (a, b) in steps {
+                if seen[a.0 as usize].get(b) == 0 {
+                    seen[a.0 as usize].set(b, 1);
+                    stack.push((a, b));
+                }
+            }
+        }
+        Err(MoveError::Illegal)
+    }
+
+    // either of hermes's builders may build, wherever they ended up
+    pub fn check_travel_build(&self, state: &State, ends: [Slot; 2], build: Slot) -> Result<(), MoveError> {
+        let builder = if self.adjacent(ends[0], build) { ends[0] } else { ends[1] };
+        let occupied = state.builders[state.next_player().0 as usize] | state.domes | Packed1(1 << ends[0].0 | 1 << ends[1].0);
+        self.check_build(state, builder, build, occupied)
+    }
+
+    fn check_build(&self, state: &State, builder: Slot, build: Slot, occupied: Packed1) -> Result<(), MoveError> {
+        if !self.adjacent(builder, build) {
+            Err(MoveError::NotAdjacent { from: builder, to: build })
+        } else if state.domes.get(build) > 0 {
+            Err(MoveError::BuildOnDome(build))
+        } else if occupied.get(build) > 0 {
+            Err(MoveError::Occupied(build))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::*;
+
+    #[test]
+    fn legal() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = distant_state(&board);
+        assert_eq!(board.validate_move(&state, Move::Move { from: Slot(0), to: Slot(5), build: Slot(10) }), Ok(()));
+        assert_eq!(board.validate_move(&INITIAL_STATE, Move::PlaceBuilders { a: Slot(0), b: Slot(24) }), Ok(()));
+    }
+
+    #[test]
+    fn errors() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = distant_state(&board);
+        state.set_building_height(Slot(6), 2);
+        state.set_building_height(Slot(10), 3);
+        state.domes.set(Slot(10), 1);
+
+        let check = |state: &State, mve: Move| board.validate_move(state, mve).err();
+        let walk = |from, to, build| Move::Move { from: Slot(from), to: Slot(to), build: Slot(build) };
+
+        assert_eq!(check(&state, Move::PlaceBuilders { a: Slot(12), b: Slot(13) }), Some(MoveError::WrongPhase));
+        assert_eq!(check(&INITIAL_STATE, walk(0, 5, 10)), Some(MoveError::WrongPhase));
+        assert_eq!(check(&INITIAL_STATE, Move::PlaceBuilders { a: Slot(3), b: Slot(3) }), Some(MoveError::Occupied(Slot(3))));
+        assert_eq!(check(&state, walk(0, 5, 30)), Some(MoveError::OffBoard(Slot(30))));
+        assert_eq!(check(&state, walk(23, 18, 13)), Some(MoveError::NotYourBuilder(Slot(23))));
+        assert_eq!(check(&state, walk(0, 1, 2)), Some(MoveError::Occupied(Slot(1))));
+        assert_eq!(check(&state, walk(1, 11, 6)), Some(MoveError::NotAdjacent { from: Slot(1), to: Slot(11) }));
+        assert_eq!(check(&state, walk(0, 5, 15)), Some(MoveError::NotAdjacent { from: Slot(5), to: Slot(15) }));
+        assert_eq!(check(&state, walk(0, 6, 12)), Some(MoveError::ClimbTooHigh { from: Slot(0), to: Slot(6) }));
+        assert_eq!(check(&state, walk(0, 5, 10)), Some(MoveError::BuildOnDome(Slot(10))));
+        assert_eq!(check(&state, walk(1, 2, 1)), None);
+        assert_eq!(check(&state, Move::Swap { from: Slot(1), to: Slot(2), build: Slot(3) }), Some(MoveError::NeedsGod(God::Apollo)));
+
+        state.gods[0] = God::Apollo;
+        assert_eq!(check(&state, Move::Swap { from: Slot(1), to: Slot(2), build: Slot(3) }), Some(MoveError::Illegal));
+    }
+
+    #[test]
+    fn god_rules() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut state = distant_state(&board);
+        state.set_building_height(Slot(5), 1);
+        state.set_building_height(Slot(10), 2);
+        state.set_building_height(Slot(2), 3);
+
+        let check = |state: &State, god: God, mve: Move| {
+            let mut state = state.clone();
+            state.gods[0] = god;
+            board.check_rules(&state, mve)
+        };
+        let far = Move::Move { from: Slot(0), to: Slot(10), build: Slot(15) };
+        assert_eq!(check(&state, God::Artemis, far), Ok(()));
+        assert_eq!(check(&state, God::Mortal, far), Err(MoveError::NotAdjacent { from: Slot(0), to: Slot(10) }));
+        state.set_building_height(Slot(5), 0);
+        assert_eq!(check(&state, God::Artemis, far), Err(MoveError::ClimbTooHigh { from: Slot(0), to: Slot(10) }));
+
+        assert_eq!(check(&state, God::Atlas, Move::Dome { from: Slot(1), to: Slot(6), build: Slot(7) }), Ok(()));
+        assert_eq!(check(&state, God::Atlas, Move::Dome { from: Slot(1), to: Slot(6), build: Slot(2) }), Err(MoveError::Illegal));
+        assert_eq!(check(&state, God::Hephaestus, Move::DoubleBuild { from: Slot(1), to: Slot(6), build: Slot(5), second: Slot(5) }), Ok(()));
+        assert_eq!(check(&state, God::Hephaestus, Move::DoubleBuild { from: Slot(1), to: Slot(6), build: Slot(10), second: Slot(10) }), Err(MoveError::Illegal));
+
+        let travel = |to, other_to, build| Move::Travel { from: Slot(0), to: Slot(to), other_from: Slot(1), other_to: Slot(other_to), build: Slot(build) };
+        assert_eq!(check(&state, God::Hermes, travel(0, 1, 6)), Ok(()));
+        assert_eq!(check(&state, God::Hermes, travel(20, 4, 15)), Ok(()));
+        assert_eq!(check(&state, God::Hermes, travel(10, 1, 6)), Err(MoveError::ClimbTooHigh { from: Slot(0), to: Slot(10) }));
+        assert_eq!(check(&state, God::Mortal, travel(0, 1, 6)), Err(MoveError::NeedsGod(God::Hermes)));
+    }
+}
@@ -0,0 +1,205 @@
+use game::*;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use time;
+
+// how often (in nodes) a search looks at the clock and the stop flag, reading the time isn't free. the node budget
+// is only a compare so it's checked on every node
+pub const LIMIT_CHECK_INTERVAL : u64 = 1024;
+
+// when a search has to give up part way through an iteration. the stop flag can be shared with
+// another thread to cancel from outside
+#[derive(Debug, Clone)]
+pub struct SearchLimits {
+    pub deadline : Option<u64>, // time::precise_time_ns
+    pub node_budget : Option<u64>, // per iteration, every depth search_within starts gets the whole budget again
+    pub stop : Arc<AtomicBool>,
+}
+
+impl SearchLimits {
+    pub fn none() -> SearchLimits {
+        SearchLimits {
+            deadline: None,
+            node_budget: None,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // ms from now
+    pub fn with_time(milliseconds: u64) -> SearchLimits {
+        SearchLimits::none().time(milliseconds)
+    }
+
+    pub fn with_nodes(nodes: u64) -> SearchLimits {
+        SearchLimits::none().nodes(nodes)
+    }
+
+    pub fn time(self, milliseconds: u64) -> SearchLimits {
+        SearchLimits {
+            deadline: Some(time::precise_time_ns() + milliseconds * 1_000_000),
+            ..self
+        }
+    }
+
+    // nodes for each iteration rather than the search as a whole, so search_within to depth d can visit up to d times
+    // as many
+    pub fn nodes(self, nodes: u64) -> SearchLimits {
+        SearchLimits {
+            node_budget: Some(nodes),
+            ..self
+        }
+    }
+
+    // cancels through the given flag, rather than one of our own
+    pub fn stopped_by(self, stop: Arc<AtomicBool>) -> SearchLimits {
+        SearchLimits {
+            stop: stop,
+            ..self
+        }
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    // nodes searched so far in this iteration
+    pub fn exceeded(&self, nodes: u64) -> bool {
+        self.over_budget(nodes) || self.out_of_time()
+    }
+
+    pub fn over_budget(&self, nodes: u64) -> bool {
+        self.node_budget.map(|budget| nodes >= budget).unwrap_or(false)
+    }
+
+    // stopped from outside, or past the deadline
+    pub fn out_of_time(&self) -> bool {
+        self.stopped() || self.deadline.map(|deadline| time::precise_time_ns() >= deadline).unwrap_or(false)
+    }
+}
+
+// iterative deepening up to max_depth within the limits. gives back the deepest completed iteration's best move
+// and its depth, an aborted iteration is thrown away unless nothing completed at all (depth 0 then)
+pub fn search_within<G, E, H>(evaluator_state: &mut E::EvaluatorState, game: &G, state: &G::State, max_depth: Depth, limits: &SearchLimits) -> (Option<(G::Move, HeuristicValue)>, Depth, EvaluatorInfo) where G: Game, E: Evaluator<G>, H: Heuristic<G> {
+    search_reporting::<G, E, H, _>(evaluator_state, game, state, max_depth, limits, |_, _, _, _| true)
+}
+
+// search_within, telling report about each completed iteration (with that iteration's info) as it finishes.
+// report gives back whether to go on to the next depth
+pub fn search_reporting<G, E, H, F>(evaluator_state: &mut E::EvaluatorState, game: &G, state: &G::State, max_depth: Depth, limits: &SearchLimits, mut report: F) -> (Option<(G::Move, HeuristicValue)>, Depth, EvaluatorInfo) where G: Game, E: Evaluator<G>, H: Heuristic<G>, F: FnMut(&E::EvaluatorState, Depth, Option<(G::Move, HeuristicValue)>, &EvaluatorInfo) -> bool {
+    let mut info = EvaluatorInfo::new();
+    let mut completed : (Option<(G::Move, HeuristicValue)>, Depth) = (None, 0);
+
+    E::new_search(evaluator_state);
+
+    for depth in 1..(max_depth + 1) {
+        let (best_move, depth_info) = E::evaluate_moves_limited::<H>(evaluator_state, game, state, depth, limits);
+        let aborted = depth_info.aborted;
+        let carry_on = aborted || report(evaluator_state, depth, best_move, &depth_info);
+        info += depth_info;
+
+        if aborted {
+            if completed.1 == 0 {
+                completed.0 = best_move;
+            }
+            break;
+        }
+
+        completed = (best_move, depth);
+
+        if !carry_on {
+            break;
+        }
+    }
+
+    (completed.0, completed.1, info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::santorini::*;
+
+    #[test]
+    fn node_budget() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let state = board.apply(Move::PlaceBuilders { a: Slot(23), b: Slot(24) }, &board.apply(Move::PlaceBuilders { a: Slot(0), b: Slot(1) }, &INITIAL_STATE));
+
+        let mut ev_state = EvState::new();
+        let (unlimited, unlimited_depth, _) = search_within::<StandardBoard, NegaMaxAlphaBetaExp, SimpleHeightHeuristic>(&mut ev_state, &board, &state, 3, &SearchLimits::none());
+        assert_eq!(unlimited_depth, 3);
+        assert!(unlimited.is_some());
+
+        // plenty for the first depths, nowhere near enough for 8
+        let mut ev_state = EvState::new();
+        let (best_move, depth, info) = search_within::<StandardBoard, NegaMaxAlphaBetaExp, SimpleHeightHeuristic>(&mut ev_state, &board, &state, 8, &SearchLimits::with_nodes(20_000));
+        assert!(info.aborted);
+        assert!(depth >= 1 && depth < 8, "stopped at depth {}", depth);
+        assert!(best_move.is_some());
+
+        // the same as searching to that depth from scratch
+        let mut ev_state = EvState::new();
+        let (expected, _, _) = search_within::<StandardBoard, NegaMaxAlphaBetaExp, SimpleHeightHeuristic>(&mut ev_state, &board, &state, depth, &SearchLimits::none());
+        assert_eq!(best_move.map(|(_, score)| score), expected.map(|(_, score)| score));
+    }
+
+    #[test]
+    fn reporting_stops_when_asked() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let mut reported = Vec::new();
+
+        let mut ev_state = EvState::new();
+        let (best_move, depth, info) = search_reporting::<StandardBoard, NegaMaxAlphaBetaExp, SimpleHeightHeuristic, _>(&mut ev_state, &board, &INITIAL_STATE, 6, &SearchLimits::none(), |_, depth, best_move, _| {
+            reported.push((depth, best_move));
+            depth < 2
+        });
+
+        assert!(!info.aborted);
+        assert_eq!(depth, 2);
+        assert_eq!(reported.len(), 2);
+        assert_eq!(reported[1], (2, best_move));
+    }
+
+    #[test]
+    fn budget_under_check_interval() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let budget = LIMIT_CHECK_INTERVAL / 10;
+
+        // stops on the budget itself, not at the next clock check
+        let mut ev_state = EvState::new();
+        let (_, info) = NegaMaxAlphaBetaExp::evaluate_moves_limited::<SimpleHeightHeuristic>(&mut ev_state, &board, &INITIAL_STATE, 3, &SearchLimits::with_nodes(budget));
+        assert!(info.aborted);
+        assert!(ev_state.nodes >= budget && ev_state.nodes < LIMIT_CHECK_INTERVAL, "stopped after {} nodes", ev_state.nodes);
+    }
+
+    #[test]
+    fn stopped_from_outside() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let limits = SearchLimits::none();
+        limits.stop();
+
+        // nothing completes, the partial first depth is all there is
+        let mut ev_state = EvState::new();
+        let (_, depth, info) = search_within::<StandardBoard, NegaMaxAlphaBetaExp, SimpleHeightHeuristic>(&mut ev_state, &board, &INITIAL_STATE, 6, &limits);
+        assert_eq!(depth, 0);
+        assert!(info.aborted);
+    }
+
+    #[test]
+    fn deadline() {
+        let board = StandardBoard::new(ZobristHash::new_unseeded());
+        let start = time::precise_time_ns();
+        let mut ev_state = EvState::new();
+        let (_, depth, info) = search_within::<StandardBoard, NegaMaxAlphaBetaExp, SimpleHeightHeuristic>(&mut ev_state, &board, &INITIAL_STATE, 20, &SearchLimits::with_time(100));
+        let elapsed_ms = (time::precise_time_ns() - start) / 1_000_000;
+
+        assert!(info.aborted);
+        assert!(depth < 20);
+        assert!(elapsed_ms < 1_000, "took {}ms", elapsed_ms);
+    }
+}
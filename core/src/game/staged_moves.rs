@@ -0,0 +1,196 @@
+use game::*;
+
+// the order the search tries moves in, each stage is only generated once the ones before it are used up
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MoveStage {
+    TTMove, // the best move from the transposition table, checked for legality rather than generated
+    Winning, // wins on the spot
+    Killers, // quiet moves that cut off at the same ply elsewhere, checked for legality like the tt move
+    Climbing, // up a level
+    Rest,
+    Done,
+}
+
+impl MoveStage {
+    pub fn next(&self) -> MoveStage {
+        match *self {
+            MoveStage::TTMove => MoveStage::Winning,
+            MoveStage::Winning => MoveStage::Killers,
+            MoveStage::Killers => MoveStage::Climbing,
+            MoveStage::Climbing => MoveStage::Rest,
+            MoveStage::Rest | MoveStage::Done => MoveStage::Done,
+        }
+    }
+
+    // handed out without being generated
+    pub fn checked(&self) -> bool {
+        *self == MoveStage::TTMove || *self == MoveStage::Killers
+    }
+}
+
+pub const MAX_PLY : usize = 64;
+pub const HISTORY_SQUARES : usize = 64;
+
+// what the search learns about good moves as it goes: two killers a ply, and a from/to history of cutoffs
+pub struct MoveOrdering<M> {
+    pub killers : Vec<[Option<M>; 2]>,
+    pub history : Vec<u32>,
+}
+
+impl<M> MoveOrdering<M> where M: Copy + Eq {
+    pub fn new() -> MoveOrdering<M> {
+        MoveOrdering {
+            killers: vec![[None, None]; MAX_PLY],
+            history: vec![0; HISTORY_SQUARES * HISTORY_SQUARES],
+        }
+    }
+
+    pub fn killers(&self, ply: usize) -> [Option<M>; 2] {
+        if ply < MAX_PLY {
+            self.killers[ply]
+        } else {
+            [None, None]
+        }
+    }
+
+    // the newest goes first, pushing out the older of the two
+    pub fn add_killer(&mut self, ply: usize, mve: M) {
+        if ply < MAX_PLY && self.killers[ply][0] != Some(mve) {
+            self.killers[ply][1] = self.killers[ply][0];
+            self.killers[ply][0] = Some(mve);
+        }
+    }
+
+    fn history_index<G>(game: &G, mve: M) -> Option<usize> where G: Game<Move=M> {
+        match game.history_key(mve) {
+            Some((from, to)) if from < HISTORY_SQUARES && to < HISTORY_SQUARES => Some(from * HISTORY_SQUARES + to),
+            _ => None,
+        }
+    }
+
+    // deeper cutoffs count for more, they save more
+    pub fn add_history<G>(&mut self, game: &G, mve: M, depth: Depth) where G: Game<Move=M> {
+        if let Some(idx) = Self::history_index(game, mve) {
+            let bonus = (depth as u32) * (depth as u32);
+            self.history[idx] = self.history[idx].saturating_add(bonus);
+        }
+    }
+
+    pub fn history_score<G>(&self, game: &G, mve: M) -> u32 where G: Game<Move=M> {
+        Self::history_index(game, mve).map(|idx| self.history[idx]).unwrap_or(0)
+    }
+
+    // killers are for the positions just searched, history is halved so older searches count for less
+    pub fn new_search(&mut self) {
+        for killers in self.killers.iter_mut() {
+            *killers = [None, None];
+        }
+        for h in self.history.iter_mut() {
+            *h /= 2;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.new_search();
+        for h in self.history.iter_mut() {
+            *h = 0;
+        }
+    }
+}
+
+// hands out a node's moves a stage at a time, generating them onto the shared move stack above any that came
+// before. children push and pop above those in between calls to next, so call finish before leaving the node
+pub struct StagedMoves<M> {
+    pub stage: MoveStage, // the next to generate
+    pub tt_move: Option<M>,
+    pub killers: [Option<M>; 2],
+    pub tried: [Option<M>; 3], // the tt move and killers once they've been handed out
+    pub begin: usize, // where this node's moves start on the stack
+    pub next: usize, // the next one to hand out
+    pub checked: bool, // whether the moves being handed out are the tt move or killers
+}
+
+impl<M> StagedMoves<M> where M: Copy + Eq {
+    pub fn new(move_stack: &MoveStack<M>, tt_move: Option<M>) -> StagedMoves<M> {
+        StagedMoves::with_killers(move_stack, tt_move, [None, None])
+    }
+
+    pub fn with_killers(move_stack: &MoveStack<M>, tt_move: Option<M>, killers: [Option<M>; 2]) -> StagedMoves<M> {
+        StagedMoves {
+            stage: MoveStage::TTMove,
+            tt_move: tt_move,
+            killers: killers,
+            tried: [None, None, None],
+            begin: move_stack.next,
+            next: move_stack.next,
+            checked: false,
+        }
+    }
+
+    fn tried(&self, mve: M) -> bool {
+        self.tried.contains(&Some(mve))
+    }
+
+    pub fn next<G>(&mut self, game: &G, state: &G::State, move_stack: &mut MoveStack<M>) -> Option<M> where G: Game<Move=M> {
+        self.next_by(game, state, move_stack, None::<fn(M) -> u32>)
+    }
+
+    // as next, with the climbing and rest stages sorted highest history first
+    pub fn next_by_history<G>(&mut self, game: &G, state: &G::State, move_stack: &mut MoveStack<M>, ordering: &MoveOrdering<M>) -> Option<M> where G: Game<Move=M> {
+        self.next_by(game, state, move_stack, Some(|mve| ordering.history_score(game, mve)))
+    }
+
+    fn next_by<G, F>(&mut self, game: &G, state: &G::State, move_stack: &mut MoveStack<M>, history: Option<F>) -> Option<M> where G: Game<Move=M>, F: Fn(M) -> u32 {
+        loop {
+            while self.next < move_stack.next {
+                let mve = move_stack.moves[self.next];
+                self.next += 1;
+                // the tt move and killers went first, so skip them when their stage generates them again
+                if self.checked || !self.tried(mve) {
+                    return Some(mve);
+                }
+            }
+
+            let stage_begin = move_stack.next;
+            match self.stage {
+                MoveStage::Done => return None,
+                MoveStage::TTMove => {
+                    match self.tt_move {
+                        Some(mve) if game.legal_move(state, mve) => {
+                            move_stack.push(mve);
+                            self.tried[0] = Some(mve);
+                        },
+                        _ => self.tt_move = None,
+                    }
+                },
+                MoveStage::Killers => {
+                    // winning killers already came out with the winning moves
+                    for i in 0..2 {
+                        match self.killers[i] {
+                            Some(mve) if !self.tried(mve) && game.legal_move(state, mve) && !game.winning_move(state, mve) => {
+                                move_stack.push(mve);
+                                self.tried[i + 1] = Some(mve);
+                            },
+                            _ => (),
+                        }
+                    }
+                },
+                stage => {
+                    game.next_moves_in_stage(state, stage, move_stack);
+                    if stage == MoveStage::Climbing || stage == MoveStage::Rest {
+                        if let Some(ref score) = history {
+                            move_stack.moves[stage_begin..move_stack.next].sort_by(|&a, &b| score(b).cmp(&score(a)));
+                        }
+                    }
+                },
+            }
+            self.checked = self.stage.checked();
+            self.stage = self.stage.next();
+        }
+    }
+
+    // pops this node's moves off the stack
+    pub fn finish(&self, move_stack: &mut MoveStack<M>) {
+        move_stack.next = self.begin;
+    }
+}
@@ -0,0 +1,374 @@
+use game::tictactoe::*;
+use game::*;
+
+use rand::Rng;
+use rand::XorShiftRng;
+
+#[derive(Debug, Clone)]
+pub struct ZobristHash {
+    pub to_move : [StateHash; 2],
+    pub switch_move : StateHash,
+    pub marks : [[StateHash; SLOT_COUNT]; 2],
+}
+
+impl ZobristHash {
+    pub fn new_unseeded() -> ZobristHash {
+        Self::new(&mut XorShiftRng::new_unseeded())
+    }
+
+    pub fn new<R : Rng>(r: &mut R) -> ZobristHash {
+        let mut hash = ZobristHash {
+            to_move: [STATE_HASH_ZERO; 2],
+            switch_move: STATE_HASH_ZERO,
+            marks: [[STATE_HASH_ZERO; SLOT_COUNT]; 2],
+        };
+
+        for to_move in &mut hash.to_move {
+            *to_move = StateHash(r.next_u64());
+        }
+
+        hash.switch_move = hash.to_move[0] ^ hash.to_move[1];
+
+        for player_hashes in &mut hash.marks {
+            for i in player_hashes {
+                *i = StateHash(r.next_u64());
+            }
+        }
+
+        hash
+    }
+}
+
+#[derive(Clone)]
+pub struct TicTacToeBoard {
+    pub transforms : [CellTransform; 7],
+    pub hash : ZobristHash,
+}
+
+impl Game for TicTacToeBoard {
+    type State = State;
+    type Move = Move;
+    type Player = Player;
+    type Undo = ();
+
+    fn initial_state(&self) -> State {
+        INITIAL_STATE
+    }
+
+    fn to_move(&self, state: &State) -> Player {
+        state.to_move
+    }
+
+    fn next_player(&self, state: &State) -> Player {
+        state.next_player()
+    }
+
+    fn player_index(&self, player: Player) -> usize {
+        player.0 as usize
+    }
+
+    fn color(&self, player: Player) -> HeuristicValue {
+        match player {
+            Player(0) => 1,
+            Player(1) => -1,
+            _ => panic!("tic-tac-toe only has two players, not {:?}", player),
+        }
+    }
+
+    fn next_moves<T : MoveSink<Move>>(&self, state: &State, move_sink: &mut T) {
+        TicTacToeBoard::next_moves(self, state, move_sink)
+    }
+
+    fn apply(&self, mve: Move, state: &State) -> State {
+        TicTacToeBoard::apply(self, mve, state)
+    }
+
+    fn make_move(&self, state: &mut State, mve: Move) {
+        state.marks[state.to_move.0 as usize].0 |= 1 << (mve.0).0;
+        state.to_move = state.next_player();
+    }
+
+    #[allow(unused_variables)]
+    fn unmake_move(&self, state: &mut State, mve: Move, undo: ()) {
+        state.to_move = state.next_player();
+        state.marks[state.to_move.0 as usize].0 &= !(1 << (mve.0).0);
+    }
+
+    fn hash(&self, state: &State) -> StateHash {
+        TicTacToeBoard::hash(self, state)
+    }
+
+    fn delta_hash(&self, state: &State, mve: Move) -> StateHash {
+        TicTacToeBoard::delta_hash(self, state, mve)
+    }
+
+    fn winning_move(&self, state: &State, mve: Move) -> bool {
+        TicTacToeBoard::winning_move(self, state, mve)
+    }
+
+    #[allow(unused_variables)]
+    fn no_moves_value(&self, state: &State) -> HeuristicValue {
+        0 // a full board without a line is a draw
+    }
+
+    fn print(&self, state: &State) -> String {
+        TicTacToeBoard::print(state)
+    }
+}
+
+impl TicTacToeBoard {
+    pub fn new(hash: ZobristHash) -> TicTacToeBoard {
+        let mut board = TicTacToeBoard {
+            transforms: [EMPTY_CELL_TRANSFORM; 7],
+            hash: hash,
+        };
+
+        board.transforms = [
+            TicTacToeBoard::transform(rotate_90),
+            TicTacToeBoard::transform(rotate_180),
+            TicTacToeBoard::transform(rotate_270),
+            TicTacToeBoard::transform(reflect_x),
+            TicTacToeBoard::transform(reflect_y),
+            TicTacToeBoard::transform(reflect_diag_a),
+            TicTacToeBoard::transform(reflect_diag_b),
+        ];
+
+        board
+    }
+
+    pub fn position(slot: Slot) -> Position {
+        Position {
+            x: slot.0 % (BOARD_SIZE as i8),
+            y: slot.0 / (BOARD_SIZE as i8),
+        }
+    }
+
+    pub fn slot(pos: Position) -> Slot {
+        Slot(pos.x + pos.y * (BOARD_SIZE as i8))
+    }
+
+    pub fn transform<F>(f: F) -> CellTransform where F: Fn(Position) -> Position {
+        let mut transform = EMPTY_CELL_TRANSFORM;
+        for i in 0..SLOT_COUNT {
+            let slot = Slot(i as i8);
+            transform.slots[i] = TicTacToeBoard::slot(f(TicTacToeBoard::position(slot)));
+        }
+        transform
+    }
+
+    pub fn transform_packed(transform: &CellTransform, packed: Packed1) -> Packed1 {
+        let mut out = PACKED1_EMPTY;
+        for slot in packed.iter() {
+            out.0 |= 1 << transform.slots[slot.0 as usize].0;
+        }
+        out
+    }
+
+    pub fn transform_state(state: &State, transform: &CellTransform) -> State {
+        State {
+            marks: [
+                TicTacToeBoard::transform_packed(transform, state.marks[0]),
+                TicTacToeBoard::transform_packed(transform, state.marks[1]),
+            ],
+            to_move: state.to_move,
+        }
+    }
+
+    pub fn permute(&self, state: &State, sink: &mut Vec<State>) {
+        for trans in &self.transforms {
+            sink.push(TicTacToeBoard::transform_state(state, trans));
+        }
+    }
+
+    // every empty cell, minus those leading to a position symmetric to one already produced
+    pub fn next_moves<T : MoveSink<Move>>(&self, state: &State, move_sink: &mut T) {
+        if state.winner().is_some() {
+            return;
+        }
+
+        let player = state.to_move.0 as usize;
+        let empty = Packed1(!state.occupied().0 & FULL_MASK);
+
+        let mut seen : Vec<[Packed1; 2]> = Vec::with_capacity(SLOT_COUNT);
+
+        for slot in empty.iter() {
+            let mut marks = state.marks;
+            marks[player].0 |= 1 << slot.0;
+
+            let symmetric = self.transforms.iter().any(|t| {
+                let transformed = [TicTacToeBoard::transform_packed(t, marks[0]), TicTacToeBoard::transform_packed(t, marks[1])];
+                seen.contains(&transformed)
+            });
+
+            if !symmetric {
+                seen.push(marks);
+                move_sink.sink(Move(slot));
+            }
+        }
+    }
+
+    // every empty cell, no symmetry reduction
+    pub fn all_moves<T : MoveSink<Move>>(&self, state: &State, move_sink: &mut T) {
+        if state.winner().is_some() {
+            return;
+        }
+
+        let empty = Packed1(!state.occupied().0 & FULL_MASK);
+        for slot in empty.iter() {
+            move_sink.sink(Move(slot));
+        }
+    }
+
+    pub fn apply(&self, mve: Move, state: &State) -> State {
+        let mut new_state = state.clone();
+        new_state.marks[state.to_move.0 as usize].0 |= 1 << (mve.0).0;
+        new_state.to_move = state.next_player();
+        new_state
+    }
+
+    pub fn hash(&self, state: &State) -> StateHash {
+        let mut hash = self.hash.to_move[state.to_move.0 as usize];
+
+        for player in 0..2 {
+            for slot in state.marks[player].iter() {
+                hash = hash ^ self.hash.marks[player][slot.0 as usize];
+            }
+        }
+
+        hash
+    }
+
+    pub fn delta_hash(&self, state: &State, mve: Move) -> StateHash {
+        self.hash.switch_move ^ self.hash.marks[state.to_move.0 as usize][(mve.0).0 as usize]
+    }
+
+    pub fn winning_move(&self, state: &State, mve: Move) -> bool {
+        let marks = Packed1(state.marks[state.to_move.0 as usize].0 | 1 << (mve.0).0);
+        State::has_line(marks)
+    }
+
+    pub fn print(state: &State) -> String {
+        let mut out = String::new();
+
+        out.push_str(&format!(" === To move {:?} === \n", state.to_move));
+
+        for y in 0..BOARD_SIZE {
+            if y > 0 {
+                out.push_str("---+---+---\n");
+            }
+            let mut row : Vec<String> = Vec::new();
+            for x in 0..BOARD_SIZE {
+                let slot = TicTacToeBoard::slot(Position { x: x as i8, y: y as i8 });
+                if state.marks[0].get(slot) > 0 {
+                    row.push(" X ".into());
+                } else if state.marks[1].get(slot) > 0 {
+                    row.push(" O ".into());
+                } else {
+                    row.push("   ".into());
+                }
+            }
+            out.push_str(&row.join("|"));
+            out.push_str("\n");
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> TicTacToeBoard {
+        TicTacToeBoard::new(ZobristHash::new_unseeded())
+    }
+
+    fn play(board: &TicTacToeBoard, cells: &[i8]) -> State {
+        let mut state = INITIAL_STATE;
+        for &c in cells {
+            state = board.apply(Move(Slot(c)), &state);
+        }
+        state
+    }
+
+    #[test]
+    fn transforms_are_permutations() {
+        let board = board();
+        for transform in &board.transforms {
+            let mut cells = PACKED1_EMPTY;
+            for slot in transform.slots.iter() {
+                cells.0 |= 1 << slot.0;
+            }
+            assert_eq!(cells.0, FULL_MASK);
+        }
+        assert_eq!(board.transforms[0].slots[0], Slot(2)); // corner goes to corner
+        assert_eq!(board.transforms[0].slots[4], Slot(4)); // center stays put
+    }
+
+    #[test]
+    fn symmetric_moves_reduced() {
+        let board = board();
+
+        let mut moves = Vec::new();
+        board.next_moves(&INITIAL_STATE, &mut moves);
+        assert_eq!(moves.len(), 3); // corner, edge, center
+
+        let mut all = Vec::new();
+        board.all_moves(&INITIAL_STATE, &mut all);
+        assert_eq!(all.len(), 9);
+    }
+
+    #[test]
+    fn delta_hash_matches_hash() {
+        let board = board();
+        let state = play(&board, &[4, 0, 8]);
+        let hash = board.hash(&state);
+
+        let mut moves = Vec::new();
+        board.all_moves(&state, &mut moves);
+        for mve in moves {
+            let new_state = board.apply(mve, &state);
+            assert_eq!(hash ^ board.delta_hash(&state, mve), board.hash(&new_state));
+        }
+    }
+
+    #[test]
+    fn perft_counts() {
+        let board = board();
+        let mut move_stack = MoveStack::new();
+        // every position up to symmetry with a depth of 1 is a single placement
+        assert_eq!(perft(&board, &INITIAL_STATE, 1, &mut move_stack), 3);
+        assert_eq!(perft(&board, &INITIAL_STATE, 2, &mut move_stack), 12);
+    }
+
+    #[test]
+    fn finds_win_in_1() {
+        let board = board();
+        // X on 0 1, O on 3 4, X to move
+        let state = play(&board, &[0, 3, 1, 4]);
+        let mut ev_state = EvState::new();
+        let (best, _) = NegaMaxAlphaBetaExp::evaluate_moves::<LineHeuristic>(&mut ev_state, &board, &state, 3);
+        assert_eq!(best, Some((Move(Slot(2)), victory_in(1))));
+    }
+
+    #[test]
+    fn blocks_loss_in_1() {
+        let board = board();
+        // X on 0 1, O on 4, O has to take 2 to hold the draw
+        let state = play(&board, &[0, 4, 1]);
+        let (best, _) = NegaMax::evaluate_moves::<LineHeuristic>(&mut (), &board, &state, 6);
+        assert_eq!(best, Some((Move(Slot(2)), 0)));
+    }
+
+    #[test]
+    fn solved_draw() {
+        let board = board();
+
+        let (best, _) = NegaMaxAlphaBeta::evaluate_moves::<LineHeuristic>(&mut (), &board, &INITIAL_STATE, 9);
+        assert_eq!(best.map(|(_, score)| score), Some(0));
+
+        let mut ev_state = EvState::new();
+        let (best, _) = NegaMaxAlphaBetaExp::evaluate_moves::<LineHeuristic>(&mut ev_state, &board, &INITIAL_STATE, 9);
+        assert_eq!(best.map(|(_, score)| score), Some(0));
+    }
+}
@@ -0,0 +1,28 @@
+use game::tictactoe::*;
+use game::*;
+
+// +n for every line only x has n marks on, -n for o
+pub struct LineHeuristic {}
+
+impl Heuristic<TicTacToeBoard> for LineHeuristic {
+    fn name() -> String {
+        "LineHeuristic".into()
+    }
+
+    #[allow(unused_variables)]
+    fn evaluate(board: &TicTacToeBoard, state: &State) -> HeuristicValue {
+        let mut n : HeuristicValue = 0;
+
+        for &line in LINES.iter() {
+            let x = (state.marks[0].0 & line).count_ones() as HeuristicValue;
+            let o = (state.marks[1].0 & line).count_ones() as HeuristicValue;
+            if o == 0 {
+                n += x;
+            } else if x == 0 {
+                n -= o;
+            }
+        }
+
+        n
+    }
+}
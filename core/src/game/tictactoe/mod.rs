@@ -0,0 +1,67 @@
+pub mod board;
+pub mod state;
+pub mod heuristic;
+
+pub use self::board::*;
+pub use self::state::*;
+pub use self::heuristic::*;
+
+use game::util::*;
+
+pub const BOARD_SIZE : usize = 3;
+pub const SLOT_COUNT : usize = 9;
+
+// all 8 lines, as 9 bit masks
+pub const LINES : [u64; 8] = [
+    0b000_000_111,
+    0b000_111_000,
+    0b111_000_000,
+    0b001_001_001,
+    0b010_010_010,
+    0b100_100_100,
+    0b100_010_001,
+    0b001_010_100,
+];
+
+pub const FULL_MASK : u64 = 0b111_111_111;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Move(pub Slot);
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CellTransform {
+    pub slots : [Slot; SLOT_COUNT]
+}
+
+pub const EMPTY_CELL_TRANSFORM : CellTransform = CellTransform { slots: [Slot(0); SLOT_COUNT] };
+
+const PRE_ROTATE : Position = Position { x: -1, y: -1 };
+const POST_ROTATE : Position = Position { x: 1, y: 1 };
+
+pub fn rotate_90(pos: Position) -> Position {
+    (ROTATE_90 * (pos + PRE_ROTATE)) + POST_ROTATE
+}
+
+pub fn rotate_180(pos: Position) -> Position {
+    (ROTATE_180 * (pos + PRE_ROTATE)) + POST_ROTATE
+}
+
+pub fn rotate_270(pos: Position) -> Position {
+    (ROTATE_270 * (pos + PRE_ROTATE)) + POST_ROTATE
+}
+
+pub fn reflect_x(pos: Position) -> Position {
+    Position { x: 2 - pos.x, y: pos.y }
+}
+
+pub fn reflect_y(pos: Position) -> Position {
+    Position { x: pos.x, y: 2 - pos.y }
+}
+
+pub fn reflect_diag_a(pos: Position) -> Position {
+    Position { x: pos.y, y: pos.x }
+}
+
+pub fn reflect_diag_b(pos: Position) -> Position {
+    reflect_diag_a(rotate_180(pos))
+}
@@ -0,0 +1,36 @@
+use game::tictactoe::*;
+use game::*;
+
+pub const INITIAL_STATE : State = State {
+    marks: [PACKED1_EMPTY; 2],
+    to_move: Player(0),
+};
+
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
+pub struct State {
+    pub marks: [Packed1; 2], // low 9 bits, x then o
+    pub to_move : Player,
+}
+
+impl State {
+    pub fn occupied(&self) -> Packed1 {
+        self.marks[0] | self.marks[1]
+    }
+
+    pub fn next_player(&self) -> Player {
+        Player((self.to_move.0 + 1) % 2)
+    }
+
+    pub fn has_line(marks: Packed1) -> bool {
+        LINES.iter().any(|&line| marks.0 & line == line)
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        for i in 0..2 {
+            if State::has_line(self.marks[i]) {
+                return Some(Player(i as i8));
+            }
+        }
+        None
+    }
+}
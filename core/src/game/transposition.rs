@@ -0,0 +1,213 @@
+use game::*;
+
+use std::mem;
+
+#[derive(Eq, Copy, PartialEq, Clone, Debug)]
+pub enum EntryType {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Eq, Copy, PartialEq, Clone, Debug)]
+pub struct TranspositionEntry<M> {
+    // pub state: State,
+    pub hash: StateHash, // 8 bytes
+    pub value: HeuristicValue, // 2 bytes
+    pub entry_type: EntryType, // 1 byte
+    pub depth: i8, // 1 byte
+    pub generation: Generation, // 1 byte
+    pub best_move: Option<M>, // 5 bytes for santorini
+
+    // move in theory could be reduced to 2 bytes + flag -> 3 bytes  + optionality == 3-4 ish bytes ...
+}
+
+impl<M> TranspositionEntry<M> {
+    pub fn value(&self, current_generation: Generation) -> i8 { // i feel this numerical type isn't correct
+        self.depth - (current_generation - self.generation) as i8 * 2
+    }
+
+    pub fn null() -> TranspositionEntry<M> {
+        TranspositionEntry {
+            // state: INITIAL_STATE,
+            hash: StateHash(0),
+            value: 0,
+            entry_type: EntryType::Lower,
+            depth: 0,
+            generation: 0,
+            best_move: None,
+        }
+    }
+}
+
+
+#[derive(Eq, Copy, PartialEq, Clone, Debug, Hash)]
+pub struct StateHash(pub u64);
+
+pub const BUCKET_SIZE : usize = 4; // 24 x 4 = 96 bytes .... that's 3 cache lines ... my cache alignment sucks, i need to get to 16 bytes per entry
+pub const STATE_HASH_ZERO : StateHash = StateHash(0);
+
+use std::ops::BitXor;
+
+pub type Generation = u8;
+
+// below any depth searched, for states that aren't Game::transposable. the entry only suggests its move
+pub const ORDERING_ONLY_DEPTH : i8 = -1;
+
+pub fn stored_depth<G>(board: &G, state: &G::State, depth: Depth) -> Depth where G: Game {
+    if board.transposable(state) { depth } else { ORDERING_ONLY_DEPTH }
+}
+
+// a decided value is kept as plies from the entry's own position rather than the root, the same position comes up
+// at different plies
+pub fn to_tt_value(value: HeuristicValue, ply: usize) -> HeuristicValue {
+    match plies_to_end(value) {
+        Some(_) if value > 0 => value + ply as HeuristicValue,
+        Some(_) => value - ply as HeuristicValue,
+        None => value,
+    }
+}
+
+pub fn from_tt_value(value: HeuristicValue, ply: usize) -> HeuristicValue {
+    match plies_to_end(value) {
+        Some(_) if value > 0 => value - ply as HeuristicValue,
+        Some(_) => value + ply as HeuristicValue,
+        None => value,
+    }
+}
+
+impl BitXor for StateHash {
+    type Output = StateHash;
+
+    fn bitxor(self, other: StateHash) -> StateHash {
+        StateHash(self.0 ^ other.0)
+    }
+}
+
+#[derive(Clone)]
+pub struct TranspositionTable<M> {
+    pub generation : Generation,
+    pub bucket_mask: u64,
+    pub entries : Vec<TranspositionEntry<M>>,
+}
+
+impl<M> TranspositionTable<M> where M: Copy {
+    #[inline]
+    pub fn bucket_location_for(&self, hash:StateHash) -> usize {
+        ((hash.0 & self.bucket_mask) as usize) * BUCKET_SIZE
+    }
+
+    pub fn increment_generation(&mut self) {
+        self.generation = self.generation.wrapping_add(1)
+    }
+
+    pub fn probe(&self, hash:StateHash) -> (usize, bool) { // entry_location, found/match
+        let bucket_location = self.bucket_location_for(hash);
+
+        // look for null or same position
+        for i in 0..BUCKET_SIZE {
+            let entry_location = bucket_location + i;
+            let entry = &self.entries[entry_location];
+            let same_hash = entry.hash == hash;
+            if entry.hash.0 == 0 || same_hash {
+                return (entry_location, same_hash)
+            }
+        }
+
+        // ok nothing the same, let's replace the least valuable entry
+        let current_generation = self.generation;
+
+        let mut replace_idx : usize = bucket_location;
+        for i in 0..BUCKET_SIZE {
+            let entry_location = bucket_location + i;
+            if self.entries[replace_idx].value(current_generation) > self.entries[entry_location].value(current_generation) { // if current replacement slot is newer than existing
+                replace_idx = entry_location;
+            }
+        }
+
+        return (replace_idx, false)
+    }
+
+    pub fn store(&mut self, idx: usize, hash:StateHash, value:HeuristicValue, depth: i8, entry_type: EntryType, best_move: Option<M>) {
+        let entry = &mut self.entries[idx];
+
+        if entry.hash != hash || depth > (entry.depth - 4) || entry_type == EntryType::Exact {
+            entry.hash = hash;
+            entry.value = value;
+            entry.entry_type = entry_type;
+            entry.depth = depth;
+            entry.generation = self.generation;
+            entry.best_move = best_move;
+        }
+    }
+
+    pub fn size_bytes(&self) -> usize {
+        TranspositionTable::<M>::approx_size_bytes(self.entries.capacity())
+    }
+
+    pub fn approx_size_bytes(entry_count: usize) -> usize {
+        mem::size_of::<TranspositionEntry<M>>() * entry_count
+    }
+
+    pub fn reset(&mut self) {
+        self.generation = 0;
+        for i in 0..self.entries.len() {
+            self.entries[i] = TranspositionEntry::null();
+        }
+    }
+
+    pub fn new(power_of_two:usize) -> TranspositionTable<M> {
+        let size = 1 << power_of_two;
+        let mut bucket_mask : usize = 1;
+        for _ in 0..(power_of_two-1-2) { // -1 is normal, the -2 is for buckets
+            bucket_mask = bucket_mask | (bucket_mask << 1);
+        }
+
+        let mut entries = Vec::with_capacity(size);
+        entries.resize(size, TranspositionEntry::null());
+
+        TranspositionTable {
+            generation: 0,
+            bucket_mask: bucket_mask as u64,
+            entries: entries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table() {
+        let mut table : TranspositionTable<u16> = TranspositionTable::new(5); // 8 boxes in theory
+        println!("ok we have a table, entry count -> {:?}, mask -> {:b}", table.entries.len(), table.bucket_mask);
+
+
+
+
+        for i in 1..40 {
+            let hash = StateHash(i);
+            let (idx, found) = table.probe(hash);
+            println!("state {:?} -> idx {:?} found {:?}", i, idx, found);
+
+            table.store(idx, hash, 12, 4, EntryType::Exact, None);
+        }
+
+    }
+
+    #[test]
+    fn decided_values_by_ply() {
+        // a win 5 plies from the root, seen from 2 plies in, is 3 plies from where it's stored
+        assert_eq!(to_tt_value(victory_in(5), 2), victory_in(3));
+        assert_eq!(to_tt_value(-victory_in(5), 2), -victory_in(3));
+        // and read back 4 plies in it's 7 from the root
+        assert_eq!(from_tt_value(victory_in(3), 4), victory_in(7));
+        assert_eq!(from_tt_value(-victory_in(3), 4), -victory_in(7));
+
+        for &value in &[0, 12, -12, BEST, WORST] {
+            assert_eq!(to_tt_value(value, 3), value);
+            assert_eq!(from_tt_value(value, 3), value);
+        }
+    }
+}
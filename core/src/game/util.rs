@@ -9,9 +9,9 @@ pub struct Player(pub i8);
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct Slot(pub i8);
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct SlotTransform {
-    pub slots : [Slot; 25]
+    pub slots : Vec<Slot> // one per slot on the board, where it ends up
 }
 
 impl SlotTransform {
@@ -20,7 +20,16 @@ impl SlotTransform {
         for sl in &self.slots {
             slots.insert(*sl);
         }
-        slots.len() == 25
+        slots.len() == self.slots.len()
+    }
+
+    // puts every slot back where it came from
+    pub fn inverse(&self) -> SlotTransform {
+        let mut slots = vec![Slot(0); self.slots.len()];
+        for (i, sl) in self.slots.iter().enumerate() {
+            slots[sl.0 as usize] = Slot(i as i8);
+        }
+        SlotTransform { slots: slots }
     }
 }
 
@@ -30,8 +39,6 @@ pub trait Packed where Self: std::marker::Sized {
     fn set(&mut self, slot: Slot, value: u8);
 }
 
-pub const EMPTY_SLOT_TRANSFORM : SlotTransform = SlotTransform { slots: [Slot(0) ; 25] };
-
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Position {
     pub x : i8,
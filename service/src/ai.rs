@@ -8,18 +8,27 @@ use std::sync::mpsc::{Sender, Receiver};
 use std::thread;
 use std::thread::JoinHandle;
 
-use aphid::{contains, Milliseconds};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::cell::RefCell;
+
+use aphid::contains;
+
+use std::fmt;
+
+use time;
 
 pub struct AIService {
     send: Sender<Request>,
     pub receive: Receiver<StateAnalysis>,
     join_handle: JoinHandle<()>,
+    stop: RefCell<Arc<AtomicBool>>, // the latest request's, each analysis gets its own so stopping one can't be undone by another
 }
 
 #[derive(Clone)]
 pub enum Request {
     Reset,
-    Analysis { state: State, ai_profile: AIProfile },
+    Analysis { state: State, ai_profile: AIProfile, stop: Arc<AtomicBool> },
     Shutdown,
 }
 
@@ -28,8 +37,42 @@ pub struct StateAnalysis {
     pub state: State,
     pub depth: Depth,
     pub best_move: Option<(Move, HeuristicValue)>,
+    pub line: Vec<(Move, HeuristicValue)>, // what the ai expects to be played from here, best_move first
     pub terminal: bool, 
-    pub rollback : bool, // rollback means we discovered we will lose guaranteed ... so we resort to the prior depth to discovering that ... so we still make a reasonable move
+}
+
+// how a decided analysis ends for the side to move, in their own turns
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Forecast {
+    WinIn(usize),
+    LossIn(usize),
+}
+
+impl fmt::Display for Forecast {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Forecast::WinIn(turns) => write!(f, "win in {}", turns),
+            Forecast::LossIn(turns) => write!(f, "loss in {}", turns),
+        }
+    }
+}
+
+impl StateAnalysis {
+    pub fn forecast(&self) -> Option<Forecast> {
+        let score = match self.best_move {
+            Some((_, score)) => score,
+            None => return None,
+        };
+        // plies count both sides, the last is the winner's
+        plies_to_end(score).map(|plies| {
+            let turns = (plies + 1) / 2;
+            if AIService::winning_player(score) == Some(self.state.to_move) {
+                Forecast::WinIn(turns)
+            } else {
+                Forecast::LossIn(turns)
+            }
+        })
+    }
 }
 
 impl AIService {
@@ -45,22 +88,26 @@ impl AIService {
 
             let board = StandardBoard::new(ZobristHash::new_unseeded());
 
-            let mut evaluator_state = NegaMaxAlphaBetaExp::new_state();
+            let mut evaluator_state : EvState<Move> = EvState::extending_threats();
 
             while let Some(event) = ai_rx.recv().ok() {
                 match event {
                     Reset => {
-                        NegaMaxAlphaBetaExp::reset(&mut evaluator_state);
+                        <NegaMaxAlphaBetaExp as Evaluator<StandardBoard>>::reset(&mut evaluator_state);
                     },
-                    Analysis { state, ai_profile } => {
+                    Analysis { state, ai_profile, stop } => {
                         println!("Starting analysis with ai_profile -> {:?}", ai_profile);
+                        let mut limits = SearchLimits::none().stopped_by(stop);
+                        if let Some(time_limit) = ai_profile.time_limit {
+                            limits = limits.time(time_limit);
+                        }
                         match ai_profile.heuristic {
                             HeuristicName::Simple => 
-                                AIService::evaluate::<NegaMaxAlphaBetaExp, SimpleHeightHeuristic>(&mut evaluator_state, &board, &state, ai_profile.depth, ai_profile.time_limit, &ai_tx),
+                                AIService::evaluate::<NegaMaxAlphaBetaExp, SimpleHeightHeuristic>(&mut evaluator_state, &board, &state, ai_profile.depth, &limits, &ai_tx),
                             HeuristicName::Neighbour => 
-                                AIService::evaluate::<NegaMaxAlphaBetaExp, NeighbourHeuristic>(&mut evaluator_state, &board, &state, ai_profile.depth, ai_profile.time_limit, &ai_tx),
+                                AIService::evaluate::<NegaMaxAlphaBetaExp, NeighbourHeuristic>(&mut evaluator_state, &board, &state, ai_profile.depth, &limits, &ai_tx),
                             HeuristicName::AdjustedNeighbour => 
-                                AIService::evaluate::<NegaMaxAlphaBetaExp, AdjustedNeighbourHeuristic>(&mut evaluator_state, &board, &state, ai_profile.depth, ai_profile.time_limit, &ai_tx),
+                                AIService::evaluate::<NegaMaxAlphaBetaExp, AdjustedNeighbourHeuristic>(&mut evaluator_state, &board, &state, ai_profile.depth, &limits, &ai_tx),
                         }
                     },
                     Shutdown => {
@@ -75,93 +122,120 @@ impl AIService {
             send: main_tx,
             receive: main_rx,
             join_handle: join_handle,
+            stop: RefCell::new(Arc::new(AtomicBool::new(false))),
         }
     }
 
+    // however far off the win is
     pub fn winning_player(heuristic_value:HeuristicValue) -> Option<Player> {
-        if heuristic_value == PLAYER_0_WIN {
+        if !decided(heuristic_value) {
+            None
+        } else if heuristic_value > 0 {
             Some(Player(0))
-        } else if heuristic_value == PLAYER_1_WIN {
-            Some(Player(1))
         } else {
-            None
+            Some(Player(1))
         }
     }
 
-    pub fn evaluate<E, H>(evaluator_state: &mut E::EvaluatorState, board: &StandardBoard, state:&State, max_depth:Depth, time_limit: Option<Milliseconds>, send: &Sender<StateAnalysis>) where E: Evaluator, H: Heuristic {
+    pub fn line_notation(board: &StandardBoard, line: &[(Move, HeuristicValue)]) -> String {
+        line.iter().map(|&(mve, score)| format!("{} ({})", board.move_notation(mve), score)).collect::<Vec<_>>().join(" ")
+    }
 
-        E::new_search(evaluator_state);
+    pub fn evaluate<E, H>(evaluator_state: &mut E::EvaluatorState, board: &StandardBoard, state:&State, max_depth:Depth, limits: &SearchLimits, send: &Sender<StateAnalysis>) where E: Evaluator<StandardBoard>, H: Heuristic<StandardBoard> {
+
+        if let Outcome::Won { player, reason } = board.outcome(state, None) {
+            println!("AI :: nothing to analyse, {:?} has already won by {:?}", player, reason);
+            send.send(StateAnalysis {
+                state: state.clone(),
+                depth: 0,
+                best_move: None,
+                line: Vec::new(),
+                terminal: true,
+            }).unwrap();
+            return;
+        }
 
         let score = H::evaluate(board, state);
-        println!("AI :: Asked for analysis max depth {:?} time limit {:?}, current score {:?} with {:?} to move", max_depth, time_limit, score, state.to_move);
-        
-        for depth in 1..(max_depth+1) {
-            let (best_move, info) = E::evaluate_moves::<H>(evaluator_state, board, state, depth);  
+        println!("AI :: Asked for analysis max depth {:?} deadline {:?}, current score {:?} with {:?} to move", max_depth, limits.deadline, score, state.to_move);
+
+        let mut line = Vec::new();
+
+        let (best_move, depth, info) = search_reporting::<StandardBoard, E, H, _>(evaluator_state, board, state, max_depth, limits, |evaluator_state, depth, best_move, info| {
+            line = E::principal_variation(evaluator_state, board, state, depth);
+            println!("AI :: depth {:?} expected line {}", depth, AIService::line_notation(board, &line));
 
             let best_move_score = best_move.map(|(_, score)| score);
             let winning_player = best_move_score.and_then(|score| AIService::winning_player(score));
 
-            println!("AI :: depth {:?} info {:?} best_move -> {:?}", depth, info, best_move);
+            let best_move_notation = best_move.map(|(mve, score)| format!("{} ({})", board.move_notation(mve), score));
+            println!("AI :: depth {:?} info {:?} best_move -> {:?}", depth, info, best_move_notation);
+
+            let mut analysis = StateAnalysis {
+                state: state.clone(),
+                depth: depth,
+                best_move: best_move,
+                line: line.clone(),
+                terminal: true,
+            };
 
             if let Some(player) = winning_player {
-                println!("AI :: at depth {:?} we've established winning player will be {:?}", depth, player);
-
-                if player != state.to_move && depth > 0 {
-                    println!("AI :: we've lost, and we've got a rollback state, rolling back, performing sample playout");
-                    // playout::<E, H>(board, state, depth);
-                    send.send(StateAnalysis {
-                        state: state.clone(),
-                        depth: depth,
-                        best_move: best_move,
-                        terminal: true, 
-                        rollback: true,
-                    }).unwrap();
-                } else {
-                    send.send(StateAnalysis {
-                        state: state.clone(),
-                        depth: depth,
-                        best_move: best_move,
-                        terminal: true, 
-                        rollback: false,
-                    }).unwrap();
-                }
-                break;
+                // the nearest win turns up at the first depth that sees it, and a lost side already puts it off
+                // as long as it can, so searching deeper won't change the move
+                println!("AI :: at depth {:?} we've established winning player will be {:?}, {}", depth, player, analysis.forecast().map(|forecast| forecast.to_string()).unwrap_or("no distance".into()));
             } else {
-                println!("info -> {:?}", info);
-                let next_timing_calc = (info.time * (info.average_branch_factor() as f64) * 1000.0) as u64; 
-                println!("we're at depth {} time was {:.3} next timing calc is {:.3}", depth, info.time, next_timing_calc);
-                let timing_bad = info.move_count > 1_000_000 && contains(time_limit, |&tl| next_timing_calc > tl);
-                let terminate = depth >= 2 && (depth >= max_depth || timing_bad);
-                println!("depth is {:?} max {} terminate? {:?}", depth, max_depth, terminate);
-                send.send(StateAnalysis {
-                    state: state.clone(),
-                    depth: depth,
-                    best_move: best_move,
-                    terminal: terminate, 
-                    rollback: false,
-                }).unwrap();
-                if terminate {
-                    break;
-                }
+                // the deadline stops a depth that runs over, this just saves starting one that's hopeless
+                let next_timing_calc = (info.time * (info.average_branch_factor() as f64) * 1_000_000_000.0) as u64;
+                println!("we're at depth {} time was {:.3} next timing calc is {}ns", depth, info.time, next_timing_calc);
+                let timing_bad = info.move_count > 1_000_000 && contains(limits.deadline, |&deadline| time::precise_time_ns() + next_timing_calc > deadline);
+                analysis.terminal = depth >= max_depth || (depth >= 2 && timing_bad);
+                println!("depth is {:?} max {} terminate? {:?}", depth, max_depth, analysis.terminal);
             }
+
+            let carry_on = !analysis.terminal;
+            send.send(analysis).unwrap();
+            carry_on
+        });
+
+        if info.aborted {
+            // search_reporting falls back to the last completed depth, or the partial first one if that's all there is
+            println!("AI :: depth {:?} aborted after {:.3}s, falling back to {:?}", depth + 1, info.time, best_move.map(|(mve, score)| format!("{} ({})", board.move_notation(mve), score)));
+            send.send(StateAnalysis {
+                state: state.clone(),
+                depth: depth,
+                best_move: best_move,
+                line: if depth > 0 { line } else { best_move.into_iter().collect() },
+                terminal: true,
+            }).unwrap();
         }
 
         println!("AI :: Evaluation over");
     }
 
+    // gives up on whatever's being analysed, it finishes with the last completed depth's move
+    pub fn stop_analysis(&self) {
+        self.stop.borrow().store(true, Ordering::Relaxed);
+    }
+
+    // anything still being analysed (or queued) is for an old state, so it's stopped first
     pub fn request_analysis(&self, state: State, ai_profile: AIProfile) {
+        self.stop_analysis();
+        let stop = Arc::new(AtomicBool::new(false));
+        *self.stop.borrow_mut() = stop.clone();
         let request = Request::Analysis {
             state: state,
             ai_profile: ai_profile,
+            stop: stop,
         };
         self.send.send(request).expect("can send analysis request to ai worker");
     }
 
     pub fn reset(&self) {
+        self.stop_analysis();
         self.send.send(Request::Reset).expect("that i can send a reset");
     }
 
     pub fn shutdown(self) {
+        self.stop_analysis();
         self.send.send(Request::Shutdown).expect("can send shutdown to ai worker");
         self.join_handle.join().unwrap();
     }
@@ -1,4 +1,4 @@
-use tavern_core::game::santorini::{Move, State};
+use tavern_core::game::santorini::{Move, State, StandardBoard, NotationError, GameRecord};
 
 
 // it's it's own thing because we're gonna ship it across the wire as essential state
@@ -16,4 +16,37 @@ impl BoardState {
             state,
         }
     }
+
+    // space separated, e.g. "a1,b3 c1,d4 a1a2b1"
+    pub fn notation(&self, board: &StandardBoard) -> String {
+        let moves : Vec<String> = self.moves.iter().map(|&mve| board.move_notation(mve)).collect();
+        moves.join(" ")
+    }
+
+    // replays from initial, every move has to be legal when it's played
+    pub fn replay(board: &StandardBoard, initial: State, notation: &str) -> Result<BoardState, NotationError> {
+        let mut board_state = BoardState::new(initial);
+        for text in notation.split_whitespace() {
+            let mve = board.parse_legal_move(&board_state.state, text)?;
+            board_state.state = board.apply(mve, &board_state.state);
+            board_state.moves.push(mve);
+        }
+        Ok(board_state)
+    }
+
+    // metadata is left for whoever knows it, the replay doesn't keep where it started
+    pub fn to_record(&self, initial: State, players: [String; 2]) -> GameRecord {
+        let mut record = GameRecord::new(players, initial);
+        for &mve in &self.moves {
+            record.push(mve);
+        }
+        record
+    }
+
+    pub fn from_record(board: &StandardBoard, record: &GameRecord) -> BoardState {
+        BoardState {
+            moves: record.moves.iter().map(|recorded| recorded.mve).collect(),
+            state: record.final_state(board),
+        }
+    }
 }
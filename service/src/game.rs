@@ -1,6 +1,6 @@
 
 use tavern_core::{Slot};
-use tavern_core::game::santorini::{Move, State, StandardBoard, AIProfile, Depth, HeuristicName};
+use tavern_core::game::santorini::{Move, State, StandardBoard, AIProfile, Depth, HeuristicName, MoveError, Outcome};
 use aphid::{Milliseconds};
 
 
@@ -151,30 +151,27 @@ impl BoardWithMoves { /// ARRRRGH java style encapsulation to ensure legal_moves
         }
     }
 
-    pub fn make_move(&mut self, board:&StandardBoard, mve:Move) {
+    pub fn make_move(&mut self, board:&StandardBoard, mve:Move) -> Result<(), MoveError> {
         if !self.legal_moves.contains(&mve) {
-            panic!("MOVE WASNT VALID -> {:?}", mve);
+            return Err(board.explain_illegal(&self.state.state, mve));
         }
         self.state.moves.push(mve);
         self.state.state = board.apply(mve, &self.state.state);
         let mut next_moves = Vec::new();
         board.next_moves_for_player(&self.state.state, &mut next_moves);
         self.legal_moves = next_moves;
+        Ok(())
     }
 
-    pub fn match_status(&self, standard_board:&StandardBoard) -> MatchStatus {
-        // handle winning case
-        if let Some(&last_move) = self.state.moves.last() {
-            if standard_board.ascension_winning_move(&self.state.state, last_move) {
-                return MatchStatus::Won(self.state.state.next_player());
-            }
-        }
+    pub fn outcome(&self, standard_board:&StandardBoard) -> Outcome {
+        standard_board.outcome(&self.state.state, self.state.moves.last().cloned())
+    }
 
-        if self.legal_moves.is_empty() {
-            return MatchStatus::Won(self.state.state.next_player())
+    pub fn match_status(&self, standard_board:&StandardBoard) -> MatchStatus {
+        match self.outcome(standard_board) {
+            Outcome::Won { player, .. } => MatchStatus::Won(player),
+            Outcome::Ongoing => MatchStatus::ToMove(self.state.state.to_move),
         }
-
-        MatchStatus::ToMove(self.state.state.to_move)
     }
 }
 